@@ -0,0 +1,19 @@
+use dynamixel_driver::FramedSerialDriver;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt()]
+pub struct Args {
+    #[structopt(about = "Serial port to use")]
+    pub port: String,
+    #[structopt(about = "Unix socket to serve the bus on")]
+    pub socket: String,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::from_args();
+    let transport = FramedSerialDriver::new(&args.port)?;
+    dynamixel_driver::daemon::serve(Box::new(transport), &args.socket).await?;
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt()]
+pub struct Args {
+    #[structopt(about = "Serial port to use")]
+    pub port: String,
+    #[structopt(long, default_value = "1", help = "Servo id")]
+    pub id: u8,
+    #[structopt(
+        long,
+        default_value = "5",
+        help = "How long to identify the servo in seconds"
+    )]
+    pub seconds: u64,
+    #[structopt(long, help = "Wiggle the servo instead of blinking its LED")]
+    pub wiggle: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::from_args();
+    let mut driver = dynamixel_driver::DynamixelDriver::new(&args.port)?;
+    let duration = Duration::from_secs(args.seconds);
+    if args.wiggle {
+        driver.identify_by_wiggle(args.id, duration).await?;
+    } else {
+        driver.identify(args.id, duration).await?;
+    }
+    Ok(())
+}
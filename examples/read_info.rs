@@ -11,15 +11,28 @@ pub struct Args {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::from_args();
     let mut driver = dynamixel_driver::DynamixelDriver::new(&args.port)?;
-    for i in 0..20 {
-        if driver.ping(i).await.is_ok() {
-            println!("Servo id: {}", i);
-            if let Ok(temperature) = driver.read_temperature(i).await {
-                println!("   temperature of {}", temperature);
-            }
-            if let Ok(position) = driver.read_position_degrees(i).await {
-                println!("   position degrees of {}", position);
-            }
+    let mut ids = vec![];
+    for id in 0..20 {
+        if driver.ping(id).await.is_ok() {
+            ids.push(id);
+        }
+    }
+    let temperatures = driver.read_temperatures(&ids).await;
+    let voltages = driver.read_voltages(&ids).await;
+    let positions = driver.read_positions_degrees(&ids).await;
+    for ((id, temperature), ((_, voltage), (_, position))) in temperatures
+        .into_iter()
+        .zip(voltages.into_iter().zip(positions))
+    {
+        println!("Servo id: {}", id);
+        if let Ok(temperature) = temperature {
+            println!("   temperature of {}", temperature);
+        }
+        if let Ok(voltage) = voltage {
+            println!("   voltage of {}", voltage);
+        }
+        if let Ok(position) = position {
+            println!("   position degrees of {}", position);
         }
     }
     Ok(())
@@ -11,9 +11,10 @@ pub struct Args {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::from_args();
     let mut driver = dynamixel_driver::DynamixelDriver::new(&args.port)?;
-    for i in 0..20 {
-        if let Ok(temperature) = driver.read_temperature(i).await {
-            println!("servo {} has temperature of {}", i, temperature);
+    let ids: Vec<u8> = (0..20).collect();
+    for (id, temperature) in driver.read_temperatures(&ids).await {
+        if let Ok(temperature) = temperature {
+            println!("servo {} has temperature of {}", id, temperature);
         }
     }
     Ok(())
@@ -1,4 +1,5 @@
 use dynamixel_driver::DynamixelDriver;
+use std::time::Duration;
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -22,18 +23,12 @@ async fn main() -> anyhow::Result<()> {
 async fn do_loop(driver: &mut DynamixelDriver) -> anyhow::Result<()> {
     loop {
         driver.write_position_degrees(1, 100.0).await?;
-        loop {
-            let pos = driver.read_position_degrees(1).await?;
-            if pos < 101.0 {
-                break;
-            }
-        }
+        driver
+            .wait_until_reached(1, 100.0, 1.0, Duration::from_secs(5))
+            .await?;
         driver.write_position_degrees(1, 200.0).await?;
-        loop {
-            let pos = driver.read_position_degrees(1).await?;
-            if pos > 199.0 {
-                break;
-            }
-        }
+        driver
+            .wait_until_reached(1, 200.0, 1.0, Duration::from_secs(5))
+            .await?;
     }
 }
@@ -5,12 +5,25 @@ use structopt::StructOpt;
 pub struct Args {
     #[structopt(about = "Serial port to use")]
     pub port: String,
+    #[structopt(long, default_value = "2", help = "Current servo id")]
+    pub id: u8,
+    #[structopt(long, default_value = "1", help = "New servo id")]
+    pub new_id: u8,
+    #[structopt(long, help = "Write the id even if another servo already uses it")]
+    pub force: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::from_args();
     let mut driver = dynamixel_driver::DynamixelDriver::new(&args.port)?;
-    driver.write_id(2, 1).await.unwrap();
+    if args.force {
+        driver.force_write_id(args.id, args.new_id).await?;
+    } else {
+        let change = driver.write_id(args.id, args.new_id).await?;
+        if !change.verified {
+            println!("servo doesn't answer at its new id {}", change.new_id);
+        }
+    }
     Ok(())
 }
@@ -1,6 +1,6 @@
-use std::time::Instant;
+use dynamixel_driver::SinusoidalSweep;
 use structopt::StructOpt;
-use tokio::time::{sleep, Duration};
+use tokio::time::Duration;
 
 #[derive(StructOpt)]
 #[structopt()]
@@ -13,13 +13,15 @@ pub struct Args {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::from_args();
 
-    let start = Instant::now();
     let mut driver = dynamixel_driver::DynamixelDriver::new(&args.port)?;
+    driver.set_profile_update_interval(Duration::from_millis(10));
 
-    loop {
-        sleep(Duration::from_millis(10)).await;
-        driver
-            .write_position_degrees(1, (start.elapsed().as_secs_f32()).sin() * 90.0 + 150.0)
-            .await?;
-    }
+    let sweep = SinusoidalSweep::new(
+        vec![1],
+        150.0,
+        90.0,
+        Duration::from_secs_f32(std::f32::consts::TAU),
+    );
+    driver.run_primitive(&sweep).await?;
+    Ok(())
 }
@@ -0,0 +1,45 @@
+use std::time::Duration;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt()]
+pub struct Args {
+    #[structopt(about = "Serial port to use")]
+    pub port: String,
+    #[structopt(long, default_value = "1", help = "Servo id")]
+    pub id: u8,
+    #[structopt(long, default_value = "1000000", help = "Baud rate")]
+    pub baud_rate: u32,
+    #[structopt(long, default_value = "10", help = "Test duration in seconds")]
+    pub seconds: u64,
+    #[structopt(long, default_value = "8", help = "Bytes per read and write")]
+    pub payload_size: u8,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::from_args();
+    let mut driver = dynamixel_driver::DynamixelDriver::with_baud_rate(&args.port, args.baud_rate)?;
+    let report = driver
+        .stress_test(
+            args.id,
+            Duration::from_secs(args.seconds),
+            args.payload_size,
+        )
+        .await?;
+    println!(
+        "{} transactions at {} baud, {:.3}% failed ({} timeouts, {} checksum errors, {} other)",
+        report.transactions,
+        args.baud_rate,
+        report.error_rate() * 100.0,
+        report.timeouts,
+        report.checksum_errors,
+        report.other_errors
+    );
+    for percentile in [50.0, 95.0, 99.0, 100.0] {
+        if let Some(latency) = report.latency_percentile(percentile) {
+            println!("p{}: {:?}", percentile, latency);
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::{thread::sleep, time::Duration};
+use std::time::Duration;
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -14,7 +14,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut driver = dynamixel_driver::DynamixelDriver::new(&args.port)?;
     let commands = vec![(1, 1023), (2, 1023)];
     driver.sync_write_position(commands).await?;
-    sleep(Duration::from_secs(2));
+    driver.wait_until_stopped(1, Duration::from_secs(5)).await?;
+    driver.wait_until_stopped(2, Duration::from_secs(5)).await?;
     let commands = vec![(1, 0), (2, 0)];
     driver.sync_write_position(commands).await?;
     Ok(())
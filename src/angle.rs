@@ -0,0 +1,127 @@
+use crate::model::ServoModel;
+
+/// Servo or joint angle, converted to and from degrees, radians and position ticks
+///
+/// Conversion to ticks rounds to the nearest tick and fails for angles outside the range
+/// of the model instead of wrapping, 0 ticks is 0 degrees.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Angle {
+    degrees: f32,
+}
+
+impl Angle {
+    pub fn from_degrees(degrees: f32) -> Angle {
+        Angle { degrees }
+    }
+
+    pub fn from_radians(radians: f32) -> Angle {
+        Angle::from_degrees(radians.to_degrees())
+    }
+
+    pub fn from_ticks(ticks: u16, model: ServoModel) -> Angle {
+        Angle::from_degrees(ticks as f32 / model.steps_per_degree())
+    }
+
+    pub fn degrees(self) -> f32 {
+        self.degrees
+    }
+
+    pub fn radians(self) -> f32 {
+        self.degrees.to_radians()
+    }
+
+    /// Nearest position tick of `model`, None for angles outside its range
+    pub fn to_ticks(self, model: ServoModel) -> Option<u16> {
+        let ticks = (self.degrees * model.steps_per_degree()).round();
+        // also rejects NaN
+        if (0.0..=model.max_position() as f32).contains(&ticks) {
+            Some(ticks as u16)
+        } else {
+            None
+        }
+    }
+
+    /// Nearest position tick of `model`, limited to its range
+    pub fn to_ticks_saturating(self, model: ServoModel) -> u16 {
+        let ticks = (self.degrees * model.steps_per_degree()).round();
+        if ticks.is_nan() {
+            return 0;
+        }
+        ticks.clamp(0.0, model.max_position() as f32) as u16
+    }
+}
+
+impl std::ops::Add for Angle {
+    type Output = Angle;
+
+    fn add(self, other: Angle) -> Angle {
+        Angle::from_degrees(self.degrees + other.degrees)
+    }
+}
+
+impl std::ops::Sub for Angle {
+    type Output = Angle;
+
+    fn sub(self, other: Angle) -> Angle {
+        Angle::from_degrees(self.degrees - other.degrees)
+    }
+}
+
+impl std::ops::Neg for Angle {
+    type Output = Angle;
+
+    fn neg(self) -> Angle {
+        Angle::from_degrees(-self.degrees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_round_to_nearest() {
+        // 511.5 ticks
+        assert_eq!(
+            Angle::from_degrees(150.0).to_ticks(ServoModel::Ax12A),
+            Some(512)
+        );
+        assert_eq!(
+            Angle::from_degrees(149.9).to_ticks(ServoModel::Ax12A),
+            Some(511)
+        );
+        assert_eq!(
+            Angle::from_degrees(90.0).to_ticks(ServoModel::Mx28),
+            Some(1024)
+        );
+        assert_eq!(Angle::from_ticks(341, ServoModel::Ax12A).degrees(), 100.0);
+        assert!((Angle::from_radians(std::f32::consts::PI).degrees() - 180.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn out_of_range_angles_are_rejected() {
+        assert_eq!(Angle::from_degrees(-1.0).to_ticks(ServoModel::Ax12A), None);
+        assert_eq!(Angle::from_degrees(301.0).to_ticks(ServoModel::Ax12A), None);
+        assert_eq!(
+            Angle::from_degrees(301.0).to_ticks(ServoModel::Mx28),
+            Some(3425)
+        );
+        assert_eq!(
+            Angle::from_degrees(f32::NAN).to_ticks(ServoModel::Mx28),
+            None
+        );
+        // rounding into range is accepted
+        assert_eq!(
+            Angle::from_degrees(-0.1).to_ticks(ServoModel::Ax12A),
+            Some(0)
+        );
+        assert_eq!(
+            Angle::from_degrees(-20.0).to_ticks_saturating(ServoModel::Ax12A),
+            0
+        );
+        assert_eq!(
+            Angle::from_degrees(400.0).to_ticks_saturating(ServoModel::Ax12A),
+            1023
+        );
+    }
+}
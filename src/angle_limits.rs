@@ -0,0 +1,75 @@
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+use crate::instructions::{DynamixelDriverError, Result};
+
+/// What the driver does with goal positions outside the angle limits of a servo, see
+/// [`crate::DynamixelDriver::read_angle_limits`]
+///
+/// The servo itself answers such goals with an angle limit error and doesn't move.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AngleLimitPolicy {
+    /// Send goals unchanged
+    #[default]
+    Ignore,
+    /// Fail with [`DynamixelDriverError::AngleLimitExceeded`] before sending
+    Reject,
+    /// Move goals to the nearest limit
+    Clamp,
+}
+
+/// CW and CCW angle limit registers of a servo in position steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AngleLimits {
+    pub cw: u16,
+    pub ccw: u16,
+}
+
+impl AngleLimits {
+    /// Both limits at zero put the servo in wheel mode, which ignores goal positions
+    pub fn is_wheel_mode(&self) -> bool {
+        self.cw == 0 && self.ccw == 0
+    }
+
+    pub(crate) fn apply(&self, id: u8, goal: u16, policy: AngleLimitPolicy) -> Result<u16> {
+        if self.is_wheel_mode() || (self.cw..=self.ccw).contains(&goal) {
+            return Ok(goal);
+        }
+        match policy {
+            AngleLimitPolicy::Ignore => Ok(goal),
+            AngleLimitPolicy::Reject => Err(DynamixelDriverError::AngleLimitExceeded {
+                id,
+                goal,
+                cw: self.cw,
+                ccw: self.ccw,
+            }),
+            AngleLimitPolicy::Clamp => Ok(goal.clamp(self.cw, self.ccw.max(self.cw))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goals_outside_limits_are_clamped_or_rejected() {
+        let limits = AngleLimits { cw: 200, ccw: 800 };
+        assert_eq!(limits.apply(1, 500, AngleLimitPolicy::Reject).unwrap(), 500);
+        assert_eq!(limits.apply(1, 900, AngleLimitPolicy::Ignore).unwrap(), 900);
+        assert_eq!(limits.apply(1, 900, AngleLimitPolicy::Clamp).unwrap(), 800);
+        assert_eq!(limits.apply(1, 100, AngleLimitPolicy::Clamp).unwrap(), 200);
+        assert!(matches!(
+            limits.apply(1, 100, AngleLimitPolicy::Reject),
+            Err(DynamixelDriverError::AngleLimitExceeded {
+                id: 1,
+                goal: 100,
+                ..
+            })
+        ));
+        let wheel = AngleLimits { cw: 0, ccw: 0 };
+        assert_eq!(wheel.apply(1, 900, AngleLimitPolicy::Reject).unwrap(), 900);
+    }
+}
@@ -0,0 +1,232 @@
+//! Typed sync write builders, each accepting only values that fit the register it writes.
+//!
+//! ```no_run
+//! # async fn example(driver: &mut dynamixel_driver::DynamixelDriver) -> Result<(), dynamixel_driver::DynamixelDriverError> {
+//! use dynamixel_driver::{Degrees, GoalPositionBatch, Ticks};
+//!
+//! let batch = GoalPositionBatch::new().add(1, Degrees(90.0)).add(2, Ticks(512));
+//! driver.sync_write_batch(batch).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::angle::Angle;
+use crate::{GOAL_POSITION, MOVING_SPEED, TORQUE_ENABLED};
+
+/// Raw register value in servo units
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ticks(pub u16);
+
+/// Joint angle with the calibration of the servo applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Degrees(pub f32);
+
+/// Revolutions per minute
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rpm(pub f32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PositionValue {
+    Degrees(f32),
+    Ticks(u16),
+}
+
+impl From<Degrees> for PositionValue {
+    fn from(Degrees(degrees): Degrees) -> Self {
+        PositionValue::Degrees(degrees)
+    }
+}
+
+impl From<Angle> for PositionValue {
+    fn from(angle: Angle) -> Self {
+        PositionValue::Degrees(angle.degrees())
+    }
+}
+
+impl From<Ticks> for PositionValue {
+    fn from(Ticks(ticks): Ticks) -> Self {
+        PositionValue::Ticks(ticks)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpeedValue {
+    Rpm(f32),
+    Ticks(u16),
+}
+
+impl From<Rpm> for SpeedValue {
+    fn from(Rpm(rpm): Rpm) -> Self {
+        SpeedValue::Rpm(rpm)
+    }
+}
+
+impl From<Ticks> for SpeedValue {
+    fn from(Ticks(ticks): Ticks) -> Self {
+        SpeedValue::Ticks(ticks)
+    }
+}
+
+/// Value of one servo in a [`SyncBatch`] before it's converted to servo units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BatchValue {
+    Raw(u32),
+    /// Joint degrees, converted with the calibration and model of the servo
+    Degrees(f32),
+}
+
+/// Sync write of one register to several servos, built from one of the typed batches
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncBatch {
+    pub(crate) addr: u8,
+    pub(crate) data_len: u8,
+    pub(crate) entries: Vec<(u8, BatchValue)>,
+}
+
+fn insert<T>(entries: &mut Vec<(u8, T)>, id: u8, value: T) {
+    // adding a servo twice keeps the last value instead of sending two entries for it
+    match entries.iter_mut().find(|(entry, _)| *entry == id) {
+        Some(entry) => entry.1 = value,
+        None => entries.push((id, value)),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GoalPositionBatch {
+    entries: Vec<(u8, PositionValue)>,
+}
+
+impl GoalPositionBatch {
+    pub fn new() -> GoalPositionBatch {
+        GoalPositionBatch::default()
+    }
+
+    pub fn add(mut self, id: u8, position: impl Into<PositionValue>) -> Self {
+        insert(&mut self.entries, id, position.into());
+        self
+    }
+}
+
+impl From<GoalPositionBatch> for SyncBatch {
+    fn from(batch: GoalPositionBatch) -> Self {
+        let entries = batch
+            .entries
+            .into_iter()
+            .map(|(id, position)| match position {
+                PositionValue::Degrees(degrees) => (id, BatchValue::Degrees(degrees)),
+                PositionValue::Ticks(ticks) => (id, BatchValue::Raw(ticks.into())),
+            })
+            .collect();
+        SyncBatch {
+            addr: GOAL_POSITION,
+            data_len: 2,
+            entries,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MovingSpeedBatch {
+    entries: Vec<(u8, SpeedValue)>,
+}
+
+impl MovingSpeedBatch {
+    pub fn new() -> MovingSpeedBatch {
+        MovingSpeedBatch::default()
+    }
+
+    /// A speed of `Rpm(0.0)` is the slowest controlled speed, only `Ticks(0)` selects the
+    /// uncontrolled maximum speed
+    pub fn add(mut self, id: u8, speed: impl Into<SpeedValue>) -> Self {
+        insert(&mut self.entries, id, speed.into());
+        self
+    }
+}
+
+// speed units are about 0.111 rpm
+fn rpm_to_raw(rpm: f32) -> u16 {
+    (rpm.abs() / 0.111).round().clamp(1.0, 1023.0) as u16
+}
+
+impl From<MovingSpeedBatch> for SyncBatch {
+    fn from(batch: MovingSpeedBatch) -> Self {
+        let entries = batch
+            .entries
+            .into_iter()
+            .map(|(id, speed)| {
+                let raw = match speed {
+                    SpeedValue::Rpm(rpm) => rpm_to_raw(rpm),
+                    SpeedValue::Ticks(ticks) => ticks,
+                };
+                (id, BatchValue::Raw(raw.into()))
+            })
+            .collect();
+        SyncBatch {
+            addr: MOVING_SPEED,
+            data_len: 2,
+            entries,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TorqueEnableBatch {
+    entries: Vec<(u8, bool)>,
+}
+
+impl TorqueEnableBatch {
+    pub fn new() -> TorqueEnableBatch {
+        TorqueEnableBatch::default()
+    }
+
+    pub fn add(mut self, id: u8, enabled: bool) -> Self {
+        insert(&mut self.entries, id, enabled);
+        self
+    }
+}
+
+impl From<TorqueEnableBatch> for SyncBatch {
+    fn from(batch: TorqueEnableBatch) -> Self {
+        let entries = batch
+            .entries
+            .into_iter()
+            .map(|(id, enabled)| (id, BatchValue::Raw(enabled.into())))
+            .collect();
+        SyncBatch {
+            addr: TORQUE_ENABLED,
+            data_len: 1,
+            entries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_target_their_register() {
+        let batch: SyncBatch = MovingSpeedBatch::new()
+            .add(1, Rpm(11.1))
+            .add(2, Ticks(0))
+            .add(3, Rpm(0.0))
+            .add(1, Rpm(-22.2))
+            .into();
+        assert_eq!(batch.addr, MOVING_SPEED);
+        assert_eq!(batch.data_len, 2);
+        assert_eq!(
+            batch.entries,
+            vec![
+                (1, BatchValue::Raw(200)),
+                (2, BatchValue::Raw(0)),
+                (3, BatchValue::Raw(1)),
+            ]
+        );
+        let batch: SyncBatch = TorqueEnableBatch::new().add(4, true).add(5, false).into();
+        assert_eq!((batch.addr, batch.data_len), (TORQUE_ENABLED, 1));
+        assert_eq!(
+            batch.entries,
+            vec![(4, BatchValue::Raw(1)), (5, BatchValue::Raw(0))]
+        );
+    }
+}
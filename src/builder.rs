@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use tokio_serial::{FlowControl, Parity, StopBits};
+
+use crate::instructions::Result;
+use crate::protocol::Protocol;
+use crate::serial_driver::{FramedSerialDriver, ReceiveTimeout, SerialSettings};
+use crate::DynamixelDriver;
+
+/// Opens a [`DynamixelDriver`] with custom serial port settings
+///
+/// ```no_run
+/// # fn main() -> Result<(), dynamixel_driver::DynamixelDriverError> {
+/// let driver = dynamixel_driver::DynamixelDriver::builder("/dev/ttyUSB0")
+///     .baud_rate(57600)
+///     .ftdi_latency_timer(1)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynamixelDriverBuilder {
+    settings: SerialSettings,
+    name: Option<String>,
+}
+
+impl DynamixelDriverBuilder {
+    pub fn new(port: &str) -> DynamixelDriverBuilder {
+        DynamixelDriverBuilder {
+            settings: SerialSettings::new(port),
+            name: None,
+        }
+    }
+
+    pub fn baud_rate(mut self, baud_rate: u32) -> Self {
+        self.settings.baud_rate = baud_rate;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: ReceiveTimeout) -> Self {
+        self.settings.timeout = timeout;
+        self
+    }
+
+    pub fn parity(mut self, parity: Parity) -> Self {
+        self.settings.parity = parity;
+        self
+    }
+
+    pub fn stop_bits(mut self, stop_bits: StopBits) -> Self {
+        self.settings.stop_bits = stop_bits;
+        self
+    }
+
+    pub fn flow_control(mut self, flow_control: FlowControl) -> Self {
+        self.settings.flow_control = flow_control;
+        self
+    }
+
+    /// Sets the latency timer of FTDI based adapters in milliseconds.
+    /// The default of 16ms dominates round trip time, 1ms is recommended.
+    /// Only supported on Linux and requires write access to sysfs.
+    pub fn ftdi_latency_timer(mut self, latency_ms: u8) -> Self {
+        self.settings.latency_timer = Some(latency_ms);
+        self
+    }
+
+    /// Initial size in bytes of the read and write buffers, which are reused for every
+    /// packet. The default fits any Protocol 1.0 packet, lower it for hosts running many buses.
+    pub fn buffer_capacity(mut self, bytes: usize) -> Self {
+        self.settings.buffer_capacity = bytes;
+        self
+    }
+
+    /// Protocol generation spoken on the bus, [`crate::Protocol1`] by default
+    pub fn protocol(mut self, protocol: Arc<dyn Protocol>) -> Self {
+        self.settings.protocol = protocol;
+        self
+    }
+
+    /// See [`DynamixelDriver::set_name`]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn build(self) -> Result<DynamixelDriver> {
+        let driver = FramedSerialDriver::open(&self.settings)?;
+        let mut driver = DynamixelDriver::with_protocol(Box::new(driver), self.settings.protocol);
+        if let Some(name) = self.name {
+            driver.set_name(name);
+        }
+        Ok(driver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn builder_defaults() {
+        let builder = DynamixelDriverBuilder::new("/dev/ttyUSB0");
+        assert_eq!(builder.settings.port, "/dev/ttyUSB0");
+        assert_eq!(builder.settings.baud_rate, 1000000);
+        assert_eq!(builder.settings.timeout, ReceiveTimeout::default());
+        assert_eq!(builder.settings.parity, Parity::None);
+        assert_eq!(builder.settings.stop_bits, StopBits::One);
+        assert_eq!(builder.settings.flow_control, FlowControl::None);
+        assert_eq!(builder.settings.latency_timer, None);
+        assert_eq!(builder.settings.buffer_capacity, 512);
+        assert_eq!(builder.name, None);
+    }
+
+    #[test]
+    fn builder_applies_settings() {
+        let timeout = ReceiveTimeout::Fixed(Duration::from_millis(5));
+        let builder = DynamixelDriverBuilder::new("COM3")
+            .baud_rate(57600)
+            .timeout(timeout)
+            .parity(Parity::Even)
+            .stop_bits(StopBits::Two)
+            .flow_control(FlowControl::Hardware)
+            .ftdi_latency_timer(1)
+            .buffer_capacity(64)
+            .name("left_leg_bus");
+        assert_eq!(builder.settings.baud_rate, 57600);
+        assert_eq!(builder.settings.timeout, timeout);
+        assert_eq!(builder.settings.parity, Parity::Even);
+        assert_eq!(builder.settings.stop_bits, StopBits::Two);
+        assert_eq!(builder.settings.flow_control, FlowControl::Hardware);
+        assert_eq!(builder.settings.latency_timer, Some(1));
+        assert_eq!(builder.settings.buffer_capacity, 64);
+        assert_eq!(builder.name.as_deref(), Some("left_leg_bus"));
+    }
+}
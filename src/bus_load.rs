@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use crate::logging::warn;
+
+use crate::instructions::{Instruction, SyncCommand};
+
+/// 8 data bits plus start and stop bit
+pub(crate) const BITS_PER_BYTE: f64 = 10.0;
+
+// AX and MX default return delay time of 250 * 2us
+const DEFAULT_RESPONSE_DELAY: Duration = Duration::from_micros(500);
+
+/// Expected bus traffic used to check whether a control loop fits into the baud rate
+///
+/// ```
+/// let plan = dynamixel_driver::TrafficPlan::new(57600)
+///     .sync_write(&[1, 2, 3, 4, 5, 6], 30, 2, 50.0)
+///     .read(1, 36, 2, 50.0);
+/// assert!(plan.utilization() < 1.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrafficPlan {
+    baud_rate: u32,
+    response_delay: Duration,
+    transfers: Vec<(Instruction, f64)>,
+}
+
+impl TrafficPlan {
+    pub fn new(baud_rate: u32) -> TrafficPlan {
+        TrafficPlan {
+            baud_rate,
+            response_delay: DEFAULT_RESPONSE_DELAY,
+            transfers: vec![],
+        }
+    }
+
+    /// Time a servo waits before answering, as configured by its return delay time register
+    pub fn response_delay(mut self, delay: Duration) -> Self {
+        self.response_delay = delay;
+        self
+    }
+
+    /// Read of `len` bytes from `addr` of servo `id`, `rate` times per second
+    pub fn read(mut self, id: u8, addr: u8, len: u8, rate: f64) -> Self {
+        self.transfers
+            .push((Instruction::read_instruction(id, addr, len), rate));
+        self
+    }
+
+    /// Write of `len` bytes to `addr` of servo `id`, `rate` times per second
+    pub fn write(mut self, id: u8, addr: u8, len: u8, rate: f64) -> Self {
+        let data = vec![0; len as usize];
+        // same size as a write, only the opcode differs
+        self.transfers
+            .push((Instruction::reg_write(id, addr, &data), rate));
+        self
+    }
+
+    /// Sync write of `len` bytes per servo, `rate` times per second
+    pub fn sync_write(mut self, ids: &[u8], addr: u8, len: u8, rate: f64) -> Self {
+        let commands = ids.iter().map(|&id| SyncCommand::new(id, 0)).collect();
+        self.transfers
+            .push((Instruction::sync_command(addr, len, commands), rate));
+        self
+    }
+
+    /// Fraction of bus time the plan needs, above 1.0 the schedule can't be kept
+    pub fn utilization(&self) -> f64 {
+        let byte_time = BITS_PER_BYTE / self.baud_rate as f64;
+        self.transfers
+            .iter()
+            .map(|(instruction, rate)| {
+                let response_len = instruction.expected_response_len();
+                let mut duration = (instruction.as_bytes().len() + response_len) as f64 * byte_time;
+                if response_len > 0 {
+                    duration += self.response_delay.as_secs_f64();
+                }
+                duration * rate
+            })
+            .sum()
+    }
+
+    /// Logs a warning if the plan needs more than `budget` of the bus, returns the utilization
+    ///
+    /// Leave headroom for retries and host latency, a budget around 0.7 is a sensible start.
+    pub fn check_budget(&self, budget: f64) -> f64 {
+        let utilization = self.utilization();
+        if utilization > budget {
+            warn!(
+                "planned bus traffic needs {:.0}% of {} baud, budget is {:.0}%",
+                utilization * 100.0,
+                self.baud_rate,
+                budget * 100.0
+            );
+        }
+        utilization
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_utilization() {
+        // 8 byte instruction and 8 byte status at 10 bits per byte take 160us at 1Mbaud
+        let plan = TrafficPlan::new(1_000_000)
+            .response_delay(Duration::ZERO)
+            .read(1, 36, 2, 1000.0);
+        assert!((plan.utilization() - 0.16).abs() < 1e-9);
+        let plan = plan.response_delay(Duration::from_micros(500));
+        assert!((plan.utilization() - 0.66).abs() < 1e-9);
+    }
+
+    #[test]
+    fn eighteen_servos_overload_slow_bus() {
+        let ids: Vec<u8> = (1..=18).collect();
+        let mut plan = TrafficPlan::new(57600).sync_write(&ids, 30, 2, 50.0);
+        for &id in &ids {
+            plan = plan.read(id, 36, 2, 50.0);
+        }
+        assert!(plan.check_budget(0.7) > 1.0);
+        // sync write carries no status packets
+        let writes_only = TrafficPlan::new(57600).sync_write(&ids, 30, 2, 50.0);
+        assert!(writes_only.check_budget(0.7) < 0.7);
+    }
+}
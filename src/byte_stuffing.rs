@@ -0,0 +1,100 @@
+//! Protocol 2.0 byte stuffing.
+//!
+//! Whenever the header pattern `FF FF FD` appears in the instruction or parameters
+//! of a Protocol 2.0 packet an extra `FD` is inserted after it, so that the pattern
+//! can only ever mean the start of a packet. The length field counts stuffed bytes.
+
+const PATTERN: [u8; 3] = [0xFF, 0xFF, 0xFD];
+const STUFFING: u8 = 0xFD;
+
+/// Inserts a stuffing byte after every occurrence of the header pattern
+pub fn stuff(payload: &[u8]) -> Vec<u8> {
+    let mut stuffed = Vec::with_capacity(payload.len() + payload.len() / 3);
+    for &byte in payload {
+        stuffed.push(byte);
+        if stuffed.ends_with(&PATTERN) {
+            stuffed.push(STUFFING);
+        }
+    }
+    stuffed
+}
+
+/// Removes the stuffing byte following every occurrence of the header pattern
+///
+/// Stuffed payloads never contain the pattern without a stuffing byte, so a missing one is
+/// reported as `None` instead of silently accepting a corrupted payload.
+pub fn unstuff(stuffed: &[u8]) -> Option<Vec<u8>> {
+    let mut payload = Vec::with_capacity(stuffed.len());
+    let mut bytes = stuffed.iter();
+    while let Some(&byte) = bytes.next() {
+        payload.push(byte);
+        if payload.ends_with(&PATTERN) {
+            match bytes.next() {
+                Some(&STUFFING) => (),
+                _ => return None,
+            }
+        }
+    }
+    Some(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn stuffs_header_pattern() {
+        assert_eq!(
+            stuff(&[0x01, 0xFF, 0xFF, 0xFD, 0x02]),
+            vec![0x01, 0xFF, 0xFF, 0xFD, 0xFD, 0x02]
+        );
+        assert_eq!(
+            stuff(&[0xFF, 0xFF, 0xFD, 0xFD]),
+            vec![0xFF, 0xFF, 0xFD, 0xFD, 0xFD]
+        );
+        assert_eq!(stuff(&[0xFF, 0xFD, 0xFF]), vec![0xFF, 0xFD, 0xFF]);
+    }
+
+    #[test]
+    fn unstuffs_header_pattern() {
+        assert_eq!(
+            unstuff(&[0xFF, 0xFF, 0xFD, 0xFD, 0xFD]).unwrap(),
+            vec![0xFF, 0xFF, 0xFD, 0xFD]
+        );
+        assert_eq!(unstuff(&[0xFF, 0xFF, 0xFD, 0x01]), None);
+        assert_eq!(unstuff(&[0xFF, 0xFF, 0xFD]), None);
+    }
+
+    fn pattern_heavy_payload() -> impl Strategy<Value = Vec<u8>> {
+        // mostly bytes from the header pattern so that runs of it are common
+        prop::collection::vec(
+            prop_oneof![Just(0xFF_u8), Just(0xFD_u8), Just(0x00_u8), any::<u8>()],
+            0..64,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(payload in pattern_heavy_payload()) {
+            prop_assert_eq!(unstuff(&stuff(&payload)), Some(payload));
+        }
+
+        #[test]
+        fn stuffed_pattern_is_always_followed_by_stuffing(payload in pattern_heavy_payload()) {
+            let stuffed = stuff(&payload);
+            for (index, window) in stuffed.windows(3).enumerate() {
+                if window == PATTERN {
+                    prop_assert_eq!(stuffed.get(index + 3), Some(&STUFFING));
+                }
+            }
+        }
+
+        #[test]
+        fn stuffing_only_grows_payload_by_pattern_count(payload in pattern_heavy_payload()) {
+            let stuffed = stuff(&payload);
+            prop_assert!(stuffed.len() >= payload.len());
+            prop_assert!(stuffed.len() <= payload.len() + payload.len() / 3);
+        }
+    }
+}
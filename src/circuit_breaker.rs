@@ -0,0 +1,212 @@
+use crate::logging::{info, warn};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::instructions::{DynamixelDriverError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive timeouts after which a servo is marked offline
+    pub failure_threshold: u32,
+    /// Wait before the first probe of an offline servo
+    pub initial_backoff: Duration,
+    /// Upper bound for the probe interval, which doubles after every failed probe
+    pub max_backoff: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            failure_threshold: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Servo is responding and requests pass through
+    Closed,
+    /// Servo is offline and requests fail fast
+    Open,
+    /// Backoff expired and the next request is let through as a probe
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct ServoCircuit {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    backoff: Duration,
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    servos: HashMap<u8, ServoCircuit>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            config,
+            servos: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn state(&self, id: u8, now: Instant) -> CircuitState {
+        match self
+            .servos
+            .get(&id)
+            .and_then(|servo| servo.opened_at.map(|opened_at| (opened_at, servo.backoff)))
+        {
+            None => CircuitState::Closed,
+            Some((opened_at, backoff)) if now.duration_since(opened_at) < backoff => {
+                CircuitState::Open
+            }
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    pub(crate) fn check(&self, id: u8, now: Instant) -> Result<()> {
+        if self.state(id, now) == CircuitState::Open {
+            return Err(DynamixelDriverError::ServoOffline(id));
+        }
+        Ok(())
+    }
+
+    /// Records a transaction result, returning the new state if the circuit opened or closed
+    pub(crate) fn record<T>(
+        &mut self,
+        id: u8,
+        result: &Result<T>,
+        now: Instant,
+    ) -> Option<CircuitState> {
+        match result {
+            Err(DynamixelDriverError::Timeout) => self.record_failure(id, now),
+            _ => self.record_success(id),
+        }
+    }
+
+    fn record_success(&mut self, id: u8) -> Option<CircuitState> {
+        let servo = self.servos.remove(&id)?;
+        servo.opened_at?;
+        info!("servo {} is responding again, closing circuit", id);
+        Some(CircuitState::Closed)
+    }
+
+    fn record_failure(&mut self, id: u8, now: Instant) -> Option<CircuitState> {
+        let config = self.config;
+        let servo = self.servos.entry(id).or_default();
+        servo.consecutive_failures += 1;
+        if servo.opened_at.is_some() {
+            // failed probe
+            servo.backoff = (servo.backoff * 2).min(config.max_backoff);
+            servo.opened_at = Some(now);
+            None
+        } else if servo.consecutive_failures >= config.failure_threshold {
+            warn!(
+                "servo {} failed {} consecutive times, marking offline",
+                id, servo.consecutive_failures
+            );
+            servo.backoff = config.initial_backoff;
+            servo.opened_at = Some(now);
+            Some(CircuitState::Open)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+        })
+    }
+
+    fn timeout() -> Result<()> {
+        Err(DynamixelDriverError::Timeout)
+    }
+
+    #[test]
+    fn opens_after_threshold() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+        assert_eq!(breaker.record(1, &timeout(), now), None);
+        assert_eq!(breaker.state(1, now), CircuitState::Closed);
+        assert_eq!(breaker.record(1, &timeout(), now), Some(CircuitState::Open));
+        assert_eq!(breaker.state(1, now), CircuitState::Open);
+        assert!(matches!(
+            breaker.check(1, now),
+            Err(DynamixelDriverError::ServoOffline(1))
+        ));
+        assert!(breaker.check(2, now).is_ok());
+        assert_eq!(breaker.record(1, &Ok(()), now), Some(CircuitState::Closed));
+        assert_eq!(breaker.record(1, &Ok(()), now), None);
+    }
+
+    #[test]
+    fn success_resets_failure_count() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+        breaker.record(1, &timeout(), now);
+        breaker.record(1, &Ok(()), now);
+        breaker.record(1, &timeout(), now);
+        assert_eq!(breaker.state(1, now), CircuitState::Closed);
+    }
+
+    #[test]
+    fn other_errors_do_not_count() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..5 {
+            breaker.record::<()>(1, &Err(DynamixelDriverError::ChecksumError(0, 1)), now);
+        }
+        assert_eq!(breaker.state(1, now), CircuitState::Closed);
+    }
+
+    #[test]
+    fn half_opens_after_backoff_and_backs_off_exponentially() {
+        let mut breaker = breaker();
+        let start = Instant::now();
+        breaker.record(1, &timeout(), start);
+        breaker.record(1, &timeout(), start);
+        let probe = start + Duration::from_millis(100);
+        assert_eq!(breaker.state(1, probe), CircuitState::HalfOpen);
+        assert!(breaker.check(1, probe).is_ok());
+
+        // failed probe doubles backoff
+        breaker.record(1, &timeout(), probe);
+        assert_eq!(
+            breaker.state(1, probe + Duration::from_millis(150)),
+            CircuitState::Open
+        );
+        let probe = probe + Duration::from_millis(200);
+        assert_eq!(breaker.state(1, probe), CircuitState::HalfOpen);
+
+        // backoff is capped
+        breaker.record(1, &timeout(), probe);
+        assert_eq!(
+            breaker.state(1, probe + Duration::from_millis(300)),
+            CircuitState::HalfOpen
+        );
+    }
+
+    #[test]
+    fn successful_probe_closes() {
+        let mut breaker = breaker();
+        let start = Instant::now();
+        breaker.record(1, &timeout(), start);
+        breaker.record(1, &timeout(), start);
+        let probe = start + Duration::from_millis(100);
+        breaker.record(1, &Ok(()), probe);
+        assert_eq!(breaker.state(1, probe), CircuitState::Closed);
+    }
+}
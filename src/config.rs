@@ -0,0 +1,368 @@
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use std::path::Path;
+#[cfg(feature = "serde")]
+use std::str::FromStr;
+
+use crate::angle_limits::AngleLimitPolicy;
+use crate::instructions::{DynamixelDriverError, Result};
+use crate::model::ServoModel;
+use crate::safety::SafetyPolicy;
+use crate::serial_driver::{ChecksumPolicy, FlushPolicy, IdMismatchPolicy};
+
+/// Driver configuration, usually loaded from a TOML file
+///
+/// ```toml
+/// port = "/dev/ttyUSB0"
+/// baud_rate = 1000000
+/// timeout_ms = 100
+/// retries = 2
+/// checksum_policy = "warn_and_accept"
+/// id_mismatch_policy = "discard"
+/// flush_policy = "on_error"
+/// auto_detect_models = true
+///
+/// [[servos]]
+/// id = 1
+/// name = "left_knee"
+/// model = "AX-12A"
+/// offset_degrees = 150.0
+/// inverted = true
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct DriverConfig {
+    pub port: String,
+    /// See [`crate::DynamixelDriver::set_name`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default = "default_baud_rate"))]
+    pub baud_rate: u32,
+    /// Fixed receive timeout, scaled with baud rate and response length when missing
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timeout_ms: Option<u64>,
+    /// Number of times a transaction is retried after a recoverable error
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub retries: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub checksum_policy: ChecksumPolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub id_mismatch_policy: IdMismatchPolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub flush_policy: FlushPolicy,
+    /// Read models of servos that aren't declared on first use
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub auto_detect_models: bool,
+    /// Let scans find out which protocol generation each servo speaks
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub detect_protocols: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub safety_policy: SafetyPolicy,
+    /// Applies to servos whose limits were read with
+    /// [`crate::DynamixelDriver::read_angle_limits`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub angle_limit_policy: AngleLimitPolicy,
+    #[cfg_attr(feature = "serde", serde(default = "default_protocol_version"))]
+    pub protocol_version: u8,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub servos: Vec<ServoDescription>,
+}
+
+#[cfg(feature = "serde")]
+fn default_baud_rate() -> u32 {
+    1000000
+}
+
+#[cfg(feature = "serde")]
+fn default_protocol_version() -> u8 {
+    1
+}
+
+#[cfg(feature = "serde")]
+impl DriverConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<DriverConfig> {
+        let contents = std::fs::read_to_string(path)?;
+        contents.parse()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !matches!(self.protocol_version, 1 | 2) {
+            return Err(DynamixelDriverError::InvalidConfig(format!(
+                "unsupported protocol version {}",
+                self.protocol_version
+            )));
+        }
+        for (index, servo) in self.servos.iter().enumerate() {
+            if self.servos[..index]
+                .iter()
+                .any(|other| other.id == servo.id)
+            {
+                return Err(DynamixelDriverError::InvalidConfig(format!(
+                    "servo id {} declared more than once",
+                    servo.id
+                )));
+            }
+            if let Some(name) = &servo.name {
+                if self.servos[..index]
+                    .iter()
+                    .any(|other| other.name.as_ref() == Some(name))
+                {
+                    return Err(DynamixelDriverError::InvalidConfig(format!(
+                        "joint name {:?} declared more than once",
+                        name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromStr for DriverConfig {
+    type Err = DynamixelDriverError;
+
+    fn from_str(contents: &str) -> Result<Self> {
+        let config: DriverConfig = toml::from_str(contents)
+            .map_err(|error| DynamixelDriverError::InvalidConfig(error.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Model and calibration of a single servo on the bus
+///
+/// Calibration is applied to all degree and radian based position APIs.
+/// Joint angle is calculated as `(servo_angle - offset_degrees)`, negated if inverted.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct ServoDescription {
+    pub id: u8,
+    /// Joint name the servo can be addressed by instead of its id
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub model: Option<ServoModel>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub offset_degrees: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inverted: bool,
+    /// Lowest joint angle in degrees goal positions may command
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_degrees: Option<f32>,
+    /// Highest joint angle in degrees goal positions may command
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_degrees: Option<f32>,
+}
+
+impl ServoDescription {
+    pub fn new(id: u8) -> ServoDescription {
+        ServoDescription {
+            id,
+            name: None,
+            model: None,
+            offset_degrees: 0.0,
+            inverted: false,
+            min_degrees: None,
+            max_degrees: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> ServoDescription {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub(crate) fn check_limits(&self, joint_degrees: f32) -> Result<()> {
+        let below = self.min_degrees.is_some_and(|min| joint_degrees < min);
+        let above = self.max_degrees.is_some_and(|max| joint_degrees > max);
+        if below || above {
+            return Err(DynamixelDriverError::JointLimitExceeded {
+                id: self.id,
+                degrees: joint_degrees,
+                min: self.min_degrees,
+                max: self.max_degrees,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn joint_to_servo_degrees(&self, joint_degrees: f32) -> f32 {
+        if self.inverted {
+            self.offset_degrees - joint_degrees
+        } else {
+            self.offset_degrees + joint_degrees
+        }
+    }
+
+    pub(crate) fn servo_to_joint_degrees(&self, servo_degrees: f32) -> f32 {
+        if self.inverted {
+            self.offset_degrees - servo_degrees
+        } else {
+            servo_degrees - self.offset_degrees
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parse_full_config() {
+        let config: DriverConfig = r#"
+            port = "/dev/ttyUSB0"
+            name = "left_leg_bus"
+            baud_rate = 57600
+            timeout_ms = 20
+            retries = 3
+            checksum_policy = "retry"
+            id_mismatch_policy = "accept_any"
+            flush_policy = "before_transaction"
+            auto_detect_models = true
+            detect_protocols = true
+            safety_policy = "torque_off_bus"
+            angle_limit_policy = "clamp"
+
+            [[servos]]
+            id = 1
+            name = "left_knee"
+            model = "AX-12A"
+            offset_degrees = 150.0
+            inverted = true
+            min_degrees = -90.0
+            max_degrees = 45.0
+
+            [[servos]]
+            id = 2
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(config.port, "/dev/ttyUSB0");
+        assert_eq!(config.name.as_deref(), Some("left_leg_bus"));
+        assert_eq!(config.baud_rate, 57600);
+        assert_eq!(config.timeout_ms, Some(20));
+        assert_eq!(config.retries, 3);
+        assert_eq!(config.checksum_policy, ChecksumPolicy::Retry);
+        assert_eq!(config.id_mismatch_policy, IdMismatchPolicy::AcceptAny);
+        assert_eq!(config.flush_policy, FlushPolicy::BeforeTransaction);
+        assert!(config.auto_detect_models);
+        assert!(config.detect_protocols);
+        assert_eq!(config.safety_policy, SafetyPolicy::TorqueOffBus);
+        assert_eq!(config.angle_limit_policy, AngleLimitPolicy::Clamp);
+        assert_eq!(config.servos.len(), 2);
+        assert_eq!(config.servos[0].name.as_deref(), Some("left_knee"));
+        assert_eq!(config.servos[0].model, Some(ServoModel::Ax12A));
+        assert!(config.servos[0].inverted);
+        assert_eq!(config.servos[0].min_degrees, Some(-90.0));
+        assert_eq!(config.servos[0].max_degrees, Some(45.0));
+        assert_eq!(config.servos[1], ServoDescription::new(2));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parse_minimal_config_uses_defaults() {
+        let config: DriverConfig = r#"port = "COM3""#.parse().unwrap();
+        assert_eq!(config.name, None);
+        assert_eq!(config.baud_rate, 1000000);
+        assert_eq!(config.timeout_ms, None);
+        assert_eq!(config.retries, 0);
+        assert_eq!(config.checksum_policy, ChecksumPolicy::Strict);
+        assert_eq!(config.id_mismatch_policy, IdMismatchPolicy::Strict);
+        assert_eq!(config.flush_policy, FlushPolicy::Never);
+        assert!(!config.auto_detect_models);
+        assert!(!config.detect_protocols);
+        assert_eq!(config.safety_policy, SafetyPolicy::Disabled);
+        assert_eq!(config.angle_limit_policy, AngleLimitPolicy::Ignore);
+        assert_eq!(config.protocol_version, 1);
+        assert!(config.servos.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reject_unsupported_protocol() {
+        let result = r#"
+            port = "COM3"
+            protocol_version = 3
+        "#
+        .parse::<DriverConfig>();
+        assert!(matches!(
+            result,
+            Err(DynamixelDriverError::InvalidConfig(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reject_duplicate_servo_ids() {
+        let result = r#"
+            port = "COM3"
+            [[servos]]
+            id = 1
+            [[servos]]
+            id = 1
+        "#
+        .parse::<DriverConfig>();
+        assert!(matches!(
+            result,
+            Err(DynamixelDriverError::InvalidConfig(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reject_duplicate_joint_names() {
+        let result = r#"
+            port = "COM3"
+            [[servos]]
+            id = 1
+            name = "left_knee"
+            [[servos]]
+            id = 2
+            name = "left_knee"
+        "#
+        .parse::<DriverConfig>();
+        assert!(matches!(
+            result,
+            Err(DynamixelDriverError::InvalidConfig(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reject_unknown_model() {
+        let result = r#"
+            port = "COM3"
+            [[servos]]
+            id = 1
+            model = "XL-9000"
+        "#
+        .parse::<DriverConfig>();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn calibration_round_trip() {
+        let servo = ServoDescription {
+            id: 1,
+            name: None,
+            model: None,
+            offset_degrees: 150.0,
+            inverted: true,
+            min_degrees: None,
+            max_degrees: None,
+        };
+        assert_eq!(servo.joint_to_servo_degrees(30.0), 120.0);
+        assert_eq!(servo.servo_to_joint_degrees(120.0), 30.0);
+        let servo = ServoDescription {
+            inverted: false,
+            ..servo
+        };
+        assert_eq!(servo.joint_to_servo_degrees(30.0), 180.0);
+        assert_eq!(servo.servo_to_joint_degrees(180.0), 30.0);
+    }
+}
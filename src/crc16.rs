@@ -0,0 +1,90 @@
+//! CRC-16 used by Protocol 2.0 packets.
+//!
+//! Polynomial `0x8005`, initial value zero, no reflection (CRC-16/BUYPASS), computed over the
+//! whole packet including the header and stored little endian after it.
+
+const POLYNOMIAL: u16 = 0x8005;
+
+const TABLE: [u16; 256] = build_table();
+
+const fn build_table() -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = (index as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ POLYNOMIAL
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+}
+
+/// Continues a CRC over more data, for packets received in pieces
+pub fn update_crc(crc: u16, data: &[u8]) -> u16 {
+    data.iter().fold(crc, |crc, &byte| {
+        (crc << 8) ^ TABLE[(((crc >> 8) as u8) ^ byte) as usize]
+    })
+}
+
+pub fn crc16(data: &[u8]) -> u16 {
+    update_crc(0, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitwise_crc(data: &[u8]) -> u16 {
+        let mut crc = 0_u16;
+        for &byte in data {
+            crc ^= (byte as u16) << 8;
+            for _ in 0..8 {
+                crc = if crc & 0x8000 != 0 {
+                    (crc << 1) ^ POLYNOMIAL
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    #[test]
+    fn check_value() {
+        assert_eq!(crc16(b"123456789"), 0xFEE8);
+    }
+
+    #[test]
+    fn reference_packets() {
+        // ping id 1 from the Protocol 2.0 e-manual
+        assert_eq!(
+            crc16(&[0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x03, 0x00, 0x01]),
+            0x4E19
+        );
+        // read present position of id 1
+        assert_eq!(
+            crc16(&[0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x07, 0x00, 0x02, 0x84, 0x00, 0x04, 0x00]),
+            0x151D
+        );
+    }
+
+    #[test]
+    fn table_matches_bitwise_implementation() {
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(crc16(&data), bitwise_crc(&data));
+    }
+
+    #[test]
+    fn incremental_update() {
+        let data = [0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x03, 0x00, 0x01];
+        assert_eq!(update_crc(crc16(&data[..3]), &data[3..]), crc16(&data));
+    }
+}
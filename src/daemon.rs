@@ -0,0 +1,333 @@
+//! Share one bus between processes through a Unix domain socket.
+//!
+//! [`serve`] owns the transport and executes one instruction at a time, so requests
+//! from different clients never interleave on the wire. [`DaemonClient`] implements
+//! [`FramedDriver`] and can be used with [`crate::DynamixelDriver::with_driver`].
+//!
+//! Every frame on the socket is a kind byte followed by a little endian `u16` length
+//! and that many bytes.
+
+use crate::logging::{info, warn};
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex;
+
+use crate::instructions::{DynamixelDriverError, Instruction, Result, StatusError};
+use crate::serial_driver::{FramedDriver, Status};
+
+// requests
+const TRANSACTION: u8 = 0;
+const CLEAR_IO_BUFFERS: u8 = 1;
+
+// responses
+const DONE: u8 = 0;
+const STATUS: u8 = 1;
+const ERROR: u8 = 2;
+
+// error codes
+const TIMEOUT: u8 = 0;
+const STATUS_ERROR: u8 = 1;
+const CHECKSUM_ERROR: u8 = 2;
+const OTHER_ERROR: u8 = 255;
+
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, kind: u8, data: &[u8]) -> Result<()> {
+    let mut frame = Vec::with_capacity(data.len() + 3);
+    frame.push(kind);
+    frame.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    frame.extend_from_slice(data);
+    writer.write_all(&frame).await?;
+    Ok(())
+}
+
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<(u8, Vec<u8>)> {
+    let mut header = [0; 3];
+    reader.read_exact(&mut header).await?;
+    let mut data = vec![0; u16::from_le_bytes([header[1], header[2]]) as usize];
+    reader.read_exact(&mut data).await?;
+    Ok((header[0], data))
+}
+
+fn status_error_flag(error: &StatusError) -> u8 {
+    error.input_voltage_error as u8
+        | (error.angle_limit_error as u8) << 1
+        | (error.overheating_error as u8) << 2
+        | (error.range_error as u8) << 3
+        | (error.checksum_error as u8) << 4
+        | (error.overload_error as u8) << 5
+        | (error.instruction_error as u8) << 6
+}
+
+fn encode_error(error: &DynamixelDriverError) -> Vec<u8> {
+    match error {
+        DynamixelDriverError::Timeout => vec![TIMEOUT],
+        DynamixelDriverError::StatusError(status_error) => {
+            vec![STATUS_ERROR, status_error_flag(status_error)]
+        }
+        DynamixelDriverError::ChecksumError(expected, received) => {
+            let mut data = vec![CHECKSUM_ERROR];
+            data.extend_from_slice(&expected.to_le_bytes());
+            data.extend_from_slice(&received.to_le_bytes());
+            data
+        }
+        other => {
+            let mut data = vec![OTHER_ERROR];
+            data.extend_from_slice(other.to_string().as_bytes());
+            data
+        }
+    }
+}
+
+fn decode_error(data: &[u8]) -> DynamixelDriverError {
+    match data {
+        [TIMEOUT] => DynamixelDriverError::Timeout,
+        [STATUS_ERROR, flag] => match StatusError::check_error(*flag) {
+            Err(error) => error,
+            Ok(()) => DynamixelDriverError::DaemonError("empty status error".to_owned()),
+        },
+        &[CHECKSUM_ERROR, expected_low, expected_high, received_low, received_high] => {
+            DynamixelDriverError::ChecksumError(
+                u16::from_le_bytes([expected_low, expected_high]),
+                u16::from_le_bytes([received_low, received_high]),
+            )
+        }
+        [OTHER_ERROR, message @ ..] => {
+            DynamixelDriverError::DaemonError(String::from_utf8_lossy(message).into_owned())
+        }
+        _ => DynamixelDriverError::DaemonError("malformed error response".to_owned()),
+    }
+}
+
+/// Serves the transport on a Unix domain socket at `path` until an error occurs
+///
+/// Fails if the socket file already exists.
+pub async fn serve(transport: Box<dyn FramedDriver>, path: impl AsRef<Path>) -> Result<()> {
+    let listener = UnixListener::bind(path.as_ref())?;
+    info!("serving bus on {:?}", path.as_ref());
+    serve_listener(Arc::new(Mutex::new(transport)), listener).await
+}
+
+async fn serve_listener(
+    transport: Arc<Mutex<Box<dyn FramedDriver>>>,
+    listener: UnixListener,
+) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let transport = transport.clone();
+        tokio::spawn(async move {
+            if let Err(error) = handle_client(transport, stream).await {
+                warn!("bus daemon client disconnected with {}", error);
+            }
+        });
+    }
+}
+
+async fn handle_client(
+    transport: Arc<Mutex<Box<dyn FramedDriver>>>,
+    mut stream: UnixStream,
+) -> Result<()> {
+    loop {
+        let (kind, data) = match read_frame(&mut stream).await {
+            Ok(frame) => frame,
+            Err(DynamixelDriverError::IoError(error))
+                if error.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+        let mut transport = transport.lock().await;
+        let response = match kind {
+            TRANSACTION => transaction(transport.as_mut(), &data).await,
+            CLEAR_IO_BUFFERS => transport.clear_io_buffers().await.map(|_| None),
+            other => Err(DynamixelDriverError::DaemonError(format!(
+                "unknown request {}",
+                other
+            ))),
+        };
+        drop(transport);
+        match response {
+            Ok(None) => write_frame(&mut stream, DONE, &[]).await?,
+            Ok(Some(status)) => {
+                let mut data = vec![status.id()];
+                data.extend_from_slice(status.params());
+                write_frame(&mut stream, STATUS, &data).await?
+            }
+            Err(error) => write_frame(&mut stream, ERROR, &encode_error(&error)).await?,
+        }
+    }
+}
+
+async fn transaction(transport: &mut dyn FramedDriver, packet: &[u8]) -> Result<Option<Status>> {
+    let instruction = Instruction::from_bytes(packet)?;
+    let expects_response = instruction.expected_response_len() > 0;
+    transport.send(instruction).await?;
+    if expects_response {
+        Ok(Some(transport.receive().await?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Transport talking to a bus shared by [`serve`] in another process
+pub struct DaemonClient {
+    stream: UnixStream,
+    pending: Option<Result<Status>>,
+}
+
+impl DaemonClient {
+    pub async fn connect(path: impl AsRef<Path>) -> Result<DaemonClient> {
+        Ok(DaemonClient {
+            stream: UnixStream::connect(path).await?,
+            pending: None,
+        })
+    }
+
+    async fn request(&mut self, kind: u8, data: &[u8]) -> Result<Option<Status>> {
+        write_frame(&mut self.stream, kind, data).await?;
+        match read_frame(&mut self.stream).await? {
+            (DONE, _) => Ok(None),
+            (STATUS, data) if !data.is_empty() => {
+                Ok(Some(Status::new(data[0], data[1..].to_vec())))
+            }
+            (ERROR, data) => Err(decode_error(&data)),
+            _ => Err(DynamixelDriverError::DaemonError(
+                "malformed response".to_owned(),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl FramedDriver for DaemonClient {
+    async fn send(&mut self, instruction: Instruction) -> Result<()> {
+        // the daemon runs the whole transaction so no other client can get in between
+        self.pending = match self.request(TRANSACTION, instruction.as_bytes()).await {
+            Ok(status) => status.map(Ok),
+            Err(DynamixelDriverError::IoError(error)) => {
+                return Err(DynamixelDriverError::IoError(error))
+            }
+            Err(error) => Some(Err(error)),
+        };
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<Status> {
+        self.pending
+            .take()
+            .unwrap_or(Err(DynamixelDriverError::Timeout))
+    }
+
+    async fn clear_io_buffers(&mut self) -> Result<()> {
+        self.pending = None;
+        self.request(CLEAR_IO_BUFFERS, &[]).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamixelDriver;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockTransport {
+        sent: Arc<std::sync::Mutex<Vec<Vec<u8>>>>,
+        responses: Vec<Result<Status>>,
+    }
+
+    #[async_trait]
+    impl FramedDriver for MockTransport {
+        async fn send(&mut self, instruction: Instruction) -> Result<()> {
+            self.sent.lock().unwrap().push(instruction.serialize());
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<Status> {
+            if self.responses.is_empty() {
+                return Err(DynamixelDriverError::Timeout);
+            }
+            self.responses.remove(0)
+        }
+
+        async fn clear_io_buffers(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn socket_path() -> std::path::PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        std::env::temp_dir().join(format!(
+            "dynamixel-daemon-{}-{}.sock",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ))
+    }
+
+    async fn start_daemon(
+        responses: Vec<Result<Status>>,
+    ) -> (std::path::PathBuf, Arc<std::sync::Mutex<Vec<Vec<u8>>>>) {
+        let sent = Arc::new(std::sync::Mutex::new(vec![]));
+        let transport = MockTransport {
+            sent: sent.clone(),
+            responses,
+        };
+        let path = socket_path();
+        let listener = UnixListener::bind(&path).unwrap();
+        tokio::spawn(serve_listener(
+            Arc::new(Mutex::new(Box::new(transport))),
+            listener,
+        ));
+        (path, sent)
+    }
+
+    #[tokio::test]
+    async fn client_runs_transactions_through_daemon() {
+        let (path, sent) = start_daemon(vec![
+            Ok(Status::new(1, vec![42])),
+            Err(DynamixelDriverError::Timeout),
+        ])
+        .await;
+        let client = DaemonClient::connect(&path).await.unwrap();
+        let mut driver = DynamixelDriver::with_driver(Box::new(client));
+        assert_eq!(driver.read_temperature(1).await.unwrap(), 42);
+        assert!(matches!(
+            driver.ping(2).await.unwrap_err(),
+            DynamixelDriverError::Timeout
+        ));
+        // broadcast doesn't wait for a status
+        driver.sync_write_torque(vec![(1, true)]).await.unwrap();
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[0], vec![255, 255, 1, 4, 2, 43, 1, 204]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn status_errors_survive_the_socket() {
+        let overload = StatusError::check_error(1 << 5).unwrap_err();
+        let (path, _) = start_daemon(vec![Err(overload)]).await;
+        let client = DaemonClient::connect(&path).await.unwrap();
+        let mut driver = DynamixelDriver::with_driver(Box::new(client));
+        match driver.ping(1).await.unwrap_err() {
+            DynamixelDriverError::StatusError(status_error) => {
+                assert!(status_error.overload_error)
+            }
+            other => panic!("unexpected error {}", other),
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn error_encoding_round_trip() {
+        let error = decode_error(&encode_error(&DynamixelDriverError::ChecksumError(1, 2)));
+        assert!(matches!(error, DynamixelDriverError::ChecksumError(1, 2)));
+        let error = decode_error(&encode_error(&DynamixelDriverError::ServoOffline(3)));
+        assert!(matches!(
+            error,
+            DynamixelDriverError::DaemonError(message) if message == "servo 3 is offline"
+        ));
+    }
+}
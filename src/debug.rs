@@ -0,0 +1,263 @@
+//! Human readable formatting of raw Dynamixel packets for logging and bus sniffing.
+//!
+//! Packets of both protocol generations are told apart by their header, Protocol 2.0
+//! parameters are shown unstuffed.
+
+use std::fmt::Write;
+
+use crate::byte_stuffing;
+use crate::instructions::{calc_checksum, StatusError};
+use crate::protocol::{Protocol, Protocol2, STATUS};
+
+const HEADER: [u8; 2] = [0xFF, 0xFF];
+
+pub fn instruction_name(opcode: u8) -> &'static str {
+    match opcode {
+        0x01 => "PING",
+        0x02 => "READ",
+        0x03 => "WRITE",
+        0x04 => "REG_WRITE",
+        0x05 => "ACTION",
+        0x06 => "RESET",
+        0x08 => "REBOOT",
+        STATUS => "STATUS",
+        0x83 => "SYNC_WRITE",
+        0x84 => "SYNC_READ",
+        0x92 => "BULK_READ",
+        0x93 => "BULK_WRITE",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Formats an instruction packet as
+/// `id=1 len=4 instruction=READ(0x02) params=[2B 01] checksum=CC (ok)`
+pub fn format_instruction(packet: &[u8]) -> String {
+    match split_packet(packet) {
+        Some(fields) => format!(
+            "id={} len={} instruction={}(0x{:02X}) params=[{}] checksum={} ({})",
+            fields.id,
+            fields.len,
+            instruction_name(fields.opcode),
+            fields.opcode,
+            hex(&fields.params),
+            fields.checksum,
+            checksum_validity(packet),
+        ),
+        None => format!("malformed instruction [{}]", hex(packet)),
+    }
+}
+
+/// Formats a status packet as
+/// `id=1 len=3 error=0x00 params=[20] checksum=DB (ok)`
+pub fn format_status(packet: &[u8]) -> String {
+    let fields = split_packet(packet).and_then(|fields| match fields.protocol2 {
+        // the error byte follows the STATUS instruction
+        true => match fields.params.split_first() {
+            Some((&error, params)) if fields.opcode == STATUS => Some(PacketFields {
+                opcode: error,
+                params: params.to_vec(),
+                ..fields
+            }),
+            _ => None,
+        },
+        false => Some(fields),
+    });
+    match fields {
+        Some(fields) => {
+            let error = fields.opcode;
+            let mut description =
+                format!("id={} len={} error=0x{:02X}", fields.id, fields.len, error);
+            let flags = match fields.protocol2 {
+                true => Protocol2::error_flags(error),
+                false => error,
+            };
+            if let Some(status_error) = StatusError::from_flags(flags) {
+                let _ = write!(description, " ({})", status_error.to_string().trim_end());
+            }
+            let _ = write!(
+                description,
+                " params=[{}] checksum={} ({})",
+                hex(&fields.params),
+                fields.checksum,
+                checksum_validity(packet)
+            );
+            description
+        }
+        None => format!("malformed status [{}]", hex(packet)),
+    }
+}
+
+/// Classic hexdump with offsets and 16 bytes per line.
+pub fn hexdump(data: &[u8]) -> String {
+    let mut output = String::new();
+    for (line, chunk) in data.chunks(16).enumerate() {
+        if line > 0 {
+            output.push('\n');
+        }
+        let _ = write!(output, "{:04X}: {}", line * 16, hex(chunk));
+    }
+    output
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct PacketFields {
+    protocol2: bool,
+    id: u8,
+    len: usize,
+    // instruction or Protocol 1.0 error byte
+    opcode: u8,
+    params: Vec<u8>,
+    checksum: String,
+}
+
+fn split_packet(packet: &[u8]) -> Option<PacketFields> {
+    if packet.starts_with(Protocol2.header()) {
+        return split_protocol2_packet(packet);
+    }
+    if packet.len() < 6 || !packet.starts_with(&HEADER) {
+        return None;
+    }
+    let len = packet[3];
+    if len < 2 || packet.len() != 4 + len as usize {
+        return None;
+    }
+    Some(PacketFields {
+        protocol2: false,
+        id: packet[2],
+        len: len.into(),
+        opcode: packet[4],
+        params: packet[5..packet.len() - 1].to_vec(),
+        checksum: format!("{:02X}", packet.last()?),
+    })
+}
+
+fn split_protocol2_packet(packet: &[u8]) -> Option<PacketFields> {
+    // header, id, length, instruction and CRC
+    if packet.len() < 10 {
+        return None;
+    }
+    let len = u16::from_le_bytes([packet[5], packet[6]]) as usize;
+    if packet.len() != 7 + len {
+        return None;
+    }
+    let crc = &packet[packet.len() - 2..];
+    let body = byte_stuffing::unstuff(&packet[7..packet.len() - 2])?;
+    let (&opcode, params) = body.split_first()?;
+    Some(PacketFields {
+        protocol2: true,
+        id: packet[4],
+        len,
+        opcode,
+        params: params.to_vec(),
+        checksum: format!("{:04X}", u16::from_le_bytes([crc[0], crc[1]])),
+    })
+}
+
+fn checksum_validity(packet: &[u8]) -> &'static str {
+    let valid = match packet.starts_with(Protocol2.header()) {
+        true => Protocol2.verify_checksum(packet).is_ok(),
+        false => calc_checksum(&packet[2..packet.len() - 1]) == packet[packet.len() - 1],
+    };
+    if valid {
+        "ok"
+    } else {
+        "invalid"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_read_instruction() {
+        let packet = [0xFF, 0xFF, 0x01, 0x04, 0x02, 0x2B, 0x01, 0xCC];
+        assert_eq!(
+            format_instruction(&packet),
+            "id=1 len=4 instruction=READ(0x02) params=[2B 01] checksum=CC (ok)"
+        );
+    }
+
+    #[test]
+    fn format_instruction_with_bad_checksum() {
+        let packet = [0xFF, 0xFF, 0x01, 0x02, 0x01, 0x00];
+        assert_eq!(
+            format_instruction(&packet),
+            "id=1 len=2 instruction=PING(0x01) params=[] checksum=00 (invalid)"
+        );
+    }
+
+    #[test]
+    fn format_status_packet() {
+        let packet = [0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0xDB];
+        assert_eq!(
+            format_status(&packet),
+            "id=1 len=3 error=0x00 params=[20] checksum=DB (ok)"
+        );
+    }
+
+    #[test]
+    fn format_status_packet_with_error() {
+        let packet = [0xFF, 0xFF, 0x01, 0x03, 0b00000100, 0x20, 0xD7];
+        assert_eq!(
+            format_status(&packet),
+            "id=1 len=3 error=0x04 (overheating_error) params=[20] checksum=D7 (ok)"
+        );
+    }
+
+    #[test]
+    fn format_protocol2_packets() {
+        let read = [
+            0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x07, 0x00, 0x02, 0x84, 0x00, 0x04, 0x00, 0x1D, 0x15,
+        ];
+        assert_eq!(
+            format_instruction(&read),
+            "id=1 len=7 instruction=READ(0x02) params=[84 00 04 00] checksum=151D (ok)"
+        );
+        let status = [
+            0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x08, 0x00, 0x55, 0x00, 0xA6, 0x00, 0x00, 0x00, 0x8C,
+            0xC0,
+        ];
+        assert_eq!(
+            format_status(&status),
+            "id=1 len=8 error=0x00 params=[A6 00 00 00] checksum=C08C (ok)"
+        );
+
+        // data range error with the alert bit set
+        let mut status = vec![0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x05, 0x00, 0x55, 0x84, 0x20];
+        let crc = crate::crc16::crc16(&status);
+        status.extend(crc.to_le_bytes());
+        assert_eq!(
+            format_status(&status),
+            format!(
+                "id=1 len=5 error=0x84 (range_error hardware_alert) params=[20] checksum={:04X} (ok)",
+                crc
+            )
+        );
+        assert_eq!(
+            format_status(&read),
+            "malformed status [FF FF FD 00 01 07 00 02 84 00 04 00 1D 15]"
+        );
+    }
+
+    #[test]
+    fn format_malformed_packet() {
+        let packet = [0xFF, 0xFF, 0x01, 0x09, 0x00];
+        assert_eq!(format_status(&packet), "malformed status [FF FF 01 09 00]");
+    }
+
+    #[test]
+    fn hexdump_splits_lines() {
+        let data: Vec<u8> = (0..20).collect();
+        assert_eq!(
+            hexdump(&data),
+            "0000: 00 01 02 03 04 05 06 07 08 09 0A 0B 0C 0D 0E 0F\n0010: 10 11 12 13"
+        );
+    }
+}
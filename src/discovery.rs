@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+#[cfg(feature = "serial")]
+use crate::instructions::Result;
+#[cfg(feature = "serial")]
+use crate::DynamixelDriver;
+
+// answers later than this are not worth waiting for during a sweep
+const MAX_SWEEP_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Per ping timeout of [`crate::DynamixelDriver::search_fast`]
+///
+/// Starts short and grows to twice the slowest answer seen, so a bus behind a slow
+/// adapter costs a few long waits instead of a long wait for every missing id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AdaptiveTimeout {
+    timeout: Duration,
+}
+
+impl AdaptiveTimeout {
+    pub fn new(initial: Duration) -> AdaptiveTimeout {
+        AdaptiveTimeout {
+            timeout: initial.min(MAX_SWEEP_TIMEOUT),
+        }
+    }
+
+    pub fn get(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn record_answer(&mut self, latency: Duration) {
+        self.timeout = self.timeout.max(latency * 2).min(MAX_SWEEP_TIMEOUT);
+    }
+
+    /// An answer arrived after its ping had already timed out
+    pub fn record_late_answer(&mut self) {
+        self.timeout = (self.timeout * 2).min(MAX_SWEEP_TIMEOUT);
+    }
+}
+
+/// Servos found on one port at one baud rate by [`discover`]
+#[cfg(feature = "serial")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discovery {
+    pub port: String,
+    pub baud_rate: u32,
+    pub ids: Vec<u8>,
+}
+
+/// Sweeps every port at every baud rate with [`DynamixelDriver::search_fast`]
+///
+/// Ports are scanned at the same time, baud rates of one port one after another.
+/// Only combinations where servos answered are returned.
+#[cfg(feature = "serial")]
+pub async fn discover(
+    ports: &[&str],
+    baud_rates: &[u32],
+    initial_timeout: Duration,
+) -> Result<Vec<Discovery>> {
+    let scans = ports.iter().map(|&port| async move {
+        let mut found = vec![];
+        for &baud_rate in baud_rates {
+            let mut driver = DynamixelDriver::with_baud_rate(port, baud_rate)?;
+            let ids = driver.search_fast(initial_timeout).await;
+            driver.close().await?;
+            let ids = ids?;
+            if !ids.is_empty() {
+                found.push(Discovery {
+                    port: port.to_owned(),
+                    baud_rate,
+                    ids,
+                });
+            }
+        }
+        Ok(found)
+    });
+    let results: Vec<Result<Vec<Discovery>>> = futures::future::join_all(scans).await;
+    let mut discoveries = vec![];
+    for result in results {
+        discoveries.extend(result?);
+    }
+    Ok(discoveries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::SimulatedBus;
+
+    #[tokio::test]
+    async fn fast_search_finds_servos() {
+        let bus = SimulatedBus::with_servos(&[1, 7, 200]);
+        let mut driver = crate::DynamixelDriver::with_driver(Box::new(bus));
+        let ids = driver.search_fast(Duration::from_millis(1)).await.unwrap();
+        assert_eq!(ids, vec![1, 7, 200]);
+    }
+
+    #[test]
+    fn timeout_grows_with_slow_answers() {
+        let mut timeout = AdaptiveTimeout::new(Duration::from_millis(5));
+        timeout.record_answer(Duration::from_millis(1));
+        assert_eq!(timeout.get(), Duration::from_millis(5));
+        timeout.record_answer(Duration::from_millis(8));
+        assert_eq!(timeout.get(), Duration::from_millis(16));
+        timeout.record_late_answer();
+        assert_eq!(timeout.get(), Duration::from_millis(32));
+        timeout.record_answer(Duration::from_secs(1));
+        assert_eq!(timeout.get(), MAX_SWEEP_TIMEOUT);
+    }
+}
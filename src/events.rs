@@ -0,0 +1,77 @@
+use tokio::sync::broadcast;
+
+use crate::circuit_breaker::CircuitState;
+use crate::failover::Switchover;
+use crate::instructions::StatusError;
+use crate::safety::SafetyPolicy;
+use crate::voltage::Brownout;
+
+/// Events buffered per subscriber before the slowest one starts missing events
+pub(crate) const EVENT_CAPACITY: usize = 64;
+
+/// Notification from the driver or its transport, see [`crate::DynamixelDriver::subscribe`]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DriverEvent {
+    /// [`crate::FailoverTransport`] switched to its other interface
+    Switchover(Switchover),
+    /// Circuit breaker marked a servo offline or saw it responding again
+    CircuitStateChanged { id: u8, state: CircuitState },
+    /// A servo answered with error flags set, such as overload or overheating
+    Alarm { id: u8, error: StatusError },
+    /// The [`SafetyPolicy`] turned off torque after servo `id` reported `error`
+    SafetyTorqueOff {
+        id: u8,
+        error: StatusError,
+        policy: SafetyPolicy,
+    },
+    /// Voltage of a servo dropped below the monitoring threshold
+    Brownout(Brownout),
+    /// Ids that answered a bus scan
+    Discovered(Vec<u8>),
+}
+
+/// [`DriverEvent`] tagged with the name of the driver that published it, see
+/// [`crate::DynamixelDriver::subscribe_named`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusEvent {
+    pub bus: Option<String>,
+    pub event: DriverEvent,
+}
+
+/// Events of one driver, tagged with the name it had when subscribing
+pub struct NamedEvents {
+    bus: Option<String>,
+    receiver: broadcast::Receiver<DriverEvent>,
+}
+
+impl NamedEvents {
+    pub(crate) fn new(bus: Option<String>, receiver: broadcast::Receiver<DriverEvent>) -> Self {
+        NamedEvents { bus, receiver }
+    }
+
+    pub fn bus(&self) -> Option<&str> {
+        self.bus.as_deref()
+    }
+
+    pub fn try_recv(&mut self) -> Result<BusEvent, broadcast::error::TryRecvError> {
+        let event = self.receiver.try_recv()?;
+        Ok(BusEvent {
+            bus: self.bus.clone(),
+            event,
+        })
+    }
+
+    pub async fn recv(&mut self) -> Result<BusEvent, broadcast::error::RecvError> {
+        let event = self.receiver.recv().await?;
+        Ok(BusEvent {
+            bus: self.bus.clone(),
+            event,
+        })
+    }
+}
+
+pub(crate) fn emit(sender: &broadcast::Sender<DriverEvent>, event: DriverEvent) {
+    // failing only means nobody is subscribed
+    let _ = sender.send(event);
+}
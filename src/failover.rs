@@ -0,0 +1,251 @@
+use crate::logging::warn;
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+
+use crate::events::{self, DriverEvent};
+
+use crate::instructions::{DynamixelDriverError, Instruction, Result};
+use crate::serial_driver::{ChecksumPolicy, FramedDriver, Status};
+use crate::stats::BusStats;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActiveTransport {
+    Primary,
+    Secondary,
+}
+
+/// Reported as [`DriverEvent::Switchover`] when [`FailoverTransport`] switches to the other interface
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Switchover {
+    pub to: ActiveTransport,
+    /// Consecutive errors that triggered the switch
+    pub errors: u32,
+}
+
+/// Transport over two interfaces wired to the same bus, switching to the other one
+/// after `error_threshold` consecutive transport errors
+///
+/// Servo reported errors don't count since they would fail on either interface.
+pub struct FailoverTransport {
+    primary: Box<dyn FramedDriver>,
+    secondary: Box<dyn FramedDriver>,
+    active: ActiveTransport,
+    error_threshold: u32,
+    consecutive_errors: u32,
+    events: Option<broadcast::Sender<DriverEvent>>,
+}
+
+impl FailoverTransport {
+    pub fn new(
+        primary: Box<dyn FramedDriver>,
+        secondary: Box<dyn FramedDriver>,
+        error_threshold: u32,
+    ) -> FailoverTransport {
+        FailoverTransport {
+            primary,
+            secondary,
+            active: ActiveTransport::Primary,
+            error_threshold: error_threshold.max(1),
+            consecutive_errors: 0,
+            events: None,
+        }
+    }
+
+    pub fn active(&self) -> ActiveTransport {
+        self.active
+    }
+
+    fn transport(&mut self) -> &mut dyn FramedDriver {
+        match self.active {
+            ActiveTransport::Primary => self.primary.as_mut(),
+            ActiveTransport::Secondary => self.secondary.as_mut(),
+        }
+    }
+
+    fn record<T>(&mut self, result: &Result<T>) {
+        match result {
+            Ok(_) | Err(DynamixelDriverError::StatusError(_)) => self.consecutive_errors = 0,
+            Err(_) => {
+                self.consecutive_errors += 1;
+                if self.consecutive_errors >= self.error_threshold {
+                    self.switch();
+                }
+            }
+        }
+    }
+
+    fn switch(&mut self) {
+        let switchover = Switchover {
+            to: match self.active {
+                ActiveTransport::Primary => ActiveTransport::Secondary,
+                ActiveTransport::Secondary => ActiveTransport::Primary,
+            },
+            errors: self.consecutive_errors,
+        };
+        warn!(
+            "switching to {:?} transport after {} consecutive errors",
+            switchover.to, switchover.errors
+        );
+        self.active = switchover.to;
+        self.consecutive_errors = 0;
+        if let Some(events) = &self.events {
+            events::emit(events, DriverEvent::Switchover(switchover));
+        }
+    }
+}
+
+#[async_trait]
+impl FramedDriver for FailoverTransport {
+    async fn send(&mut self, instruction: Instruction) -> Result<()> {
+        let result = self.transport().send(instruction).await;
+        if result.is_err() {
+            self.record(&result);
+        }
+        result
+    }
+
+    async fn receive(&mut self) -> Result<Status> {
+        let result = self.transport().receive().await;
+        self.record(&result);
+        result
+    }
+
+    async fn clear_io_buffers(&mut self) -> Result<()> {
+        self.transport().clear_io_buffers().await
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        let primary = self.primary.close().await;
+        let secondary = self.secondary.close().await;
+        primary.and(secondary)
+    }
+
+    fn set_checksum_policy(&mut self, policy: ChecksumPolicy) {
+        self.primary.set_checksum_policy(policy);
+        self.secondary.set_checksum_policy(policy);
+    }
+
+    fn set_mixed_protocols(&mut self, enabled: bool) {
+        self.primary.set_mixed_protocols(enabled);
+        self.secondary.set_mixed_protocols(enabled);
+    }
+
+    fn set_event_sender(&mut self, events: broadcast::Sender<DriverEvent>) {
+        self.primary.set_event_sender(events.clone());
+        self.secondary.set_event_sender(events.clone());
+        self.events = Some(events);
+    }
+
+    fn stats(&self) -> BusStats {
+        let primary = self.primary.stats();
+        let secondary = self.secondary.stats();
+        BusStats {
+            frames_decoded: primary.frames_decoded + secondary.frames_decoded,
+            checksum_mismatches: primary.checksum_mismatches + secondary.checksum_mismatches,
+            accepted_bad_checksums: primary.accepted_bad_checksums
+                + secondary.accepted_bad_checksums,
+            checksum_retries: primary.checksum_retries + secondary.checksum_retries,
+            bytes_sent: primary.bytes_sent + secondary.bytes_sent,
+            bytes_received: primary.bytes_received + secondary.bytes_received,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::StatusError;
+    use std::sync::{Arc, Mutex};
+
+    struct MockTransport {
+        name: &'static str,
+        log: Arc<Mutex<Vec<&'static str>>>,
+        responses: Vec<Result<Status>>,
+    }
+
+    #[async_trait]
+    impl FramedDriver for MockTransport {
+        async fn send(&mut self, _instruction: Instruction) -> Result<()> {
+            self.log.lock().unwrap().push(self.name);
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<Status> {
+            if self.responses.is_empty() {
+                return Err(DynamixelDriverError::Timeout);
+            }
+            self.responses.remove(0)
+        }
+
+        async fn clear_io_buffers(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn failover(
+        primary: Vec<Result<Status>>,
+        secondary: Vec<Result<Status>>,
+    ) -> (FailoverTransport, Arc<Mutex<Vec<&'static str>>>) {
+        let log = Arc::new(Mutex::new(vec![]));
+        let transport = FailoverTransport::new(
+            Box::new(MockTransport {
+                name: "primary",
+                log: log.clone(),
+                responses: primary,
+            }),
+            Box::new(MockTransport {
+                name: "secondary",
+                log: log.clone(),
+                responses: secondary,
+            }),
+            2,
+        );
+        (transport, log)
+    }
+
+    #[tokio::test]
+    async fn switches_after_threshold_and_reports() {
+        let (mut transport, log) = failover(vec![], vec![Ok(Status::new(1, vec![]))]);
+        let (sender, mut events) = broadcast::channel(8);
+        transport.set_event_sender(sender);
+        for _ in 0..2 {
+            transport.send(Instruction::ping(1)).await.unwrap();
+            assert!(transport.receive().await.is_err());
+        }
+        assert_eq!(transport.active(), ActiveTransport::Secondary);
+        transport.send(Instruction::ping(1)).await.unwrap();
+        assert_eq!(transport.receive().await.unwrap(), Status::new(1, vec![]));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["primary", "primary", "secondary"]
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            DriverEvent::Switchover(Switchover {
+                to: ActiveTransport::Secondary,
+                errors: 2
+            })
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn servo_errors_and_successes_reset_count() {
+        let status_error = StatusError::check_error(1).unwrap_err();
+        let (mut transport, _) = failover(
+            vec![
+                Err(DynamixelDriverError::Timeout),
+                Err(status_error),
+                Err(DynamixelDriverError::Timeout),
+                Ok(Status::new(1, vec![])),
+                Err(DynamixelDriverError::Timeout),
+            ],
+            vec![],
+        );
+        for _ in 0..5 {
+            let _ = transport.receive().await;
+        }
+        assert_eq!(transport.active(), ActiveTransport::Primary);
+    }
+}
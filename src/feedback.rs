@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Last known state of one servo in a [`GroupFeedback`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointFeedback {
+    /// Joint angle in degrees with calibration applied
+    pub position: f32,
+    /// Speed in rpm, positive CCW
+    pub speed: f32,
+    /// Load as a fraction of maximum torque, positive CCW
+    pub load: f32,
+    /// When the values were read
+    pub updated: Instant,
+}
+
+/// Feedback of a group of servos, keeping the last good values of joints that
+/// failed to answer so controllers can decide how to treat stale joints
+#[derive(Debug, Clone, Default)]
+pub struct GroupFeedback {
+    joints: BTreeMap<u8, Option<JointFeedback>>,
+    failures: BTreeMap<u8, u32>,
+}
+
+impl GroupFeedback {
+    pub fn new(ids: &[u8]) -> GroupFeedback {
+        GroupFeedback {
+            joints: ids.iter().map(|&id| (id, None)).collect(),
+            failures: ids.iter().map(|&id| (id, 0)).collect(),
+        }
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = u8> + '_ {
+        self.joints.keys().copied()
+    }
+
+    /// Last successfully read feedback, None if the servo never answered
+    pub fn get(&self, id: u8) -> Option<&JointFeedback> {
+        self.joints.get(&id).and_then(|joint| joint.as_ref())
+    }
+
+    /// Reads that failed since the last successful one
+    pub fn consecutive_failures(&self, id: u8) -> u32 {
+        self.failures.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Age of the last successful read, None if the servo never answered
+    pub fn age(&self, id: u8, now: Instant) -> Option<Duration> {
+        self.get(id)
+            .map(|joint| now.saturating_duration_since(joint.updated))
+    }
+
+    /// True if the joint was never read or its last successful read is older than `max_age`
+    pub fn is_stale(&self, id: u8, max_age: Duration, now: Instant) -> bool {
+        self.age(id, now).map(|age| age > max_age).unwrap_or(true)
+    }
+
+    pub fn stale_joints(&self, max_age: Duration, now: Instant) -> Vec<u8> {
+        self.ids()
+            .filter(|&id| self.is_stale(id, max_age, now))
+            .collect()
+    }
+
+    pub(crate) fn update(&mut self, id: u8, feedback: JointFeedback) {
+        self.joints.insert(id, Some(feedback));
+        self.failures.insert(id, 0);
+    }
+
+    pub(crate) fn record_failure(&mut self, id: u8) {
+        *self.failures.entry(id).or_insert(0) += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn staleness_tracks_last_successful_read() {
+        let start = Instant::now();
+        let mut feedback = GroupFeedback::new(&[1, 2]);
+        assert_eq!(
+            feedback.stale_joints(Duration::from_secs(1), start),
+            vec![1, 2]
+        );
+        feedback.update(
+            1,
+            JointFeedback {
+                position: 10.0,
+                speed: 0.0,
+                load: 0.0,
+                updated: start,
+            },
+        );
+        feedback.record_failure(2);
+        feedback.record_failure(2);
+        let later = start + Duration::from_millis(500);
+        assert_eq!(
+            feedback.stale_joints(Duration::from_secs(1), later),
+            vec![2]
+        );
+        assert_eq!(feedback.age(1, later), Some(Duration::from_millis(500)));
+        assert_eq!(feedback.consecutive_failures(2), 2);
+        feedback.record_failure(1);
+        // a failed read keeps the last values but they age
+        assert_eq!(feedback.get(1).unwrap().position, 10.0);
+        assert!(feedback.is_stale(1, Duration::from_millis(100), later));
+    }
+}
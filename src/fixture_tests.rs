@@ -0,0 +1,202 @@
+//! Replays hand-written byte streams from `testdata/fixtures` through the codecs
+//! and compares the decoded frames with the expectations stored next to them.
+//!
+//! The fixtures follow the worked examples of the Protocol 1.0 and 2.0 e-manuals and
+//! the MX control tables, none of them were captured from hardware. Replaying real bus
+//! captures needs an ingestion step that is left for a follow-up.
+
+use bytes::BytesMut;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Arc;
+use tokio_util::codec::Decoder;
+
+use crate::instructions::{DynamixelDriverError, Instruction, HARDWARE_ALERT};
+use crate::protocol::{Protocol, Protocol1, Protocol2};
+use crate::serial_driver::{DynamixelProtocol, InstructionDecoder, Status};
+
+#[derive(Debug, PartialEq)]
+enum Frame {
+    Status(Status),
+    Instruction(Instruction),
+    Error(&'static str),
+}
+
+fn error_kind(error: &DynamixelDriverError) -> &'static str {
+    match error {
+        DynamixelDriverError::ChecksumError(_, _) => "checksum",
+        DynamixelDriverError::HeaderLenTooSmall(_) => "header_len",
+        DynamixelDriverError::StatusError(_) => "status",
+        DynamixelDriverError::DecodingError(_) => "decoding",
+        _ => "other",
+    }
+}
+
+fn parse_hex(bytes: &str, location: &str) -> Vec<u8> {
+    bytes
+        .split_whitespace()
+        .map(|byte| {
+            u8::from_str_radix(byte, 16)
+                .unwrap_or_else(|_| panic!("{}: invalid hex byte {:?}", location, byte))
+        })
+        .collect()
+}
+
+struct Replay {
+    protocol: Arc<dyn Protocol>,
+    codec: DynamixelProtocol,
+    buffer: BytesMut,
+    instruction_codec: InstructionDecoder,
+    instruction_buffer: BytesMut,
+    decoded: VecDeque<Frame>,
+}
+
+// decode until the codec stops making progress, same as it would waiting on the port
+fn decode_all<D>(
+    codec: &mut D,
+    buffer: &mut BytesMut,
+    decoded: &mut VecDeque<Frame>,
+    frame: fn(D::Item) -> Frame,
+) where
+    D: Decoder<Error = DynamixelDriverError>,
+{
+    loop {
+        let buffered = buffer.len();
+        match codec.decode(buffer) {
+            Ok(Some(item)) => decoded.push_back(frame(item)),
+            Ok(None) if buffer.len() == buffered => break,
+            Ok(None) => (),
+            Err(error) => decoded.push_back(Frame::Error(error_kind(&error))),
+        }
+    }
+}
+
+impl Replay {
+    fn new(protocol: Arc<dyn Protocol>) -> Self {
+        Replay {
+            codec: DynamixelProtocol::with_protocol(protocol.clone()),
+            buffer: BytesMut::new(),
+            instruction_codec: InstructionDecoder::with_protocol(protocol.clone()),
+            protocol,
+            instruction_buffer: BytesMut::new(),
+            decoded: VecDeque::new(),
+        }
+    }
+
+    fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+        decode_all(
+            &mut self.codec,
+            &mut self.buffer,
+            &mut self.decoded,
+            Frame::Status,
+        );
+    }
+
+    fn feed_instructions(&mut self, bytes: &[u8]) {
+        self.instruction_buffer.extend_from_slice(bytes);
+        decode_all(
+            &mut self.instruction_codec,
+            &mut self.instruction_buffer,
+            &mut self.decoded,
+            Frame::Instruction,
+        );
+    }
+}
+
+fn run_corpus_file(path: &Path) -> usize {
+    let contents = std::fs::read_to_string(path).unwrap();
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    let mut case_name = String::new();
+    let mut protocol: Arc<dyn Protocol> = Arc::new(Protocol1);
+    let mut replay = Replay::new(protocol.clone());
+    let mut cases = 0;
+
+    let finish_case = |replay: &Replay, case_name: &str| {
+        assert!(
+            replay.decoded.is_empty(),
+            "{} case {:?}: unexpected frames {:?}",
+            file_name,
+            case_name,
+            replay.decoded
+        );
+    };
+
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let location = format!("{}:{} ({})", file_name, line_number + 1, case_name);
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match keyword {
+            "protocol" => {
+                protocol = match rest {
+                    "1" => Arc::new(Protocol1),
+                    "2" => Arc::new(Protocol2),
+                    other => panic!("{}: unknown protocol {:?}", location, other),
+                };
+            }
+            "case" => {
+                finish_case(&replay, &case_name);
+                case_name = rest.to_owned();
+                replay = Replay::new(protocol.clone());
+                cases += 1;
+            }
+            ">" => replay.feed(&parse_hex(rest, &location)),
+            ">>" => replay.feed_instructions(&parse_hex(rest, &location)),
+            "status" => {
+                let mut values = parse_hex(rest, &location);
+                let id = values.remove(0);
+                let expected = Frame::Status(Status::new(id, values));
+                assert_eq!(replay.decoded.pop_front(), Some(expected), "{}", location);
+            }
+            "alert" => {
+                let mut values = parse_hex(rest, &location);
+                let id = values.remove(0);
+                let expected = Frame::Status(Status::with_error(id, HARDWARE_ALERT, values));
+                assert_eq!(replay.decoded.pop_front(), Some(expected), "{}", location);
+            }
+            "instruction" => {
+                let values = parse_hex(rest, &location);
+                let [id, opcode, params @ ..] = &values[..] else {
+                    panic!("{}: instruction needs an id and an opcode", location);
+                };
+                let expected =
+                    Frame::Instruction(replay.protocol.instruction(*id, *opcode, params));
+                assert_eq!(replay.decoded.pop_front(), Some(expected), "{}", location);
+            }
+            "error" => {
+                let expected = Frame::Error(match rest {
+                    "checksum" => "checksum",
+                    "header_len" => "header_len",
+                    "status" => "status",
+                    "decoding" => "decoding",
+                    other => panic!("{}: unknown error kind {:?}", location, other),
+                });
+                assert_eq!(replay.decoded.pop_front(), Some(expected), "{}", location);
+            }
+            "remaining" => {
+                let expected: usize = rest.parse().unwrap();
+                let buffered = replay.buffer.len() + replay.instruction_buffer.len();
+                assert_eq!(buffered, expected, "{}", location);
+            }
+            other => panic!("{}: unknown keyword {:?}", location, other),
+        }
+    }
+    finish_case(&replay, &case_name);
+    cases
+}
+
+#[test]
+fn fixture_corpus() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/fixtures");
+    let mut cases = 0;
+    for entry in std::fs::read_dir(corpus).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map(|ext| ext == "txt").unwrap_or(false) {
+            cases += run_corpus_file(&path);
+        }
+    }
+    assert!(cases > 0, "fixture corpus is empty");
+}
@@ -0,0 +1,176 @@
+//! Intercepting instructions on their way to real hardware, for hardware-in-the-loop tests
+//! of application code.
+
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::events::DriverEvent;
+use crate::instructions::{DynamixelDriverError, Instruction, Result};
+use crate::serial_driver::{ChecksumPolicy, FramedDriver, Status};
+use crate::stats::BusStats;
+
+/// What a hook does with the packet it was called for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HookAction {
+    #[default]
+    Pass,
+    /// Pass the packet on after waiting
+    Delay(Duration),
+    /// Never send the instruction or lose the status, the driver sees a timeout
+    Drop,
+}
+
+type SendHook = Box<dyn FnMut(&Instruction) -> HookAction + Send + Sync>;
+type ReceiveHook = Box<dyn FnMut(&Instruction, &Result<Status>) -> HookAction + Send + Sync>;
+
+/// Transport calling hooks before every instruction is sent and after every status is received
+///
+/// Hooks can match on the instruction and assert by panicking, everything else is passed
+/// on to the wrapped transport.
+///
+/// ```no_run
+/// # fn example(port: Box<dyn dynamixel_driver::FramedDriver>) {
+/// use dynamixel_driver::{DynamixelDriver, HookAction, HookedTransport};
+/// use std::time::Duration;
+///
+/// let transport = HookedTransport::new(port)
+///     .before_send(|instruction| match instruction.id() {
+///         3 => HookAction::Drop,
+///         _ => HookAction::Pass,
+///     })
+///     .after_receive(|_, _| HookAction::Delay(Duration::from_millis(5)));
+/// let driver = DynamixelDriver::with_driver(Box::new(transport));
+/// # }
+/// ```
+pub struct HookedTransport {
+    inner: Box<dyn FramedDriver>,
+    before_send: Option<SendHook>,
+    after_receive: Option<ReceiveHook>,
+    last_instruction: Option<Instruction>,
+    dropped: bool,
+}
+
+impl HookedTransport {
+    pub fn new(inner: Box<dyn FramedDriver>) -> HookedTransport {
+        HookedTransport {
+            inner,
+            before_send: None,
+            after_receive: None,
+            last_instruction: None,
+            dropped: false,
+        }
+    }
+
+    pub fn before_send<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&Instruction) -> HookAction + Send + Sync + 'static,
+    {
+        self.before_send = Some(Box::new(hook));
+        self
+    }
+
+    /// Called with the instruction the status answers
+    pub fn after_receive<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&Instruction, &Result<Status>) -> HookAction + Send + Sync + 'static,
+    {
+        self.after_receive = Some(Box::new(hook));
+        self
+    }
+}
+
+#[async_trait]
+impl FramedDriver for HookedTransport {
+    async fn send(&mut self, instruction: Instruction) -> Result<()> {
+        let action = match &mut self.before_send {
+            Some(hook) => hook(&instruction),
+            None => HookAction::Pass,
+        };
+        self.last_instruction = Some(instruction.clone());
+        self.dropped = action == HookAction::Drop;
+        match action {
+            HookAction::Pass => self.inner.send(instruction).await,
+            HookAction::Delay(delay) => {
+                tokio::time::sleep(delay).await;
+                self.inner.send(instruction).await
+            }
+            HookAction::Drop => Ok(()),
+        }
+    }
+
+    async fn receive(&mut self) -> Result<Status> {
+        if self.dropped {
+            return Err(DynamixelDriverError::Timeout);
+        }
+        let result = self.inner.receive().await;
+        let action = match (&mut self.after_receive, &self.last_instruction) {
+            (Some(hook), Some(instruction)) => hook(instruction, &result),
+            _ => HookAction::Pass,
+        };
+        match action {
+            HookAction::Pass => result,
+            HookAction::Delay(delay) => {
+                tokio::time::sleep(delay).await;
+                result
+            }
+            HookAction::Drop => Err(DynamixelDriverError::Timeout),
+        }
+    }
+
+    async fn clear_io_buffers(&mut self) -> Result<()> {
+        self.inner.clear_io_buffers().await
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.inner.close().await
+    }
+
+    fn set_checksum_policy(&mut self, policy: ChecksumPolicy) {
+        self.inner.set_checksum_policy(policy);
+    }
+
+    fn set_mixed_protocols(&mut self, enabled: bool) {
+        self.inner.set_mixed_protocols(enabled);
+    }
+
+    fn set_event_sender(&mut self, events: broadcast::Sender<DriverEvent>) {
+        self.inner.set_event_sender(events);
+    }
+
+    fn stats(&self) -> BusStats {
+        self.inner.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::SimulatedBus;
+    use crate::DynamixelDriver;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn hooks_drop_and_observe_packets() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let recorded = seen.clone();
+        let transport = HookedTransport::new(Box::new(SimulatedBus::with_servos(&[1, 2])))
+            .before_send(|instruction| match instruction.id() {
+                2 => HookAction::Drop,
+                _ => HookAction::Pass,
+            })
+            .after_receive(move |instruction, status| {
+                assert!(status.is_ok());
+                recorded.lock().unwrap().push(instruction.id());
+                HookAction::Delay(Duration::from_millis(1))
+            });
+        let mut driver = DynamixelDriver::with_driver(Box::new(transport));
+        driver.ping(1).await.unwrap();
+        assert!(matches!(
+            driver.ping(2).await,
+            Err(DynamixelDriverError::Timeout)
+        ));
+        driver.ping(1).await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![1, 1]);
+    }
+}
@@ -1,5 +1,17 @@
+use std::borrow::Cow;
 use thiserror::Error;
 
+use crate::byte_stuffing;
+
+use crate::model::{Capability, ServoModel};
+use crate::protocol::{Protocol, Protocol1, Protocol2, BULK_READ, BULK_WRITE};
+use crate::serial_driver::ResponseKind;
+
+/// Id the USB2AX adapter answers to
+pub(crate) const USB2AX_ID: u8 = 0xFD;
+
+const PROTOCOL2_HEADER: [u8; 4] = [0xFF, 0xFF, 0xFD, 0x00];
+
 pub(crate) type Result<T> = std::result::Result<T, DynamixelDriverError>;
 
 #[derive(Error, Debug)]
@@ -9,8 +21,9 @@ pub enum DynamixelDriverError {
     Timeout,
     #[error("status error {0:?}")]
     StatusError(StatusError),
+    /// Inverted sum of Protocol 1.0 or CRC-16 of Protocol 2.0
     #[error("checksum error expected {0:?} received {1:?}")]
-    ChecksumError(u8, u8),
+    ChecksumError(u16, u16),
     #[error("header length too small {0:?}")]
     HeaderLenTooSmall(usize),
     #[error("reading error")]
@@ -21,8 +34,136 @@ pub enum DynamixelDriverError {
     DecodingError(&'static str),
     #[error("Id mismatch error. Expected {0:?} got {1:?}")]
     IdMismatchError(u8, u8),
-    #[error("failed to open serial port")]
-    FailedOpeningSerialPort,
+    #[error("servo {id} answered with {actual} parameters, expected {expected}")]
+    ResponseLengthMismatch {
+        id: u8,
+        expected: usize,
+        actual: usize,
+    },
+    #[cfg(feature = "serial")]
+    #[error("failed to open serial port {port:?}: {source}")]
+    FailedOpeningSerialPort {
+        port: String,
+        source: tokio_serial::Error,
+    },
+    #[error("write to EEPROM address {0:?} rejected while EEPROM writes are locked")]
+    EepromWriteLocked(u8),
+    #[error("id {0} is already used by another servo")]
+    IdInUse(u8),
+    #[error("id {0} is reserved, servo ids range from 0 to 253")]
+    ReservedId(u8),
+    #[error("servo {id} is a {actual:?} but {expected:?} was expected")]
+    ModelMismatch {
+        id: u8,
+        expected: ServoModel,
+        actual: ServoModel,
+    },
+    #[error("no expected model declared for servo {0}, refusing to verify identity")]
+    UnknownExpectedModel(u8),
+    #[error("{cause}, restoring registers (id, address) {failed:?} failed as well")]
+    RollbackIncomplete {
+        cause: Box<DynamixelDriverError>,
+        failed: Vec<(u8, u8)>,
+    },
+    /// Error of a driver named with [`crate::DynamixelDriver::set_name`], see [`Self::root`]
+    #[error("{bus}: {source}")]
+    Bus {
+        bus: String,
+        source: Box<DynamixelDriverError>,
+    },
+    #[error("{0:?} has no known stall torque")]
+    UnknownStallTorque(ServoModel),
+    #[error("invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error(
+        "goal of {degrees} degrees for servo {id} is outside its joint limits {min:?} to {max:?}"
+    )]
+    JointLimitExceeded {
+        id: u8,
+        degrees: f32,
+        min: Option<f32>,
+        max: Option<f32>,
+    },
+    #[error("goal of {degrees} degrees for servo {id} is outside the range of a {model:?}")]
+    PositionOutOfRange {
+        id: u8,
+        degrees: f32,
+        model: ServoModel,
+    },
+    #[error("goal position {goal} of servo {id} is outside its angle limits {cw} to {ccw}")]
+    AngleLimitExceeded {
+        id: u8,
+        goal: u16,
+        cw: u16,
+        ccw: u16,
+    },
+    #[error("no servo configured for joint {0:?}")]
+    UnknownJoint(String),
+    #[error("servo {0} is offline")]
+    ServoOffline(u8),
+    #[error("verification of servo {id} address {addr} failed, expected {expected} read {actual}")]
+    VerificationError {
+        id: u8,
+        addr: u8,
+        expected: u32,
+        actual: u32,
+    },
+    #[error("servo {0} did not finish moving in time")]
+    MotionTimeout(u8),
+    #[error("servo {id} stopped at {position} degrees before reaching its goal")]
+    MotionStalled { id: u8, position: f32 },
+    #[error("bus daemon error: {0}")]
+    DaemonError(String),
+    #[error("operation not supported by transport")]
+    UnsupportedByTransport,
+    #[error("servo {id} is a {model:?} which doesn't support {capability:?}")]
+    Unsupported {
+        id: u8,
+        model: ServoModel,
+        capability: Capability,
+    },
+    #[error("received {received} from servo {id} while expecting {expected}")]
+    UnexpectedResponse {
+        id: u8,
+        expected: ResponseKind,
+        received: ResponseKind,
+    },
+    #[error("goal torque {0} is not a finite number")]
+    InvalidTorque(f32),
+    #[error("current of {0} mA is not a finite positive number in range of the register")]
+    InvalidCurrent(f32),
+    #[error("profile value {0} is not a finite positive number in range of the register")]
+    InvalidProfile(f32),
+    #[error("PWM duty {0} is not a finite number from -1.0 to 1.0")]
+    InvalidPwm(f32),
+    #[error("servo {0} has torque enabled, disable it first")]
+    TorqueEnabled(u8),
+    #[error("homing offset of {0} steps is outside -1044479 to 1044479")]
+    HomingOffsetOutOfRange(i32),
+    #[error("indirect addressing remaps up to 28 bytes, {0} were requested")]
+    TooManyIndirectBytes(usize),
+    #[error("servo {0} doesn't speak Protocol 2.0")]
+    Protocol2Required(u8),
+    #[error("servo {id} speaks Protocol 2.0 and has no equivalent of AX register {addr}")]
+    NoProtocol2Register { id: u8, addr: u8 },
+    #[error("servo {0} speaks another protocol generation than the sync write")]
+    SyncProtocolMismatch(u8),
+    #[error("sync writes carry 1, 2 or 4 bytes per servo, not {0}")]
+    InvalidSyncDataLength(u8),
+    #[error("value {value} doesn't fit the {data_len} bytes per servo of the sync write")]
+    SyncValueTooWide { value: u32, data_len: u8 },
+    #[error("servo {0} is listed more than once in a bulk write")]
+    DuplicateBulkWrite(u8),
+    #[error("write of {len} bytes at address {addr} of servo {id} runs past address 65535")]
+    AddressOverflow { id: u8, addr: u16, len: usize },
+    #[error("servo {id} is a {model:?} which has no {len} byte register at address {addr}")]
+    RegisterOutOfRange {
+        id: u8,
+        model: ServoModel,
+        addr: u16,
+        len: usize,
+    },
+    #[cfg(feature = "serial")]
     #[error("tokio serial error {0:?}")]
     TokioSerialError(#[from] tokio_serial::Error),
 }
@@ -30,15 +171,26 @@ pub enum DynamixelDriverError {
 impl DynamixelDriverError {
     pub fn is_recoverable(&self) -> bool {
         matches!(
-            self,
+            self.root(),
             DynamixelDriverError::Timeout
                 | DynamixelDriverError::StatusError(_)
                 | DynamixelDriverError::ChecksumError(_, _)
                 | DynamixelDriverError::ReadingError
                 | DynamixelDriverError::DecodingError(_)
                 | DynamixelDriverError::IdMismatchError(_, _)
+                | DynamixelDriverError::ResponseLengthMismatch { .. }
+                | DynamixelDriverError::UnexpectedResponse { .. }
+                | DynamixelDriverError::ServoOffline(_)
         )
     }
+
+    /// The error without the bus name added by named drivers
+    pub fn root(&self) -> &DynamixelDriverError {
+        match self {
+            DynamixelDriverError::Bus { source, .. } => source.root(),
+            error => error,
+        }
+    }
 }
 
 #[derive(PartialEq, Debug, Eq, Clone)]
@@ -50,14 +202,27 @@ pub struct StatusError {
     pub overheating_error: bool,
     pub angle_limit_error: bool,
     pub input_voltage_error: bool,
+    /// Protocol 2.0 alert, details are in the hardware error status register
+    pub hardware_alert: bool,
 }
 
+/// Alert bit of the status error byte, only Protocol 2.0 servos set it
+pub(crate) const HARDWARE_ALERT: u8 = 1 << 7;
+
 impl StatusError {
     pub(crate) fn check_error(flag: u8) -> Result<()> {
+        match StatusError::from_flags(flag) {
+            Some(status_error) => Err(DynamixelDriverError::StatusError(status_error)),
+            None => Ok(()),
+        }
+    }
+
+    /// Decodes the error byte of a status packet, None if no flag is set
+    pub fn from_flags(flag: u8) -> Option<StatusError> {
         if flag == 0 {
-            return Ok(());
+            return None;
         }
-        let status_error = StatusError {
+        Some(StatusError {
             input_voltage_error: flag & (1 << 0) != 0,
             angle_limit_error: flag & (1 << 1) != 0,
             overheating_error: flag & (1 << 2) != 0,
@@ -65,8 +230,77 @@ impl StatusError {
             checksum_error: flag & (1 << 4) != 0,
             overload_error: flag & (1 << 5) != 0,
             instruction_error: flag & (1 << 6) != 0,
-        };
-        Err(DynamixelDriverError::StatusError(status_error))
+            hardware_alert: flag & HARDWARE_ALERT != 0,
+        })
+    }
+
+    /// Merges the hardware error status register of a Protocol 2.0 servo into the
+    /// matching flags, encoder and electrical shock errors only show as the alert
+    pub(crate) fn with_hardware_error(mut self, status: HardwareErrorStatus) -> StatusError {
+        self.input_voltage_error |= status.input_voltage_error;
+        self.overheating_error |= status.overheating_error;
+        self.overload_error |= status.overload_error;
+        self
+    }
+
+    /// Error byte with the flags of this error set
+    pub fn flags(&self) -> u8 {
+        [
+            self.input_voltage_error,
+            self.angle_limit_error,
+            self.overheating_error,
+            self.range_error,
+            self.checksum_error,
+            self.overload_error,
+            self.instruction_error,
+            self.hardware_alert,
+        ]
+        .iter()
+        .enumerate()
+        .filter(|(_, set)| **set)
+        .fold(0, |flags, (bit, _)| flags | 1 << bit)
+    }
+}
+
+/// Hardware Error Status register of a Protocol 2.0 servo, errors stay latched until the
+/// servo is rebooted
+#[derive(PartialEq, Debug, Eq, Clone, Copy, Default)]
+pub struct HardwareErrorStatus {
+    pub input_voltage_error: bool,
+    pub overheating_error: bool,
+    pub encoder_error: bool,
+    pub electrical_shock_error: bool,
+    pub overload_error: bool,
+}
+
+impl HardwareErrorStatus {
+    pub fn from_flags(flags: u8) -> HardwareErrorStatus {
+        HardwareErrorStatus {
+            input_voltage_error: flags & (1 << 0) != 0,
+            overheating_error: flags & (1 << 2) != 0,
+            encoder_error: flags & (1 << 3) != 0,
+            electrical_shock_error: flags & (1 << 4) != 0,
+            overload_error: flags & (1 << 5) != 0,
+        }
+    }
+
+    /// Register value with the flags of this status set
+    pub fn flags(&self) -> u8 {
+        [
+            (self.input_voltage_error, 0),
+            (self.overheating_error, 2),
+            (self.encoder_error, 3),
+            (self.electrical_shock_error, 4),
+            (self.overload_error, 5),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |flags, (_, bit)| flags | 1 << bit)
+    }
+
+    /// True if no hardware error is latched
+    pub fn is_clear(&self) -> bool {
+        self.flags() == 0
     }
 }
 
@@ -94,11 +328,14 @@ impl std::fmt::Display for StatusError {
         if self.instruction_error {
             description.push_str("instruction_error ");
         }
+        if self.hardware_alert {
+            description.push_str("hardware_alert ");
+        }
         write!(f, "{}", description)
     }
 }
 
-pub(crate) fn calc_checksum(payload: &[u8]) -> u8 {
+pub fn calc_checksum(payload: &[u8]) -> u8 {
     let mut sum: u8 = 0;
     for b in payload {
         sum = sum.wrapping_add(*b);
@@ -107,95 +344,175 @@ pub(crate) fn calc_checksum(payload: &[u8]) -> u8 {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) struct Instruction {
+pub struct Instruction {
     payload: Vec<u8>,
 }
 
 impl Instruction {
+    /// Parses a complete instruction packet, validating header, length and checksum
+    pub fn from_bytes(packet: &[u8]) -> Result<Self> {
+        Protocol1.decode_instruction(packet)
+    }
+
+    pub(crate) fn from_payload(payload: Vec<u8>) -> Self {
+        Instruction { payload }
+    }
+
     pub fn read_instruction(id: u8, addr: u8, length: u8) -> Self {
-        let mut data = vec![
-            0xFF, // header
-            0xFF, id,   // ID
-            0x04, // Len
-            0x02, // Instruction
-            addr, length,
-        ];
-        let checksum = calc_checksum(&data[2..]);
-        data.push(checksum);
-        Instruction { payload: data }
+        Protocol1.read(id, addr as u16, length as u16)
+    }
+
+    /// Write of consecutive bytes starting at `addr`
+    pub fn write(id: u8, addr: u8, data: &[u8]) -> Self {
+        Protocol1.write(id, addr as u16, data)
     }
 
     pub fn write_u8(id: u8, addr: u8, data: u8) -> Self {
-        let len = 4;
-        let mut payload = vec![
-            0xFF, // header
-            0xFF, id,   // ID
-            len,  // Length
-            0x03, // Instruction
-            addr, data,
-        ];
-        let checksum = calc_checksum(&payload[2..]);
-        payload.push(checksum);
-        Instruction { payload }
+        Protocol1.write(id, addr as u16, &[data])
     }
 
     pub fn write_u16(id: u8, addr: u8, data: u16) -> Self {
-        let len = 5;
+        Protocol1.write(id, addr as u16, &data.to_le_bytes())
+    }
+
+    /// Write that is held by the servo until an [`Self::action`] is received
+    pub fn reg_write(id: u8, addr: u8, data: &[u8]) -> Self {
+        Protocol1.reg_write(id, addr as u16, data)
+    }
+
+    pub fn action(id: u8) -> Self {
+        Protocol1.action(id)
+    }
+
+    pub fn ping(id: u8) -> Self {
+        Protocol1.ping(id)
+    }
+
+    pub fn sync_command(addr: u8, data_len: u8, commands: Vec<SyncCommand>) -> Self {
+        if !matches!(data_len, 1 | 2 | 4) {
+            unimplemented!("Sync write only implement for u8, u16 and u32");
+        }
+        Protocol1.sync_write(addr as u16, data_len as u16, &commands)
+    }
+
+    /// BULK_READ of MX servos, each listed servo answers with its own status packet
+    /// in the order of `reads`
+    pub fn bulk_read(reads: &[BulkRead]) -> Self {
+        let mut params = Vec::with_capacity(1 + reads.len() * 3);
+        params.push(0x00);
+        for read in reads {
+            params.extend_from_slice(&[read.len, read.id, read.addr]);
+        }
+        Protocol1.instruction(0xFE, BULK_READ, &params)
+    }
+
+    /// Protocol 2.0 BULK_WRITE, broadcast without an answer
+    pub fn bulk_write(writes: &BulkWrite) -> Self {
+        let mut params = vec![];
+        for entry in writes.entries() {
+            params.push(entry.id);
+            params.extend_from_slice(&entry.addr.to_le_bytes());
+            params.extend_from_slice(&(entry.data.len() as u16).to_le_bytes());
+            params.extend_from_slice(&entry.data);
+        }
+        Protocol2.instruction(0xFE, BULK_WRITE, &params)
+    }
+
+    /// SYNC_READ handled by the USB2AX adapter firmware, which polls every servo
+    /// and answers with a single status packet holding all values in order
+    pub fn usb2ax_sync_read(addr: u8, data_len: u8, ids: &[u8]) -> Self {
         let mut payload = vec![
             0xFF, // header
             0xFF,
-            id,   // ID
-            len,  // Length
-            0x03, // Instruction
+            0xFE,                // Always broadcast ID
+            ids.len() as u8 + 4, // Len
+            0x84,                // Instruction
             addr,
-            data as u8,
-            (data >> 8) as u8,
+            data_len,
         ];
+        payload.extend_from_slice(ids);
         let checksum = calc_checksum(&payload[2..]);
         payload.push(checksum);
         Instruction { payload }
     }
 
-    pub fn ping(id: u8) -> Self {
-        let mut payload = vec![
-            0xFF, // header
-            0xFF, id,   // ID
-            0x02, // Len
-            0x01, // Instruction
-        ];
-        let checksum = calc_checksum(&payload[2..]);
-        payload.push(checksum);
-        Instruction { payload }
+    // Protocol 1.0 packets can't start with this header, their length is at least 2
+    pub(crate) fn is_protocol2(&self) -> bool {
+        self.payload.starts_with(&PROTOCOL2_HEADER)
     }
 
-    pub fn sync_command(addr: u8, data_len: u8, commands: Vec<SyncCommand>) -> Self {
-        let len = (data_len + 1) * commands.len() as u8 + 4;
-        let mut data = vec![
-            0xFF, // header
-            0xFF, 0xFE, // Always broadcast ID
-            len,  // Len
-            0x83, // Instruction
-            addr, data_len,
-        ];
-        // add params
-        for entry in &commands {
-            data.push(entry.id);
-            match data_len {
-                1 => {
-                    data.push(entry.value as u8);
-                }
-                2 => {
-                    data.push(entry.value as u8);
-                    data.push((entry.value >> 8) as u8);
-                }
-                _ => {
-                    unimplemented!("Sync write only implement for u8 and u16");
-                }
-            }
+    pub fn id(&self) -> u8 {
+        match self.is_protocol2() {
+            true => self.payload[4],
+            false => self.payload[2],
+        }
+    }
+
+    pub fn opcode(&self) -> u8 {
+        match self.is_protocol2() {
+            true => self.payload[7],
+            false => self.payload[4],
         }
-        let checksum = calc_checksum(&data[2..]);
-        data.push(checksum);
-        Instruction { payload: data }
+    }
+
+    /// Parameters between the opcode and the checksum, with byte stuffing removed
+    pub fn params(&self) -> Cow<'_, [u8]> {
+        let end = self.payload.len();
+        if !self.is_protocol2() {
+            return Cow::Borrowed(&self.payload[5..end - 1]);
+        }
+        let body = byte_stuffing::unstuff(&self.payload[7..end - 2])
+            .expect("instructions are stuffed when they are built or parsed");
+        Cow::Owned(body[1..].to_vec())
+    }
+
+    /// Id the status packet is expected from, the USB2AX answers sync reads itself
+    pub fn response_id(&self) -> u8 {
+        match self.opcode() {
+            0x84 if !self.is_protocol2() => USB2AX_ID,
+            _ => self.id(),
+        }
+    }
+
+    // header, id, length, error and checksum around the status parameters, Protocol 2.0
+    // adds a longer header, a second length byte, the status instruction and a CRC
+    fn status_overhead(&self) -> usize {
+        match self.is_protocol2() {
+            true => 11,
+            false => 6,
+        }
+    }
+
+    /// Size of the status packet the servo answers with, zero for broadcasts
+    pub fn expected_response_len(&self) -> usize {
+        let overhead = self.status_overhead();
+        let params = self.params();
+        match (self.opcode(), self.is_protocol2()) {
+            // USB2AX answers with the data of every listed servo
+            (0x84, false) => overhead + params[1] as usize * (params.len() - 2),
+            // every servo answers with a status of its own
+            (BULK_READ, false) => params[1..]
+                .chunks_exact(3)
+                .map(|read| overhead + read[0] as usize)
+                .sum(),
+            _ if self.id() == 0xFE => 0,
+            // read returns the requested number of bytes
+            (0x02, false) => overhead + params[1] as usize,
+            (0x02, true) => overhead + u16::from_le_bytes([params[2], params[3]]) as usize,
+            // model number and firmware version
+            (0x01, true) => overhead + 3,
+            _ => overhead,
+        }
+    }
+
+    /// Number of parameters in the status answering this instruction
+    pub(crate) fn expected_response_params(&self) -> usize {
+        self.expected_response_len()
+            .saturating_sub(self.status_overhead())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.payload
     }
 
     pub fn serialize(self) -> Vec<u8> {
@@ -203,6 +520,83 @@ impl Instruction {
     }
 }
 
+/// Register range of one servo in a [`Instruction::bulk_read`]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct BulkRead {
+    pub id: u8,
+    pub addr: u8,
+    pub len: u8,
+}
+
+impl BulkRead {
+    pub fn new(id: u8, addr: u8, len: u8) -> BulkRead {
+        BulkRead { id, addr, len }
+    }
+}
+
+/// Register write of one servo in a [`BulkWrite`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkWriteEntry {
+    pub id: u8,
+    pub addr: u16,
+    pub data: Vec<u8>,
+}
+
+/// Builder of a Protocol 2.0 BULK_WRITE, which writes a different register of every
+/// listed servo in one packet
+///
+/// Every servo can only be listed once, values are written little endian.
+///
+/// ```
+/// use dynamixel_driver::BulkWrite;
+///
+/// // goal position of an XM430 and of an MX-28 with 2.0 firmware, then an LED
+/// let writes = BulkWrite::new()
+///     .u32(1, 116, 2048)
+///     .u32(2, 116, 1024)
+///     .u8(3, 65, 1);
+/// assert_eq!(writes.entries().len(), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkWrite {
+    entries: Vec<BulkWriteEntry>,
+}
+
+impl BulkWrite {
+    pub fn new() -> BulkWrite {
+        BulkWrite::default()
+    }
+
+    pub fn bytes(mut self, id: u8, addr: u16, data: &[u8]) -> BulkWrite {
+        self.entries.push(BulkWriteEntry {
+            id,
+            addr,
+            data: data.to_vec(),
+        });
+        self
+    }
+
+    pub fn u8(self, id: u8, addr: u16, value: u8) -> BulkWrite {
+        self.bytes(id, addr, &[value])
+    }
+
+    pub fn u16(self, id: u8, addr: u16, value: u16) -> BulkWrite {
+        self.bytes(id, addr, &value.to_le_bytes())
+    }
+
+    pub fn u32(self, id: u8, addr: u16, value: u32) -> BulkWrite {
+        self.bytes(id, addr, &value.to_le_bytes())
+    }
+
+    pub fn entries(&self) -> &[BulkWriteEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub struct SyncCommand {
     id: u8,
@@ -264,6 +658,208 @@ impl From<(u8, f32)> for SyncCommandFloat {
     }
 }
 
+/// Sync write packet built once and patched in place for fixed high rate command patterns
+///
+/// Each tick only overwrites the values and the checksum instead of serializing a new
+/// packet, send it with [`crate::DynamixelDriver::send_prepared`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedSyncWrite {
+    instruction: Instruction,
+    data_len: usize,
+    ids: Vec<u8>,
+}
+
+impl PreparedSyncWrite {
+    // header, broadcast id, length, instruction, address and data length
+    const HEADER_LEN: usize = 7;
+
+    pub fn new(addr: u8, data_len: u8, ids: &[u8]) -> Result<PreparedSyncWrite> {
+        if !matches!(data_len, 1 | 2 | 4) {
+            return Err(DynamixelDriverError::InvalidSyncDataLength(data_len));
+        }
+        let commands = ids.iter().map(|&id| SyncCommand::new(id, 0)).collect();
+        Ok(PreparedSyncWrite {
+            instruction: Instruction::sync_command(addr, data_len, commands),
+            data_len: data_len as usize,
+            ids: ids.to_vec(),
+        })
+    }
+
+    pub fn ids(&self) -> &[u8] {
+        &self.ids
+    }
+
+    pub(crate) fn addr(&self) -> u8 {
+        self.instruction.payload[5]
+    }
+
+    fn value_range(&self, index: usize) -> std::ops::Range<usize> {
+        let start = Self::HEADER_LEN + index * (self.data_len + 1) + 1;
+        start..start + self.data_len
+    }
+
+    /// Value currently set for the servo at `index` in the id list
+    pub fn value(&self, index: usize) -> u32 {
+        let mut bytes = [0; 4];
+        bytes[..self.data_len].copy_from_slice(&self.instruction.payload[self.value_range(index)]);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Sets the value for the servo at `index` in the id list, failing if it is wider
+    /// than the data length of the packet
+    pub fn set_value(&mut self, index: usize, value: u32) -> Result<()> {
+        let bytes = value.to_le_bytes();
+        if bytes[self.data_len..].iter().any(|&byte| byte != 0) {
+            return Err(DynamixelDriverError::SyncValueTooWide {
+                value,
+                data_len: self.data_len as u8,
+            });
+        }
+        let range = self.value_range(index);
+        self.instruction.payload[range].copy_from_slice(&bytes[..self.data_len]);
+        Ok(())
+    }
+
+    /// Sets the value for servo `id`, returns false if the packet doesn't address it
+    pub fn set(&mut self, id: u8, value: u32) -> Result<bool> {
+        match self.ids.iter().position(|&other| other == id) {
+            Some(index) => self.set_value(index, value).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    pub(crate) fn instruction(&mut self) -> &Instruction {
+        let last = self.instruction.payload.len() - 1;
+        self.instruction.payload[last] = calc_checksum(&self.instruction.payload[2..last]);
+        &self.instruction
+    }
+}
+
+/// Single register write in a [`crate::DynamixelDriver::write_many`] batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteOp {
+    U8 { id: u8, addr: u8, value: u8 },
+    U16 { id: u8, addr: u8, value: u16 },
+}
+
+impl WriteOp {
+    pub fn u8(id: u8, addr: u8, value: u8) -> WriteOp {
+        WriteOp::U8 { id, addr, value }
+    }
+
+    pub fn u16(id: u8, addr: u8, value: u16) -> WriteOp {
+        WriteOp::U16 { id, addr, value }
+    }
+
+    pub fn id(&self) -> u8 {
+        match *self {
+            WriteOp::U8 { id, .. } | WriteOp::U16 { id, .. } => id,
+        }
+    }
+
+    pub fn addr(&self) -> u8 {
+        match *self {
+            WriteOp::U8 { addr, .. } | WriteOp::U16 { addr, .. } => addr,
+        }
+    }
+
+    pub fn value(&self) -> u16 {
+        match *self {
+            WriteOp::U8 { value, .. } => value as u16,
+            WriteOp::U16 { value, .. } => value,
+        }
+    }
+
+    pub(crate) fn len(&self) -> u8 {
+        match self {
+            WriteOp::U8 { .. } => 1,
+            WriteOp::U16 { .. } => 2,
+        }
+    }
+
+    // same register with another value
+    pub(crate) fn with_value(&self, value: u16) -> WriteOp {
+        match *self {
+            WriteOp::U8 { id, addr, .. } => WriteOp::u8(id, addr, value as u8),
+            WriteOp::U16 { id, addr, .. } => WriteOp::u16(id, addr, value),
+        }
+    }
+
+    // same value at the address of another control table
+    pub(crate) fn with_address(&self, addr: u8) -> WriteOp {
+        match *self {
+            WriteOp::U8 { id, value, .. } => WriteOp::u8(id, addr, value),
+            WriteOp::U16 { id, value, .. } => WriteOp::u16(id, addr, value),
+        }
+    }
+
+    pub(crate) fn instruction(&self, protocol: &dyn Protocol, registered: bool) -> Instruction {
+        let data = &self.value().to_le_bytes()[..self.len() as usize];
+        if registered {
+            protocol.reg_write(self.id(), self.addr().into(), data)
+        } else {
+            protocol.write(self.id(), self.addr().into(), data)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncVerification {
+    /// Ping every servo after the write
+    Ping,
+    /// Read the written register back from every servo and compare values
+    ReadBack,
+}
+
+/// Per servo results of a verified sync operation
+#[derive(Debug, Default)]
+pub struct SyncOutcome {
+    results: Vec<(u8, Result<()>)>,
+}
+
+impl SyncOutcome {
+    pub(crate) fn push(&mut self, id: u8, result: Result<()>) {
+        self.results.push((id, result));
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    pub fn succeeded_ids(&self) -> Vec<u8> {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    pub fn failed_ids(&self) -> Vec<u8> {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    pub fn results(&self) -> &[(u8, Result<()>)] {
+        &self.results
+    }
+
+    pub fn into_results(self) -> Vec<(u8, Result<()>)> {
+        self.results
+    }
+}
+
+/// Result of [`crate::DynamixelDriver::write_id`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdChange {
+    pub old_id: u8,
+    pub new_id: u8,
+    /// The servo answered a ping at its new id after the write
+    pub verified: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -289,6 +885,138 @@ mod tests {
         assert_eq!(payload, expected);
     }
 
+    #[test]
+    fn write_bytes_matches_typed_writes() {
+        assert_eq!(
+            Instruction::write(0xFE, 0x03, &[1]),
+            Instruction::write_u8(0xFE, 0x03, 1)
+        );
+        assert_eq!(
+            Instruction::write(1, 30, &[0x00, 0x02]),
+            Instruction::write_u16(1, 30, 512)
+        );
+    }
+
+    #[test]
+    fn expected_response_len() {
+        assert_eq!(
+            Instruction::read_instruction(1, 36, 2).expected_response_len(),
+            8
+        );
+        assert_eq!(Instruction::write_u8(1, 24, 1).expected_response_len(), 6);
+        assert_eq!(Instruction::ping(1).expected_response_len(), 6);
+        let params = vec![SyncCommand::new(1, 10)];
+        assert_eq!(
+            Instruction::sync_command(30, 2, params).expected_response_len(),
+            0
+        );
+        assert_eq!(
+            Instruction::usb2ax_sync_read(36, 2, &[1, 2, 3]).expected_response_len(),
+            12
+        );
+    }
+
+    #[test]
+    fn prepared_sync_write_matches_sync_command() {
+        let mut prepared = PreparedSyncWrite::new(30, 2, &[1, 2, 3]).unwrap();
+        prepared.set_value(0, 512).unwrap();
+        assert!(prepared.set(3, 1023).unwrap());
+        assert!(!prepared.set(4, 0).unwrap());
+        let expected = Instruction::sync_command(
+            30,
+            2,
+            vec![
+                SyncCommand::new(1, 512),
+                SyncCommand::new(2, 0),
+                SyncCommand::new(3, 1023),
+            ],
+        );
+        assert_eq!(prepared.instruction(), &expected);
+        prepared.set(2, 100).unwrap();
+        assert_ne!(prepared.instruction(), &expected);
+    }
+
+    #[test]
+    fn prepared_sync_write_rejects_invalid_widths() {
+        assert!(matches!(
+            PreparedSyncWrite::new(30, 3, &[1]),
+            Err(DynamixelDriverError::InvalidSyncDataLength(3))
+        ));
+        let mut prepared = PreparedSyncWrite::new(24, 1, &[1]).unwrap();
+        assert!(matches!(
+            prepared.set(1, 256),
+            Err(DynamixelDriverError::SyncValueTooWide {
+                value: 256,
+                data_len: 1
+            })
+        ));
+        assert_eq!(prepared.value(0), 0);
+    }
+
+    #[test]
+    fn usb2ax_sync_read() {
+        let packet = Instruction::usb2ax_sync_read(36, 2, &[1, 2]);
+        assert_eq!(
+            packet.as_bytes(),
+            &[0xFF, 0xFF, 0xFE, 0x06, 0x84, 36, 2, 1, 2, 0x4E]
+        );
+        assert_eq!(packet.response_id(), 0xFD);
+        assert_eq!(Instruction::ping(3).response_id(), 3);
+    }
+
+    #[test]
+    fn bulk_read() {
+        // example from the MX-28 e-manual
+        let packet = Instruction::bulk_read(&[BulkRead::new(1, 30, 2), BulkRead::new(2, 36, 2)]);
+        assert_eq!(
+            packet.as_bytes(),
+            &[0xFF, 0xFF, 0xFE, 0x09, 0x92, 0x00, 0x02, 0x01, 0x1E, 0x02, 0x02, 0x24, 0x1D]
+        );
+        assert_eq!(packet.expected_response_len(), 16);
+    }
+
+    #[test]
+    fn bulk_write() {
+        let writes = BulkWrite::new().u8(1, 0x20, 0xA0).u16(2, 0x1F, 0x0150);
+        let packet = Instruction::bulk_write(&writes);
+        assert!(packet.is_protocol2());
+        assert_eq!((packet.id(), packet.opcode()), (0xFE, 0x93));
+        assert_eq!(
+            &*packet.params(),
+            &[0x01, 0x20, 0x00, 0x01, 0x00, 0xA0, 0x02, 0x1F, 0x00, 0x02, 0x00, 0x50, 0x01]
+        );
+        assert_eq!(packet.expected_response_len(), 0);
+    }
+
+    #[test]
+    fn instruction_from_bytes() {
+        let packet = Instruction::read_instruction(1, 43, 1);
+        assert_eq!(Instruction::from_bytes(packet.as_bytes()).unwrap(), packet);
+        assert_eq!((packet.opcode(), &*packet.params()), (0x02, &[43, 1][..]));
+        assert!(matches!(
+            Instruction::from_bytes(&[0xFF, 0xFF, 0x01, 0x02, 0x01, 0x00]),
+            Err(DynamixelDriverError::ChecksumError(0xFB, 0x00))
+        ));
+        assert!(Instruction::from_bytes(&[0xFF, 0xFF, 0x01, 0x04, 0x01, 0xF9]).is_err());
+        assert!(Instruction::from_bytes(&[0xFF, 0x01, 0x02, 0x01, 0xFB]).is_err());
+    }
+
+    #[test]
+    fn reg_write_and_action_serialization() {
+        assert_eq!(
+            Instruction::reg_write(1, 30, &[0x00, 0x02]).serialize(),
+            vec![0xFF, 0xFF, 0x01, 0x05, 0x04, 0x1E, 0x00, 0x02, 0xD5]
+        );
+        assert_eq!(
+            Instruction::action(0xFE).serialize(),
+            vec![0xFF, 0xFF, 0xFE, 0x02, 0x05, 0xFA]
+        );
+        assert_eq!(
+            WriteOp::u16(1, 30, 512).instruction(&Protocol1, true),
+            Instruction::reg_write(1, 30, &[0x00, 0x02])
+        );
+    }
+
     #[test]
     fn ping_serialization() {
         let packet = Instruction::ping(1);
@@ -319,7 +1047,18 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "not implemented: Sync write only implement for u8 and u16")]
+    fn sync_write_serialization_u32() {
+        let params = vec![SyncCommand::new(1, 0x0064_0200)];
+        let packet = Instruction::sync_command(30, 4, params);
+        let payload = packet.serialize();
+        assert_eq!(
+            payload,
+            vec![255, 255, 254, 9, 131, 30, 4, 1, 0, 2, 100, 0, 236]
+        )
+    }
+
+    #[test]
+    #[should_panic(expected = "not implemented: Sync write only implement for u8, u16 and u32")]
     fn sync_write_serialization_fail() {
         let params = vec![SyncCommand::new(1, 10), SyncCommand::new(2, 10)];
         let packet = Instruction::sync_command(30, 3, params);
@@ -0,0 +1,213 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::path::Path;
+#[cfg(feature = "serde")]
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use crate::instructions::{DynamixelDriverError, Result};
+use crate::model::ServoModel;
+
+/// Expected contents of a bus, stored as TOML
+///
+/// AX and MX servos have no serial number register so servos are identified by
+/// id, model and firmware version.
+///
+/// ```toml
+/// [[servos]]
+/// id = 1
+/// model = "AX-12A"
+/// firmware = 24
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct Inventory {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub servos: Vec<InventoryEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct InventoryEntry {
+    pub id: u8,
+    pub model: ServoModel,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub firmware: Option<u8>,
+}
+
+/// Differences between an expected [`Inventory`] and the servos found on the bus
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InventoryDiff {
+    /// Expected servos that didn't answer
+    pub missing: Vec<InventoryEntry>,
+    /// Servos that answered but aren't in the inventory
+    pub extra: Vec<InventoryEntry>,
+    /// Pairs of ids whose models are exchanged, most likely servos plugged into the wrong joints
+    pub swapped: Vec<(u8, u8)>,
+    /// Servos whose model or firmware differs from the inventory, as (expected, found)
+    pub changed: Vec<(InventoryEntry, InventoryEntry)>,
+}
+
+impl InventoryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.swapped.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+impl Inventory {
+    #[cfg(feature = "serde")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Inventory> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, self.to_toml())?;
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("inventory always serializes to TOML")
+    }
+
+    pub fn get(&self, id: u8) -> Option<&InventoryEntry> {
+        self.servos.iter().find(|servo| servo.id == id)
+    }
+
+    /// Compares the expected inventory with a `live` scan of the bus
+    pub fn compare(&self, live: &Inventory) -> InventoryDiff {
+        let mut diff = InventoryDiff::default();
+        let mut mismatched = vec![];
+        for expected in &self.servos {
+            match live.get(expected.id) {
+                None => diff.missing.push(expected.clone()),
+                Some(found) if found.model != expected.model => {
+                    mismatched.push((expected.clone(), found.clone()))
+                }
+                Some(found)
+                    if expected.firmware.is_some() && found.firmware != expected.firmware =>
+                {
+                    diff.changed.push((expected.clone(), found.clone()))
+                }
+                Some(_) => (),
+            }
+        }
+        diff.extra = live
+            .servos
+            .iter()
+            .filter(|found| self.get(found.id).is_none())
+            .cloned()
+            .collect();
+
+        while let Some((expected, found)) = mismatched.pop() {
+            let partner = mismatched.iter().position(|(other_expected, other_found)| {
+                other_expected.model == found.model && other_found.model == expected.model
+            });
+            match partner {
+                Some(index) => {
+                    let (other, _) = mismatched.remove(index);
+                    diff.swapped
+                        .push((other.id.min(expected.id), other.id.max(expected.id)));
+                }
+                None => diff.changed.push((expected, found)),
+            }
+        }
+        diff.swapped.sort_unstable();
+        diff.changed.sort_by_key(|(expected, _)| expected.id);
+        diff
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromStr for Inventory {
+    type Err = DynamixelDriverError;
+
+    fn from_str(contents: &str) -> Result<Self> {
+        toml::from_str(contents)
+            .map_err(|error| DynamixelDriverError::InvalidConfig(error.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u8, model: ServoModel) -> InventoryEntry {
+        InventoryEntry {
+            id,
+            model,
+            firmware: None,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn toml_round_trip() {
+        let inventory = Inventory {
+            servos: vec![
+                InventoryEntry {
+                    firmware: Some(24),
+                    ..entry(1, ServoModel::Ax12A)
+                },
+                entry(2, ServoModel::Other(1020)),
+            ],
+        };
+        let parsed: Inventory = inventory.to_toml().parse().unwrap();
+        assert_eq!(parsed, inventory);
+    }
+
+    #[test]
+    fn detects_missing_extra_swapped_and_changed() {
+        let expected = Inventory {
+            servos: vec![
+                entry(1, ServoModel::Ax12A),
+                entry(2, ServoModel::Ax18A),
+                entry(3, ServoModel::Mx28),
+                entry(4, ServoModel::Mx64),
+                InventoryEntry {
+                    firmware: Some(24),
+                    ..entry(5, ServoModel::Ax12A)
+                },
+                entry(6, ServoModel::Ax12A),
+            ],
+        };
+        let live = Inventory {
+            servos: vec![
+                entry(1, ServoModel::Ax18A),
+                entry(2, ServoModel::Ax12A),
+                entry(3, ServoModel::Mx106),
+                InventoryEntry {
+                    firmware: Some(25),
+                    ..entry(5, ServoModel::Ax12A)
+                },
+                InventoryEntry {
+                    firmware: Some(25),
+                    ..entry(6, ServoModel::Ax12A)
+                },
+                entry(7, ServoModel::Ax12A),
+            ],
+        };
+        let diff = expected.compare(&live);
+        assert_eq!(diff.missing, vec![entry(4, ServoModel::Mx64)]);
+        assert_eq!(diff.extra, vec![entry(7, ServoModel::Ax12A)]);
+        assert_eq!(diff.swapped, vec![(1, 2)]);
+        let changed: Vec<u8> = diff
+            .changed
+            .iter()
+            .map(|(expected, _)| expected.id)
+            .collect();
+        assert_eq!(changed, vec![3, 5]);
+        assert!(!diff.is_empty());
+        assert!(expected.compare(&expected).is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::instructions::{DynamixelDriverError, Result, WriteOp};
+
+/// One EEPROM write recorded by [`crate::DynamixelDriver::enable_eeprom_journal`]
+///
+/// Stored as one line per write:
+///
+/// ```text
+/// 1760700000123 id=3 register=6 len=2 old=0 new=1023
+/// ```
+///
+/// `old=-` marks broadcast writes, where there's no single previous value to read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub time: SystemTime,
+    pub id: u8,
+    pub register: u8,
+    /// Register width in bytes
+    pub len: u8,
+    pub old: Option<u16>,
+    pub new: u16,
+}
+
+impl JournalEntry {
+    /// Write restoring the value the register had before this entry
+    pub fn revert(&self) -> Option<WriteOp> {
+        let old = self.old?;
+        Some(match self.len {
+            1 => WriteOp::u8(self.id, self.register, old as u8),
+            _ => WriteOp::u16(self.id, self.register, old),
+        })
+    }
+}
+
+impl fmt::Display for JournalEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = self
+            .time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        write!(
+            f,
+            "{} id={} register={} len={} old=",
+            millis, self.id, self.register, self.len
+        )?;
+        match self.old {
+            Some(old) => write!(f, "{}", old)?,
+            None => write!(f, "-")?,
+        }
+        write!(f, " new={}", self.new)
+    }
+}
+
+impl FromStr for JournalEntry {
+    type Err = DynamixelDriverError;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let invalid = || DynamixelDriverError::InvalidConfig(format!("bad journal entry {line:?}"));
+        let mut fields = line.split_whitespace();
+        let millis: u64 = fields
+            .next()
+            .and_then(|time| time.parse().ok())
+            .ok_or_else(invalid)?;
+        let mut field = |name: &str| {
+            fields
+                .next()
+                .and_then(|field| field.strip_prefix(name)?.strip_prefix('='))
+                .ok_or_else(invalid)
+        };
+        let id = field("id")?.parse().map_err(|_| invalid())?;
+        let register = field("register")?.parse().map_err(|_| invalid())?;
+        let len = field("len")?.parse().map_err(|_| invalid())?;
+        let old = match field("old")? {
+            "-" => None,
+            old => Some(old.parse().map_err(|_| invalid())?),
+        };
+        let new = field("new")?.parse().map_err(|_| invalid())?;
+        if fields.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(JournalEntry {
+            time: UNIX_EPOCH + Duration::from_millis(millis),
+            id,
+            register,
+            len,
+            old,
+            new,
+        })
+    }
+}
+
+/// Reads every entry of a journal file, oldest first
+pub fn read_journal(path: impl AsRef<Path>) -> Result<Vec<JournalEntry>> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Journal file kept open by the driver, every entry is written out before the call
+/// that made the write returns
+pub(crate) struct EepromJournal {
+    file: File,
+}
+
+impl EepromJournal {
+    pub fn open(path: impl AsRef<Path>) -> Result<EepromJournal> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(EepromJournal { file })
+    }
+
+    pub fn append(&mut self, entry: &JournalEntry) -> Result<()> {
+        writeln!(self.file, "{}", entry)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_round_trip() {
+        let entry = JournalEntry {
+            time: UNIX_EPOCH + Duration::from_millis(1_760_700_000_123),
+            id: 3,
+            register: 6,
+            len: 2,
+            old: Some(0),
+            new: 1023,
+        };
+        let line = entry.to_string();
+        assert_eq!(line, "1760700000123 id=3 register=6 len=2 old=0 new=1023");
+        assert_eq!(line.parse::<JournalEntry>().unwrap(), entry);
+        assert_eq!(entry.revert(), Some(WriteOp::u16(3, 6, 0)));
+
+        let broadcast: JournalEntry = "0 id=254 register=4 len=1 old=- new=1".parse().unwrap();
+        assert_eq!(broadcast.old, None);
+        assert_eq!(broadcast.revert(), None);
+        assert!("0 id=1 register=4 len=1 new=1"
+            .parse::<JournalEntry>()
+            .is_err());
+    }
+}
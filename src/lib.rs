@@ -1,386 +1,6116 @@
 #![doc = include_str!("../README.md")]
 
+mod angle;
+mod angle_limits;
+mod batch;
+#[cfg(feature = "serial")]
+mod builder;
+mod bus_load;
+pub mod byte_stuffing;
+mod circuit_breaker;
+mod config;
+pub mod crc16;
+#[cfg(all(unix, feature = "daemon"))]
+pub mod daemon;
+pub mod debug;
+mod discovery;
+mod events;
+mod failover;
+mod feedback;
+#[cfg(test)]
+mod fixture_tests;
+#[cfg(feature = "hooks")]
+mod hooks;
 mod instructions;
+mod inventory;
+mod journal;
+mod logging;
+#[cfg(feature = "trajectory")]
+mod mirror;
+mod model;
+mod motion;
+mod odometry;
+mod operating_mode;
+#[cfg(feature = "trajectory")]
+mod pose_buffer;
+mod position_watch;
+pub mod prelude;
+#[cfg(feature = "trajectory")]
+mod primitives;
+mod protocol;
+mod ram;
+#[cfg(feature = "ros")]
+pub mod ros;
+mod safety;
 mod serial_driver;
+mod servo_config;
+mod sniffer;
+mod stats;
+mod stress;
+#[cfg(feature = "trajectory")]
+mod teach;
+#[cfg(feature = "teleop")]
+pub mod teleop;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+mod thermal;
+#[cfg(feature = "urdf")]
+pub mod urdf;
+mod voltage;
+
+use crate::logging::warn;
+use batch::BatchValue;
+use circuit_breaker::CircuitBreaker;
+use discovery::AdaptiveTimeout;
+use events::EVENT_CAPACITY;
+use instructions::{Result, USB2AX_ID};
+use journal::EepromJournal;
+use motion::moving_speed_for;
+use position_watch::PositionWatchers;
+use ram::{RAM_LEN, RAM_START};
+use std::collections::HashMap;
+#[cfg(feature = "trajectory")]
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use thermal::TemperatureMonitor;
+use tokio::sync::broadcast;
+use voltage::VoltageMonitor;
 
-use instructions::{Instruction, Result};
-use serial_driver::{FramedDriver, FramedSerialDriver};
+pub use angle::Angle;
+pub use angle_limits::{AngleLimitPolicy, AngleLimits};
+pub use async_trait::async_trait;
+pub use batch::{
+    Degrees, GoalPositionBatch, MovingSpeedBatch, PositionValue, Rpm, SpeedValue, SyncBatch, Ticks,
+    TorqueEnableBatch,
+};
+#[cfg(feature = "serial")]
+pub use builder::DynamixelDriverBuilder;
+pub use bus_load::TrafficPlan;
+pub use circuit_breaker::{CircuitBreakerConfig, CircuitState};
+pub use config::{DriverConfig, ServoDescription};
+#[cfg(feature = "serial")]
+pub use discovery::{discover, Discovery};
+pub use events::{BusEvent, DriverEvent, NamedEvents};
+pub use failover::{ActiveTransport, FailoverTransport, Switchover};
+pub use feedback::{GroupFeedback, JointFeedback};
+#[cfg(feature = "hooks")]
+pub use hooks::{HookAction, HookedTransport};
+pub use instructions::{
+    BulkRead, BulkWrite, BulkWriteEntry, DynamixelDriverError, HardwareErrorStatus, IdChange,
+    Instruction, PreparedSyncWrite, StatusError, SyncCommand, SyncCommandFloat, SyncOutcome,
+    SyncVerification, WriteOp,
+};
+pub use inventory::{Inventory, InventoryDiff, InventoryEntry};
+pub use journal::{read_journal, JournalEntry};
+#[cfg(feature = "trajectory")]
+pub use mirror::Mirror;
+pub use model::{Capability, PidGains, ServoModel};
+pub use motion::{JointMove, RelaxGroup, VelocityLimiter, VelocityProfile};
+pub use odometry::WheelOdometry;
+pub use operating_mode::{DriveMode, OperatingMode};
+#[cfg(feature = "trajectory")]
+pub use pose_buffer::PoseBuffer;
+pub use position_watch::PositionChanges;
+#[cfg(feature = "trajectory")]
+pub use primitives::{MotionPrimitive, ReturnToHome, SinusoidalSweep, StepSequence};
+pub use protocol::{Protocol, Protocol1, Protocol2};
+pub use ram::{RamTable, RamWrite};
+pub use safety::SafetyPolicy;
+#[cfg(feature = "serial")]
+pub use serial_driver::{list_ports, probe_port, FramedSerialDriver, PortDiagnostic, PortInfo};
+pub use serial_driver::{
+    ChecksumPolicy, DynamixelProtocol, FlushPolicy, FramedDriver, IdMismatchPolicy,
+    InstructionDecoder, ReceiveTimeout, ResponseKind, Status,
+};
+pub use servo_config::{ConfigProgress, ServoConfig};
+pub use sniffer::{BusSniffer, FrameKind, SniffedFrame, SnifferStats};
+pub use stats::BusStats;
+pub use stress::StressReport;
+#[cfg(feature = "trajectory")]
+pub use teach::{RecordedFrame, Recording};
+pub use thermal::TemperatureTrend;
+#[cfg(feature = "serial")]
+pub use tokio_serial::{FlowControl, Parity, SerialPort, SerialStream, StopBits};
+pub use voltage::Brownout;
 
-pub use instructions::{DynamixelDriverError, SyncCommand, SyncCommandFloat};
+const BROADCAST_ID: u8 = 254;
 
 // EEPROM table
-// const MODEL_NUMBER: u8 = 0;
-// const FIRMWARE_VERSION: u8 = 2;
+const MODEL_NUMBER: u8 = 0;
+const FIRMWARE_VERSION: u8 = 2;
 const ID: u8 = 3;
 // const BAUD_RATE: u8 = 4;
+const CW_ANGLE_LIMIT: u8 = 6;
+const CCW_ANGLE_LIMIT: u8 = 8;
+const TEMPERATURE_LIMIT: u8 = 11;
 const MAX_TORQUE: u8 = 14;
+// first address past the EEPROM area
+const EEPROM_END: u8 = 24;
 
 // RAM table
 const TORQUE_ENABLED: u8 = 24;
+const LED: u8 = 25;
 const CW_COMPLIANCE_MARGIN: u8 = 26;
 const CCW_COMPLIANCE_MARGIN: u8 = 27;
 const CW_COMPLIANCE_SLOPE: u8 = 28;
 const CCW_COMPLIANCE_SLOPE: u8 = 29;
 const GOAL_POSITION: u8 = 30;
 const MOVING_SPEED: u8 = 32;
+const TORQUE_LIMIT: u8 = 34;
 const PRESENT_POSITION: u8 = 36;
+const PRESENT_SPEED: u8 = 38;
+const PRESENT_LOAD: u8 = 40;
 const PRESENT_TEMPERATURE: u8 = 43;
 const PRESENT_VOLTAGE: u8 = 42;
+const REGISTERED_INSTRUCTION: u8 = 44;
+const MOVING: u8 = 46;
+
+// X series control table of Protocol 2.0 servos
+// model information takes addresses 2 to 5
+const PROTOCOL2_FIRMWARE_VERSION: u8 = 6;
+const PROTOCOL2_ID: u8 = 7;
+const PROTOCOL2_DRIVE_MODE: u8 = 10;
+const PROTOCOL2_OPERATING_MODE: u8 = 11;
+const PROTOCOL2_SECONDARY_ID: u8 = 12;
+const PROTOCOL2_MAX_POSITION_LIMIT: u8 = 48;
+const PROTOCOL2_MIN_POSITION_LIMIT: u8 = 52;
+const PROTOCOL2_HOMING_OFFSET: u8 = 20;
+const PROTOCOL2_PWM_LIMIT: u8 = 36;
+const PROTOCOL2_CURRENT_LIMIT: u8 = 38;
+// operating mode, limits and shutdown settings live up to 63
+const PROTOCOL2_EEPROM_END: u8 = 64;
+const PROTOCOL2_TORQUE_ENABLE: u8 = 64;
+const PROTOCOL2_LED: u8 = 65;
+const PROTOCOL2_REGISTERED_INSTRUCTION: u8 = 69;
+// latched hardware errors, flagged by the alert bit
+const PROTOCOL2_HARDWARE_ERROR_STATUS: u8 = 70;
+// profile acceleration and velocity are followed by the goal position
+const PROTOCOL2_GOAL_PWM: u8 = 100;
+const PROTOCOL2_PROFILE_ACCELERATION: u8 = 108;
+const PROTOCOL2_PROFILE_VELOCITY: u8 = 112;
+const PROTOCOL2_GOAL_POSITION: u8 = 116;
+const PROTOCOL2_REALTIME_TICK: u8 = 120;
+const PROTOCOL2_PRESENT_CURRENT: u8 = 126;
+// first block of indirect addressing, each address maps one byte of the data registers
+const PROTOCOL2_INDIRECT_ADDRESS: u8 = 168;
+const PROTOCOL2_INDIRECT_DATA: u8 = 224;
+const PROTOCOL2_INDIRECT_LEN: usize = 28;
+const PROTOCOL2_MOVING: u8 = 122;
+const PROTOCOL2_PRESENT_TEMPERATURE: u8 = 146;
+
+// current unit of the XM and XH series
+const PROTOCOL2_CURRENT_UNIT_MA: f32 = 2.69;
+const PROTOCOL2_VELOCITY_UNIT_RPM: f32 = 0.229;
+const PROTOCOL2_MAX_HOMING_OFFSET: i32 = 1_044_479;
+// rev/min²
+const PROTOCOL2_ACCELERATION_UNIT: f32 = 214.577;
+
+const DEFAULT_MOTION_POLL_INTERVAL: Duration = Duration::from_millis(10);
+const DEFAULT_PROFILE_UPDATE_INTERVAL: Duration = Duration::from_millis(20);
+// stop waiting for the right servo on a bus that keeps answering with others
+const MAX_DISCARDED_RESPONSES: usize = 8;
+const IDENTIFY_INTERVAL: Duration = Duration::from_millis(250);
+const IDENTIFY_WIGGLE_DEGREES: f32 = 5.0;
+
+// MX RAM table, D, I and P gains take the place of the AX compliance registers
+const D_GAIN: u8 = 26;
+// MX-64/106 RAM table
+const TORQUE_CONTROL_MODE_ENABLE: u8 = 70;
+const GOAL_TORQUE: u8 = 71;
 
 pub struct DynamixelDriver {
     port: Box<dyn FramedDriver>,
+    name: Option<String>,
+    eeprom_writes_allowed: bool,
+    verify_identity: bool,
+    eeprom_journal: Option<EepromJournal>,
+    retries: u32,
+    servos: HashMap<u8, ServoDescription>,
+    models: HashMap<u8, ServoModel>,
+    angle_limits: HashMap<u8, AngleLimits>,
+    // secondary ids of Protocol 2.0 servos as last read or written
+    secondary_ids: HashMap<u8, u8>,
+    angle_limit_policy: AngleLimitPolicy,
+    auto_detect_models: bool,
+    servo_protocols: HashMap<u8, Arc<dyn Protocol>>,
+    detect_protocols: bool,
+    circuit_breaker: Option<CircuitBreaker>,
+    safety_policy: SafetyPolicy,
+    checksum_policy: ChecksumPolicy,
+    checksum_retries: u64,
+    id_mismatch_policy: IdMismatchPolicy,
+    flush_policy: FlushPolicy,
+    motion_poll_interval: Duration,
+    profile_update_interval: Duration,
+    usb2ax_sync_read: bool,
+    sync_spot_check: bool,
+    spot_check_index: usize,
+    spot_checks: u64,
+    spot_check_failures: u64,
+    voltage_monitor: Option<VoltageMonitor>,
+    temperature_monitor: Option<TemperatureMonitor>,
+    position_watchers: PositionWatchers,
+    events: broadcast::Sender<DriverEvent>,
+    protocol: Arc<dyn Protocol>,
+    shutdown_sequence: Option<(Vec<RelaxGroup>, Duration)>,
+    closed: bool,
 }
 
 impl DynamixelDriver {
+    #[cfg(feature = "serial")]
     pub fn new(port_name: &str) -> Result<DynamixelDriver> {
         let driver = FramedSerialDriver::new(port_name)?;
-        Ok(DynamixelDriver {
-            port: Box::new(driver),
-        })
+        Ok(DynamixelDriver::with_driver(Box::new(driver)))
     }
 
+    #[cfg(feature = "serial")]
     pub fn with_baud_rate(port: &str, baud_rate: u32) -> Result<DynamixelDriver> {
         let driver = FramedSerialDriver::with_baud_rate(port, baud_rate)?;
-        Ok(DynamixelDriver {
-            port: Box::new(driver),
-        })
+        Ok(DynamixelDriver::with_driver(Box::new(driver)))
     }
 
-    #[cfg(test)]
-    fn with_driver(connection: Box<dyn FramedDriver>) -> DynamixelDriver {
-        DynamixelDriver { port: connection }
+    #[cfg(all(feature = "serial", feature = "serde"))]
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<DynamixelDriver> {
+        DynamixelDriver::from_config(&DriverConfig::from_file(path)?)
     }
 
-    async fn read_u8(&mut self, id: u8, addr: u8) -> Result<u8> {
-        let command = Instruction::read_instruction(id, addr, 1);
-        self.port.send(command).await?;
-        let response = self.port.receive().await?;
-        if id != response.id() {
-            return Err(DynamixelDriverError::IdMismatchError(id, response.id()));
+    #[cfg(feature = "serial")]
+    pub fn from_config(config: &DriverConfig) -> Result<DynamixelDriver> {
+        let timeout = config
+            .timeout_ms
+            .map(|timeout| ReceiveTimeout::Fixed(Duration::from_millis(timeout)))
+            .unwrap_or_default();
+        let protocol: Arc<dyn Protocol> = match config.protocol_version {
+            2 => Arc::new(Protocol2),
+            _ => Arc::new(Protocol1),
+        };
+        let mut driver = DynamixelDriver::builder(&config.port)
+            .baud_rate(config.baud_rate)
+            .timeout(timeout)
+            .protocol(protocol)
+            .build()?;
+        if let Some(name) = &config.name {
+            driver.set_name(name.clone());
+        }
+        driver.retries = config.retries;
+        driver.set_checksum_policy(config.checksum_policy);
+        driver.set_id_mismatch_policy(config.id_mismatch_policy);
+        driver.set_flush_policy(config.flush_policy);
+        driver.set_auto_detect_models(config.auto_detect_models);
+        driver.set_detect_protocols(config.detect_protocols);
+        driver.set_safety_policy(config.safety_policy);
+        driver.set_angle_limit_policy(config.angle_limit_policy);
+        for servo in &config.servos {
+            driver.configure_servo(servo.clone());
         }
-        response.as_u8()
+        Ok(driver)
     }
 
-    async fn read_u16(&mut self, id: u8, addr: u8) -> Result<u16> {
-        let command = Instruction::read_instruction(id, addr, 2);
-        self.port.send(command).await?;
-        let response = self.port.receive().await?;
-        if id != response.id() {
-            return Err(DynamixelDriverError::IdMismatchError(id, response.id()));
-        }
-        response.as_u16()
+    #[cfg(feature = "serial")]
+    pub fn builder(port: &str) -> DynamixelDriverBuilder {
+        DynamixelDriverBuilder::new(port)
     }
 
-    async fn write_u8(&mut self, id: u8, addr: u8, value: u8) -> Result<()> {
-        let msg = Instruction::write_u8(id, addr, value);
-        self.port.send(msg).await?;
-        let response = self.port.receive().await?;
-        if id != response.id() {
-            return Err(DynamixelDriverError::IdMismatchError(id, response.id()));
+    /// Creates a driver on top of a custom transport
+    pub fn with_driver(connection: Box<dyn FramedDriver>) -> DynamixelDriver {
+        DynamixelDriver::with_protocol(connection, Arc::new(Protocol1))
+    }
+
+    /// Driver building its instructions with `protocol`, which has to match the
+    /// framing of the transport
+    pub fn with_protocol(
+        mut connection: Box<dyn FramedDriver>,
+        protocol: Arc<dyn Protocol>,
+    ) -> DynamixelDriver {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        connection.set_event_sender(events.clone());
+        DynamixelDriver {
+            port: connection,
+            name: None,
+            eeprom_writes_allowed: true,
+            verify_identity: false,
+            eeprom_journal: None,
+            retries: 0,
+            servos: HashMap::new(),
+            models: HashMap::new(),
+            angle_limits: HashMap::new(),
+            secondary_ids: HashMap::new(),
+            angle_limit_policy: AngleLimitPolicy::default(),
+            auto_detect_models: false,
+            servo_protocols: HashMap::new(),
+            detect_protocols: false,
+            circuit_breaker: None,
+            safety_policy: SafetyPolicy::default(),
+            checksum_policy: ChecksumPolicy::default(),
+            checksum_retries: 0,
+            id_mismatch_policy: IdMismatchPolicy::default(),
+            flush_policy: FlushPolicy::default(),
+            motion_poll_interval: DEFAULT_MOTION_POLL_INTERVAL,
+            profile_update_interval: DEFAULT_PROFILE_UPDATE_INTERVAL,
+            usb2ax_sync_read: false,
+            sync_spot_check: false,
+            spot_check_index: 0,
+            spot_checks: 0,
+            spot_check_failures: 0,
+            voltage_monitor: None,
+            temperature_monitor: None,
+            position_watchers: PositionWatchers::default(),
+            events,
+            protocol,
+            shutdown_sequence: None,
+            closed: false,
         }
-        Ok(())
     }
 
-    async fn write_u16(&mut self, id: u8, addr: u8, value: u16) -> Result<()> {
-        let msg = Instruction::write_u16(id, addr, value);
-        self.port.send(msg).await?;
-        let response = self.port.receive().await?;
-        if id != response.id() {
-            return Err(DynamixelDriverError::IdMismatchError(id, response.id()));
+    /// Apply settings to the underlying serial port after the driver was opened
+    ///
+    /// ```no_run
+    /// # async fn example() -> Result<(), dynamixel_driver::DynamixelDriverError> {
+    /// use dynamixel_driver::SerialPort;
+    /// let mut driver = dynamixel_driver::DynamixelDriver::new("/dev/ttyUSB0")?;
+    /// driver.configure_serial_port(|port| port.set_baud_rate(57600))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "serial")]
+    pub fn configure_serial_port<F>(&mut self, mut configure: F) -> Result<()>
+    where
+        F: FnMut(&mut SerialStream) -> tokio_serial::Result<()>,
+    {
+        self.port.configure_serial_port(&mut configure)
+    }
+
+    /// Name the bus, such as "left_leg_bus", for robots with several of them
+    ///
+    /// The name is added to tracing spans of every transaction, to [`Self::bus_stats`],
+    /// to events from [`Self::subscribe_named`] and to errors as [`DynamixelDriverError::Bus`].
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = Some(name.into());
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Number of times a transaction is retried after a recoverable error
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    pub fn set_checksum_policy(&mut self, policy: ChecksumPolicy) {
+        self.checksum_policy = policy;
+        self.port.set_checksum_policy(policy);
+    }
+
+    pub fn set_id_mismatch_policy(&mut self, policy: IdMismatchPolicy) {
+        self.id_mismatch_policy = policy;
+    }
+
+    pub fn set_flush_policy(&mut self, policy: FlushPolicy) {
+        self.flush_policy = policy;
+    }
+
+    /// Turn off torque as soon as any status reports overheating or an input voltage
+    /// error, publishing [`DriverEvent::SafetyTorqueOff`]
+    pub fn set_safety_policy(&mut self, policy: SafetyPolicy) {
+        self.safety_policy = policy;
+    }
+
+    pub fn bus_stats(&self) -> BusStats {
+        let mut stats = self.port.stats();
+        stats.bus = self.name.clone();
+        stats.checksum_retries = self.checksum_retries;
+        stats.spot_checks = self.spot_checks;
+        stats.spot_check_failures = self.spot_check_failures;
+        if let Some(monitor) = &self.voltage_monitor {
+            stats.min_voltages = monitor.min_voltages().clone();
         }
-        Ok(())
+        stats
     }
 
-    pub async fn ping(&mut self, id: u8) -> Result<()> {
-        let ping = Instruction::ping(id);
-        self.port.send(ping).await?;
-        let response = self.port.receive().await?;
-        if id != response.id() {
-            return Err(DynamixelDriverError::IdMismatchError(id, response.id()));
+    /// Track every voltage read, recording per servo minimums in [`Self::bus_stats`]
+    /// and publishing [`DriverEvent::Brownout`] whenever a servo dips below `threshold` volts
+    pub fn enable_voltage_monitor(&mut self, threshold: f32) {
+        match &mut self.voltage_monitor {
+            Some(monitor) => monitor.set_threshold(threshold),
+            None => self.voltage_monitor = Some(VoltageMonitor::new(threshold)),
         }
-        Ok(())
     }
 
-    pub async fn write_id(&mut self, id: u8, new_id: u8) -> Result<()> {
-        self.write_u8(id, ID, new_id).await?;
-        Ok(())
+    pub fn disable_voltage_monitor(&mut self) {
+        self.voltage_monitor = None;
     }
 
-    pub async fn write_torque(&mut self, id: u8, torque_enabled: bool) -> Result<()> {
-        if torque_enabled {
-            Ok(self.write_u8(id, TORQUE_ENABLED, 1).await?)
-        } else {
-            Ok(self.write_u8(id, TORQUE_ENABLED, 0).await?)
+    /// Keep temperature readings of the last `window` for [`Self::temperature_trend`]
+    pub fn enable_temperature_monitor(&mut self, window: Duration) {
+        match &mut self.temperature_monitor {
+            Some(monitor) => monitor.set_window(window),
+            None => self.temperature_monitor = Some(TemperatureMonitor::new(window)),
         }
     }
 
-    pub async fn read_temperature(&mut self, id: u8) -> Result<u8> {
-        self.read_u8(id, PRESENT_TEMPERATURE).await
+    pub fn disable_temperature_monitor(&mut self) {
+        self.temperature_monitor = None;
     }
 
-    pub async fn read_voltage(&mut self, id: u8) -> Result<f32> {
-        Ok(self.read_u8(id, PRESENT_VOLTAGE).await? as f32 / 10.0)
+    /// Heating rate fitted over temperatures read while the monitor was enabled
+    pub fn temperature_trend(&self, id: u8) -> Option<TemperatureTrend> {
+        self.temperature_monitor.as_ref()?.trend(id)
     }
 
-    pub async fn read_position(&mut self, id: u8) -> Result<u16> {
-        let position = self.read_u16(id, PRESENT_POSITION).await?;
-        Ok(position)
+    /// Projected time until the servo reaches its temperature limit and shuts down,
+    /// None while it isn't heating up
+    ///
+    /// Poll [`Self::read_temperature`] with the monitor enabled to keep the estimate current,
+    /// this only reads the limit register.
+    pub async fn time_to_temperature_limit(&mut self, id: u8) -> Result<Option<Duration>> {
+        let Some(trend) = self.temperature_trend(id) else {
+            return Ok(None);
+        };
+        let limit = self.read_u8(id, TEMPERATURE_LIMIT).await?;
+        Ok(trend.time_to_limit(limit))
     }
 
-    pub async fn read_position_degrees(&mut self, id: u8) -> Result<f32> {
-        let position = self.read_u16(id, PRESENT_POSITION).await? as f32;
-        let position = position / 3.41;
-        Ok(position)
+    /// Single subscription point for transport switchovers, circuit breaker changes,
+    /// servo alarms, brownouts and bus scan results
+    ///
+    /// Subscribers that fall more than a few dozen events behind miss the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<DriverEvent> {
+        self.events.subscribe()
     }
 
-    pub async fn read_position_rad(&mut self, id: u8) -> Result<f32> {
-        let pos_rad = self.read_position_degrees(id).await?.to_radians();
-        Ok(pos_rad)
+    /// Like [`Self::subscribe`], with every event tagged with the name of the driver
+    /// so streams of several buses can be merged
+    pub fn subscribe_named(&self) -> NamedEvents {
+        NamedEvents::new(self.name.clone(), self.events.subscribe())
     }
 
-    pub async fn write_compliance_margin_both(&mut self, id: u8, compliance: u8) -> Result<()> {
-        self.write_u8(id, CW_COMPLIANCE_MARGIN, compliance).await?;
-        self.write_u8(id, CCW_COMPLIANCE_MARGIN, compliance).await?;
-        Ok(())
+    /// Mark servos offline after consecutive timeouts and fail fast instead of waiting
+    /// for the full timeout, probing them again with exponential backoff
+    pub fn enable_circuit_breaker(&mut self, config: CircuitBreakerConfig) {
+        self.circuit_breaker = Some(CircuitBreaker::new(config));
     }
 
-    pub async fn write_compliance_slope_both(&mut self, id: u8, compliance: u8) -> Result<()> {
-        self.write_u8(id, CW_COMPLIANCE_SLOPE, compliance).await?;
-        self.write_u8(id, CCW_COMPLIANCE_SLOPE, compliance).await?;
-        Ok(())
+    pub fn disable_circuit_breaker(&mut self) {
+        self.circuit_breaker = None;
     }
 
-    pub async fn sync_write_compliance_margin_both<T: Into<SyncCommand>>(
-        &mut self,
-        compliance: Vec<T>,
-    ) -> Result<()> {
-        let compliance: Vec<SyncCommand> = compliance
-            .into_iter()
-            .map(|command| command.into())
-            .collect();
-        let message_cw = Instruction::sync_command(CW_COMPLIANCE_MARGIN, 1, compliance.clone());
-        let message_cww = Instruction::sync_command(CCW_COMPLIANCE_MARGIN, 1, compliance);
-        self.port.send(message_cw).await?;
-        self.port.send(message_cww).await?;
-        Ok(())
+    pub fn circuit_state(&self, id: u8) -> CircuitState {
+        self.circuit_breaker
+            .as_ref()
+            .map(|breaker| breaker.state(id, Instant::now()))
+            .unwrap_or(CircuitState::Closed)
     }
 
-    pub async fn sync_write_compliance_slope_both<T: Into<SyncCommand>>(
-        &mut self,
-        compliance: Vec<T>,
-    ) -> Result<()> {
-        let compliance: Vec<SyncCommand> = compliance
-            .into_iter()
-            .map(|command| command.into())
-            .collect();
-        let message_cw = Instruction::sync_command(CW_COMPLIANCE_SLOPE, 1, compliance.clone());
-        let message_cww = Instruction::sync_command(CCW_COMPLIANCE_SLOPE, 1, compliance);
-        self.port.send(message_cw).await?;
-        self.port.send(message_cww).await?;
-        Ok(())
+    /// How often [`Self::wait_until_reached`] and [`Self::wait_until_stopped`] poll the servo
+    pub fn set_motion_poll_interval(&mut self, interval: Duration) {
+        self.motion_poll_interval = interval;
     }
 
-    pub async fn sync_write_torque<T: Into<SyncCommand>>(&mut self, torque: Vec<T>) -> Result<()> {
-        let torque_commands: Vec<SyncCommand> =
-            torque.into_iter().map(|command| command.into()).collect();
-        let torque_message = Instruction::sync_command(TORQUE_ENABLED, 1, torque_commands);
-        self.port.send(torque_message).await?;
-        Ok(())
+    /// How often [`Self::move_profiled`] sends an intermediate goal position
+    pub fn set_profile_update_interval(&mut self, interval: Duration) {
+        self.profile_update_interval = interval;
     }
 
-    pub async fn write_position(&mut self, id: u8, pos: u16) -> Result<()> {
-        self.write_u16(id, GOAL_POSITION, pos).await?;
-        Ok(())
+    /// Relax sequence run by [`Self::close`] before releasing the port,
+    /// see [`Self::relax_sequenced`]
+    pub fn set_shutdown_sequence<T: Into<RelaxGroup>>(&mut self, groups: Vec<T>, delay: Duration) {
+        let groups = groups.into_iter().map(|group| group.into()).collect();
+        self.shutdown_sequence = Some((groups, delay));
     }
 
-    pub async fn write_position_degrees(&mut self, id: u8, pos: f32) -> Result<()> {
-        let goal_position = ((pos * 3.41) as i32) as u16;
-        self.write_u16(id, GOAL_POSITION, goal_position).await?;
-        Ok(())
+    pub fn clear_shutdown_sequence(&mut self) {
+        self.shutdown_sequence = None;
     }
 
-    pub async fn write_position_rad(&mut self, id: u8, pos: f32) -> Result<()> {
-        self.write_position_degrees(id, pos.to_degrees()).await?;
-        Ok(())
+    /// Runs the shutdown sequence if one is set, flushes pending writes and releases the port
+    ///
+    /// Dropping the driver can't do any of this since it would have to wait on the bus.
+    /// The port is released even if the shutdown sequence fails, the first error is returned.
+    pub async fn close(mut self) -> Result<()> {
+        self.closed = true;
+        let shutdown = match self.shutdown_sequence.take() {
+            Some((groups, delay)) => self.relax_sequenced(groups, delay).await,
+            None => Ok(()),
+        };
+        let close = self.port.close().await;
+        shutdown.and(close)
     }
 
-    pub async fn sync_write_position<T: Into<SyncCommand>>(
-        &mut self,
-        positions: Vec<T>,
-    ) -> Result<()> {
-        let positions: Vec<SyncCommand> = positions
-            .into_iter()
-            .map(|command| command.into())
-            .collect();
-        let message = Instruction::sync_command(GOAL_POSITION, 2, positions);
-        self.port.send(message).await?;
-        Ok(())
+    /// After every sync write read the written register back from one servo of the batch,
+    /// rotating through them, to catch packets silently dropped by the adapter. Failures are
+    /// logged and counted in [`Self::bus_stats`] instead of failing the write.
+    pub fn set_sync_spot_check(&mut self, enabled: bool) {
+        self.sync_spot_check = enabled;
     }
 
-    pub async fn sync_write_position_degrees(
-        &mut self,
-        positions: Vec<SyncCommandFloat>,
-    ) -> Result<()> {
-        let positions_dyn_units: Vec<SyncCommand> = positions
-            .into_iter()
-            .map(|command| {
-                let goal_position = ((command.value() * 3.41) as i32) as u32;
-                SyncCommand::new(command.id(), goal_position)
-            })
-            .collect();
-        let message = Instruction::sync_command(GOAL_POSITION, 2, positions_dyn_units);
-        self.port.send(message).await?;
-        Ok(())
+    /// Read multiple servos with the SYNC_READ instruction of the USB2AX adapter
+    /// instead of one round trip per servo
+    pub fn set_usb2ax_sync_read(&mut self, enabled: bool) {
+        self.usb2ax_sync_read = enabled;
     }
 
-    pub async fn sync_write_position_rad(
-        &mut self,
-        positions: Vec<SyncCommandFloat>,
-    ) -> Result<()> {
-        let positions_degrees: Vec<SyncCommandFloat> = positions
-            .into_iter()
-            .map(|command| SyncCommandFloat::new(command.id(), command.value().to_degrees()))
-            .collect();
-        self.sync_write_position_degrees(positions_degrees).await?;
-        Ok(())
+    /// Pings the id the USB2AX adapter answers to and enables its sync read if it responds
+    pub async fn detect_usb2ax(&mut self) -> Result<bool> {
+        let detected = match self.ping(USB2AX_ID).await {
+            Ok(()) => true,
+            Err(error) if matches!(error.root(), DynamixelDriverError::IoError(_)) => {
+                return Err(error)
+            }
+            Err(_) => false,
+        };
+        self.usb2ax_sync_read = detected;
+        Ok(detected)
     }
 
-    pub async fn sync_write_moving_speed<T: Into<SyncCommand>>(
-        &mut self,
-        speeds: Vec<T>,
-    ) -> Result<()> {
-        let speeds: Vec<SyncCommand> = speeds.into_iter().map(|command| command.into()).collect();
-        let message = Instruction::sync_command(MOVING_SPEED, 2, speeds);
-        self.port.send(message).await?;
-        Ok(())
+    /// Declare model and calibration of a servo, replacing any previous declaration for its id
+    pub fn configure_servo(&mut self, servo: ServoDescription) {
+        self.servos.insert(servo.id, servo);
     }
 
-    pub async fn read_max_torque(&mut self, id: u8) -> Result<f32> {
-        let max_torque = self.read_u16(id, MAX_TORQUE).await? as f32;
-        let max_torque_percentage = max_torque / 2013.0;
-        Ok(max_torque_percentage)
+    pub fn servo_description(&self, id: u8) -> Option<&ServoDescription> {
+        self.servos.get(&id)
     }
 
-    pub async fn search_all(&mut self) -> Result<Vec<u8>> {
-        let mut ids = vec![];
-        for i in 1..254 {
-            if self.ping(i).await.is_ok() {
-                ids.push(i);
+    /// Resolves a joint name declared with [`Self::configure_servo`] to a servo id
+    pub fn joint_id(&self, name: &str) -> Result<u8> {
+        self.servos
+            .values()
+            .find(|servo| servo.name.as_deref() == Some(name))
+            .map(|servo| servo.id)
+            .ok_or_else(|| DynamixelDriverError::UnknownJoint(name.to_owned()))
+    }
+
+    /// Converts a goal in joint degrees to servo degrees, rejecting goals outside joint limits
+    fn goal_to_servo_degrees(&self, id: u8, degrees: f32) -> Result<f32> {
+        match self.servos.get(&id) {
+            Some(servo) => {
+                servo.check_limits(degrees)?;
+                Ok(servo.joint_to_servo_degrees(degrees))
             }
+            None => Ok(degrees),
         }
-        Ok(ids)
     }
 
-    pub async fn clear_io_buffers(&mut self) -> Result<()> {
-        self.port.clear_io_buffers().await?;
-        Ok(())
+    /// Sets joint limits of configured servos from the revolute joints of a URDF
+    /// with the same name, returns names of URDF joints without a matching servo
+    #[cfg(feature = "urdf")]
+    pub fn apply_urdf_limits(&mut self, urdf: &str) -> Result<Vec<String>> {
+        let mut unmatched = vec![];
+        for joint in urdf::parse_joints(urdf)? {
+            let servo = self
+                .servos
+                .values_mut()
+                .find(|servo| servo.name.as_deref() == Some(joint.name.as_str()));
+            match servo {
+                Some(servo) => {
+                    servo.min_degrees = Some(joint.lower_degrees);
+                    servo.max_degrees = Some(joint.upper_degrees);
+                }
+                None => unmatched.push(joint.name),
+            }
+        }
+        Ok(unmatched)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use async_trait::async_trait;
-    use instructions::Instruction;
-    use serial_driver::Status;
-    use std::sync::{Arc, Mutex};
+    /// Reads the model of each servo on first use and caches it to select position
+    /// conversions and ranges, so mixed AX/MX buses work without declaring models
+    ///
+    /// Without it servos that weren't declared with [`Self::configure_servo`] are
+    /// treated as AX series.
+    pub fn set_auto_detect_models(&mut self, enabled: bool) {
+        self.auto_detect_models = enabled;
+    }
 
-    struct MockFramedDriver {
-        written_data: Arc<Mutex<Vec<Vec<u8>>>>,
-        mock_read_data: Vec<Status>,
+    /// Make [`Self::search_all`] ping every id with Protocol 1.0 and 2.0 framing and
+    /// talk to each servo found in the protocol it answered, so buses mixing both work
+    ///
+    /// Servos not found by a scan use the protocol of the driver, as do broadcasts and
+    /// sync writes. The transport has to decode both generations, see
+    /// [`FramedDriver::set_mixed_protocols`].
+    pub fn set_detect_protocols(&mut self, enabled: bool) {
+        self.detect_protocols = enabled;
+        self.port.set_mixed_protocols(enabled);
     }
 
-    impl MockFramedDriver {
-        fn new(mock_read_data: Vec<Status>, written_data: Arc<Mutex<Vec<Vec<u8>>>>) -> Self {
-            MockFramedDriver {
-                written_data,
-                mock_read_data,
+    /// Protocol generation servo `id` answered in during the last scan
+    pub fn servo_protocol(&self, id: u8) -> Option<u8> {
+        self.servo_protocols
+            .get(&id)
+            .map(|protocol| protocol.version())
+    }
+
+    fn protocol_for(&self, id: u8) -> &dyn Protocol {
+        self.servo_protocols
+            .get(&id)
+            .unwrap_or(&self.protocol)
+            .as_ref()
+    }
+
+    // protocols in use, the bus default first and every other generation once
+    fn bus_protocols(&self) -> Vec<Arc<dyn Protocol>> {
+        let mut protocols = vec![self.protocol.clone()];
+        for protocol in self.servo_protocols.values() {
+            if protocols
+                .iter()
+                .all(|known| known.version() != protocol.version())
+            {
+                protocols.push(protocol.clone());
             }
         }
+        protocols
     }
 
-    #[async_trait]
-    impl FramedDriver for MockFramedDriver {
-        async fn send(&mut self, message: Instruction) -> Result<()> {
-            let payload = message.serialize();
-            self.written_data.lock().unwrap().push(payload);
-            Ok(())
+    // AX register addresses are translated for Protocol 2.0 servos where the X series has
+    // an equivalent of the same width
+    fn register_address(&self, id: u8, addr: u8) -> Result<u8> {
+        if self.protocol_for(id).version() != 2 {
+            return Ok(addr);
+        }
+        match addr {
+            MODEL_NUMBER => Ok(MODEL_NUMBER),
+            FIRMWARE_VERSION => Ok(PROTOCOL2_FIRMWARE_VERSION),
+            ID => Ok(PROTOCOL2_ID),
+            TORQUE_ENABLED => Ok(PROTOCOL2_TORQUE_ENABLE),
+            LED => Ok(PROTOCOL2_LED),
+            REGISTERED_INSTRUCTION => Ok(PROTOCOL2_REGISTERED_INSTRUCTION),
+            MOVING => Ok(PROTOCOL2_MOVING),
+            PRESENT_TEMPERATURE => Ok(PROTOCOL2_PRESENT_TEMPERATURE),
+            _ => Err(DynamixelDriverError::NoProtocol2Register { id, addr }),
         }
+    }
 
-        async fn receive(&mut self) -> Result<Status> {
-            Ok(self.mock_read_data.remove(0))
+    fn require_protocol2(&self, id: u8) -> Result<()> {
+        match self.protocol_for(id).version() {
+            2 => Ok(()),
+            _ => Err(DynamixelDriverError::Protocol2Required(id)),
         }
+    }
 
-        async fn clear_io_buffers(&mut self) -> Result<()> {
-            Ok(())
+    fn require_ax_table(&self, id: u8, addr: u8) -> Result<()> {
+        match self.protocol_for(id).version() {
+            2 => Err(DynamixelDriverError::NoProtocol2Register { id, addr }),
+            _ => Ok(()),
         }
     }
 
-    #[tokio::test]
-    async fn sync_write_compliance_writes() {
-        let writing_buffer = Arc::new(Mutex::new(vec![]));
-        let mock_port = MockFramedDriver::new(vec![], writing_buffer.clone());
-        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
-        let commands = vec![(1_u8, 0_u32), (2, 0), (3, 0), (4, 0)];
-        driver
-            .sync_write_compliance_slope_both(commands)
-            .await
-            .unwrap();
+    // sync writes go out in the protocol of the driver, so every servo has to speak it
+    fn sync_address(&self, addr: u8, commands: &[SyncCommand]) -> Result<u8> {
+        let version = self.protocol.version();
+        if let Some(command) = commands
+            .iter()
+            .find(|command| self.protocol_for(command.id()).version() != version)
+        {
+            return Err(DynamixelDriverError::SyncProtocolMismatch(command.id()));
+        }
+        self.register_address(BROADCAST_ID, addr)
+    }
 
-        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
-        assert_eq!(
-            writing_buffer_guard.remove(0),
-            vec![255, 255, 254, 12, 131, 28, 1, 1, 0, 2, 0, 3, 0, 4, 0, 75]
-        );
-        assert_eq!(
-            writing_buffer_guard.remove(0),
-            vec![255, 255, 254, 12, 131, 29, 1, 1, 0, 2, 0, 3, 0, 4, 0, 74]
-        );
-        assert!(writing_buffer_guard.is_empty());
+    /// Check goal positions of servos whose limits were read with
+    /// [`Self::read_angle_limits`] before sending them
+    pub fn set_angle_limit_policy(&mut self, policy: AngleLimitPolicy) {
+        self.angle_limit_policy = policy;
     }
 
-    #[tokio::test]
-    async fn sync_write_positions_writes() {
-        let writing_buffer = Arc::new(Mutex::new(vec![]));
-        let mock_port = MockFramedDriver::new(vec![], writing_buffer.clone());
-        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
-        let commands = vec![(1_u8, 0_u32), (2, 0), (3, 0), (4, 0)];
-        driver.sync_write_position(commands).await.unwrap();
+    /// Reads the CW and CCW angle limits of a servo, goal positions written to it from
+    /// then on are handled according to [`Self::set_angle_limit_policy`]
+    ///
+    /// Protocol 2.0 servos report their min and max position limits as the CW and CCW limits.
+    pub async fn read_angle_limits(&mut self, id: u8) -> Result<AngleLimits> {
+        let limits = match self.protocol_for(id).version() {
+            2 => {
+                // max position limit is followed by the min position limit
+                let command =
+                    self.protocol_for(id)
+                        .read(id, PROTOCOL2_MAX_POSITION_LIMIT.into(), 8);
+                let limits: [u8; 8] = self.transaction(command).await?.read_bytes()?;
+                let limit = |bytes: &[u8]| {
+                    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+                        .min(u16::MAX.into()) as u16
+                };
+                AngleLimits {
+                    cw: limit(&limits[4..]),
+                    ccw: limit(&limits[..4]),
+                }
+            }
+            _ => {
+                let command = self.protocol_for(id).read(id, CW_ANGLE_LIMIT.into(), 4);
+                let status = self.transaction(command).await?;
+                let params = status.params();
+                AngleLimits {
+                    cw: u16::from_le_bytes([params[0], params[1]]),
+                    ccw: u16::from_le_bytes([params[2], params[3]]),
+                }
+            }
+        };
+        self.angle_limits.insert(id, limits);
+        Ok(limits)
+    }
 
-        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
-        assert_eq!(
-            writing_buffer_guard.remove(0),
-            vec![255, 255, 254, 16, 131, 30, 2, 1, 0, 0, 2, 0, 0, 3, 0, 0, 4, 0, 0, 68]
-        );
-        assert!(writing_buffer_guard.is_empty());
+    /// Angle limits read with [`Self::read_angle_limits`], updated by later limit writes
+    pub fn known_angle_limits(&self, id: u8) -> Option<AngleLimits> {
+        self.angle_limits.get(&id).copied()
     }
 
-    #[tokio::test]
-    async fn write_positions_writes() {
-        let writing_buffer = Arc::new(Mutex::new(vec![]));
-        let mock_port = MockFramedDriver::new(vec![Status::new(1, vec![])], writing_buffer.clone());
-        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
-        driver.write_position(1, 150).await.unwrap();
-        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
-        assert_eq!(
-            writing_buffer_guard.remove(0),
-            vec![255, 255, 1, 5, 3, 30, 150, 0, 66]
-        );
-        assert!(writing_buffer_guard.is_empty());
+    // takes the address of the servo's own control table, the upper half of four byte
+    // goal position writes to AX servos is the moving speed
+    fn limit_goal(&self, id: u8, addr: u8, value: u32) -> Result<u32> {
+        let Some(limits) = self.angle_limits.get(&id) else {
+            return Ok(value);
+        };
+        match self.protocol_for(id).version() {
+            2 if addr == PROTOCOL2_GOAL_POSITION => {
+                let goal = value.min(u16::MAX.into()) as u16;
+                let limited = limits.apply(id, goal, self.angle_limit_policy)?;
+                Ok(if limited == goal {
+                    value
+                } else {
+                    limited.into()
+                })
+            }
+            2 => Ok(value),
+            _ if addr == GOAL_POSITION => {
+                let goal = limits.apply(id, value as u16, self.angle_limit_policy)?;
+                Ok(value & 0xFFFF_0000 | goal as u32)
+            }
+            _ => Ok(value),
+        }
     }
 
-    #[tokio::test]
-    async fn sync_write_torque_writes() {
-        let writing_buffer = Arc::new(Mutex::new(vec![]));
-        let mock_port = MockFramedDriver::new(vec![Status::new(1, vec![])], writing_buffer.clone());
-        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
-        let input = vec![(1, 0), (2, 0), (3, 1), (4, 1)];
-        driver.sync_write_torque(input).await.unwrap();
-        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
-        assert_eq!(
-            writing_buffer_guard.remove(0),
-            vec![255, 255, 254, 12, 131, 24, 1, 1, 0, 2, 0, 3, 1, 4, 1, 77]
-        );
-        assert!(writing_buffer_guard.is_empty());
+    fn limit_goals(&self, addr: u8, commands: Vec<SyncCommand>) -> Result<Vec<SyncCommand>> {
+        commands
+            .into_iter()
+            .map(|command| {
+                let value = self.limit_goal(command.id(), addr, command.value())?;
+                Ok(SyncCommand::new(command.id(), value))
+            })
+            .collect()
+    }
+
+    fn record_angle_limit_write(&mut self, id: u8, addr: u8, value: u16) {
+        let (servo_protocols, default) = (&self.servo_protocols, &self.protocol);
+        for (servo, limits) in self.angle_limits.iter_mut() {
+            if id != BROADCAST_ID && *servo != id {
+                continue;
+            }
+            let version = servo_protocols.get(servo).unwrap_or(default).version();
+            match (version, addr) {
+                (2, PROTOCOL2_MIN_POSITION_LIMIT) => limits.cw = value,
+                (2, PROTOCOL2_MAX_POSITION_LIMIT) => limits.ccw = value,
+                (2, _) => (),
+                (_, CW_ANGLE_LIMIT) => limits.cw = value,
+                (_, CCW_ANGLE_LIMIT) => limits.ccw = value,
+                _ => (),
+            }
+        }
+    }
+
+    /// Model declared with [`Self::configure_servo`] or read from the servo earlier
+    pub fn known_model(&self, id: u8) -> Option<ServoModel> {
+        self.servos
+            .get(&id)
+            .and_then(|servo| servo.model)
+            .or_else(|| self.models.get(&id).copied())
+    }
+
+    async fn model(&mut self, id: u8) -> Result<ServoModel> {
+        match self.known_model(id) {
+            Some(model) => Ok(model),
+            None if self.auto_detect_models => self.read_model(id).await,
+            None => Ok(ServoModel::Ax12A),
+        }
+    }
+
+    /// Fails with [`DynamixelDriverError::Unsupported`] if the model of `id` is known and
+    /// lacks `capability`, servos of unknown model are let through
+    async fn require(&mut self, id: u8, capability: Capability) -> Result<()> {
+        let Some(model) = self.detected_model(id).await? else {
+            return Ok(());
+        };
+        if !model.supports(capability) {
+            return Err(DynamixelDriverError::Unsupported {
+                id,
+                model,
+                capability,
+            });
+        }
+        Ok(())
+    }
+
+    // known model, read from the servo only when auto detection is enabled
+    async fn detected_model(&mut self, id: u8) -> Result<Option<ServoModel>> {
+        match self.known_model(id) {
+            Some(model) => Ok(Some(model)),
+            None if self.auto_detect_models => self.read_model(id).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn require_all(
+        &mut self,
+        commands: &[SyncCommand],
+        capability: Capability,
+    ) -> Result<()> {
+        for command in commands {
+            self.require(command.id(), capability).await?;
+        }
+        Ok(())
+    }
+
+    /// Converts servo degrees to the nearest position step, rejecting goals outside the range
+    /// of the model, servos of unknown models are treated as AX servos
+    async fn degrees_to_steps(&mut self, id: u8, degrees: f32) -> Result<u16> {
+        let model = self.model(id).await?;
+        Angle::from_degrees(degrees)
+            .to_ticks(model)
+            .ok_or(DynamixelDriverError::PositionOutOfRange { id, degrees, model })
+    }
+
+    async fn steps_to_joint_degrees(&mut self, id: u8, steps: u16) -> Result<f32> {
+        let model = self.model(id).await?;
+        let angle = Angle::from_ticks(steps, model);
+        Ok(self.servo_to_joint_degrees(id, angle.degrees()))
+    }
+
+    fn servo_to_joint_degrees(&self, id: u8, degrees: f32) -> f32 {
+        self.servos
+            .get(&id)
+            .map(|servo| servo.servo_to_joint_degrees(degrees))
+            .unwrap_or(degrees)
+    }
+
+    /// Safe mode rejects writes to the EEPROM area of the control table (id, baud rate,
+    /// limits, alarms) until they are explicitly allowed with [`Self::allow_eeprom_writes`].
+    pub fn enable_safe_mode(&mut self) {
+        self.eeprom_writes_allowed = false;
+    }
+
+    pub fn allow_eeprom_writes(&mut self) {
+        self.eeprom_writes_allowed = true;
+    }
+
+    pub fn eeprom_writes_allowed(&self) -> bool {
+        self.eeprom_writes_allowed
+    }
+
+    /// Before writing to EEPROM (id, baud rate, limits) read the model number of the target
+    /// and refuse the write unless it matches the model declared with [`Self::configure_servo`]
+    pub fn set_identity_verification(&mut self, enabled: bool) {
+        self.verify_identity = enabled;
+    }
+
+    /// Appends every EEPROM write to the journal at `path`, with the value the register
+    /// had before, so configuration changes can be audited and reverted
+    ///
+    /// The file is created if missing and existing entries are kept. Each journaled write
+    /// reads the register first, see [`JournalEntry`] for the format.
+    pub fn enable_eeprom_journal(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        self.eeprom_journal = Some(EepromJournal::open(path)?);
+        Ok(())
+    }
+
+    pub fn disable_eeprom_journal(&mut self) {
+        self.eeprom_journal = None;
+    }
+
+    // reads the current value when the write has to be journaled
+    async fn journal_entry(
+        &mut self,
+        id: u8,
+        addr: u8,
+        len: u8,
+        new: u16,
+    ) -> Result<Option<JournalEntry>> {
+        if self.eeprom_journal.is_none() || addr >= self.eeprom_end(id) {
+            return Ok(None);
+        }
+        let old = if id == BROADCAST_ID {
+            None
+        } else {
+            Some(self.read_address(id, addr, len).await?)
+        };
+        Ok(Some(JournalEntry {
+            time: SystemTime::now(),
+            id,
+            register: addr,
+            len,
+            old,
+            new,
+        }))
+    }
+
+    // journal entries hold up to two bytes, longer writes are recorded in pieces
+    async fn journal_entries(
+        &mut self,
+        id: u8,
+        addr: u16,
+        data: &[u8],
+    ) -> Result<Vec<Option<JournalEntry>>> {
+        let mut entries = vec![];
+        for (offset, chunk) in data.chunks(2).enumerate() {
+            let chunk_addr = u16::try_from(offset * 2)
+                .ok()
+                .and_then(|offset| addr.checked_add(offset))
+                .ok_or(DynamixelDriverError::AddressOverflow {
+                    id,
+                    addr,
+                    len: data.len(),
+                })?;
+            // EEPROM ends long before address 256, nothing past it is journaled
+            let Ok(chunk_addr) = u8::try_from(chunk_addr) else {
+                break;
+            };
+            let value = match chunk {
+                [low, high] => u16::from_le_bytes([*low, *high]),
+                [value] => (*value).into(),
+                _ => unreachable!("chunks are one or two bytes"),
+            };
+            entries.push(
+                self.journal_entry(id, chunk_addr, chunk.len() as u8, value)
+                    .await?,
+            );
+        }
+        Ok(entries)
+    }
+
+    fn append_journal(&mut self, entry: Option<JournalEntry>) -> Result<()> {
+        match (entry, &mut self.eeprom_journal) {
+            (Some(entry), Some(journal)) => journal.append(&JournalEntry {
+                time: SystemTime::now(),
+                ..entry
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn eeprom_end(&self, id: u8) -> u8 {
+        match self.protocol_for(id).version() {
+            2 => PROTOCOL2_EEPROM_END,
+            _ => EEPROM_END,
+        }
+    }
+
+    async fn check_eeprom_write(&mut self, id: u8, addr: u8) -> Result<()> {
+        if addr >= self.eeprom_end(id) {
+            return Ok(());
+        }
+        if !self.eeprom_writes_allowed {
+            return Err(DynamixelDriverError::EepromWriteLocked(addr));
+        }
+        if self.verify_identity {
+            self.verify_model(id).await?;
+        }
+        Ok(())
+    }
+
+    async fn verify_model(&mut self, id: u8) -> Result<()> {
+        let expected = self
+            .servos
+            .get(&id)
+            .and_then(|servo| servo.model)
+            .ok_or(DynamixelDriverError::UnknownExpectedModel(id))?;
+        let actual = self.read_model(id).await?;
+        if actual != expected {
+            return Err(DynamixelDriverError::ModelMismatch {
+                id,
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    async fn transaction(&mut self, instruction: Instruction) -> Result<Status> {
+        let id = instruction.id();
+        if let Some(breaker) = &self.circuit_breaker {
+            breaker.check(id, Instant::now())?;
+        }
+        #[cfg(feature = "tracing")]
+        let span = match &self.name {
+            // error level so the name is attached to log lines at any filter level
+            Some(name) => tracing::error_span!("dynamixel_bus", bus = %name),
+            None => tracing::Span::none(),
+        };
+        let result = self.transaction_with_retries(instruction);
+        #[cfg(feature = "tracing")]
+        let result = tracing::Instrument::instrument(result, span);
+        let result = result.await;
+        if let Some(breaker) = &mut self.circuit_breaker {
+            if let Some(state) = breaker.record(id, &result, Instant::now()) {
+                events::emit(&self.events, DriverEvent::CircuitStateChanged { id, state });
+            }
+        }
+        let result = match result {
+            Err(DynamixelDriverError::StatusError(error)) => Err(
+                DynamixelDriverError::StatusError(self.handle_alarm(id, error).await),
+            ),
+            result => result,
+        };
+        result.map_err(|error| self.with_bus_name(error))
+    }
+
+    /// Publishes the alarm and applies the safety policy
+    ///
+    /// Protocol 2.0 alerts are resolved through the hardware error status register
+    /// first, the returned error carries the flags found there.
+    async fn handle_alarm(&mut self, id: u8, mut error: StatusError) -> StatusError {
+        if error.hardware_alert && self.protocol_for(id).version() == 2 {
+            match self.receive_hardware_error_status(id).await {
+                Ok(status) => error = error.with_hardware_error(status),
+                Err(read_error) => warn!(
+                    "failed reading hardware error status of servo {}: {}",
+                    id, read_error
+                ),
+            }
+        }
+        events::emit(
+            &self.events,
+            DriverEvent::Alarm {
+                id,
+                error: error.clone(),
+            },
+        );
+        if self.safety_policy.is_triggered_by(&error) {
+            self.safety_torque_off(id, error.clone()).await;
+        }
+        error
+    }
+
+    /// Reads which hardware errors a Protocol 2.0 servo has latched, the alert flag of
+    /// its statuses only tells that one is
+    pub async fn read_hardware_error_status(&mut self, id: u8) -> Result<HardwareErrorStatus> {
+        if self.protocol_for(id).version() != 2 {
+            return Err(DynamixelDriverError::Protocol2Required(id));
+        }
+        self.receive_hardware_error_status(id)
+            .await
+            .map_err(|error| self.with_bus_name(error))
+    }
+
+    // bypasses exchange() as every status carries the alert until the servo reboots
+    async fn receive_hardware_error_status(&mut self, id: u8) -> Result<HardwareErrorStatus> {
+        let instruction = self
+            .protocol_for(id)
+            .read(id, PROTOCOL2_HARDWARE_ERROR_STATUS.into(), 1);
+        self.port.send(instruction).await?;
+        let status = self.port.receive().await?;
+        if status.id() != id {
+            return Err(DynamixelDriverError::IdMismatchError(id, status.id()));
+        }
+        match status.params() {
+            [status] => Ok(HardwareErrorStatus::from_flags(*status)),
+            params => Err(DynamixelDriverError::ResponseLengthMismatch {
+                id,
+                expected: 1,
+                actual: params.len(),
+            }),
+        }
+    }
+
+    fn with_bus_name(&self, error: DynamixelDriverError) -> DynamixelDriverError {
+        match &self.name {
+            Some(bus) => DynamixelDriverError::Bus {
+                bus: bus.clone(),
+                source: Box::new(error),
+            },
+            None => error,
+        }
+    }
+
+    // bypasses transaction() as the servo keeps reporting the error in every status
+    async fn safety_torque_off(&mut self, id: u8, error: StatusError) {
+        let policy = self.safety_policy;
+        warn!("turning off torque after servo {} reported {:?}", id, error);
+        let result = match policy {
+            SafetyPolicy::Disabled => return,
+            SafetyPolicy::TorqueOffServo => self.torque_off(id).await,
+            SafetyPolicy::TorqueOffBus => {
+                // one broadcast per protocol generation on the bus
+                let mut result = Ok(());
+                for protocol in self.bus_protocols() {
+                    let addr = match protocol.version() {
+                        2 => PROTOCOL2_TORQUE_ENABLE,
+                        _ => TORQUE_ENABLED,
+                    };
+                    let instruction = protocol.write(BROADCAST_ID, addr.into(), &[0]);
+                    result = result.and(self.port.send(instruction).await);
+                }
+                result
+            }
+        };
+        match result {
+            Ok(()) => events::emit(
+                &self.events,
+                DriverEvent::SafetyTorqueOff { id, error, policy },
+            ),
+            Err(error) => warn!("failed turning off torque of servo {}: {}", id, error),
+        }
+    }
+
+    // torque enable is in RAM on every table, the lock is still checked without going
+    // through check_eeprom_write as identity checks would end up back in the alarm handler
+    async fn torque_off(&mut self, id: u8) -> Result<()> {
+        let addr = self.register_address(id, TORQUE_ENABLED)?;
+        if addr < self.eeprom_end(id) && !self.eeprom_writes_allowed {
+            return Err(DynamixelDriverError::EepromWriteLocked(addr));
+        }
+        let instruction = self.protocol_for(id).write(id, addr.into(), &[0]);
+        match self.send_and_receive(instruction).await {
+            Ok(_) | Err(DynamixelDriverError::StatusError(_)) => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+
+    async fn transaction_with_retries(&mut self, instruction: Instruction) -> Result<Status> {
+        let mut attempt = 0;
+        let mut checksum_retried = false;
+        loop {
+            match self.send_and_receive(instruction.clone()).await {
+                Err(DynamixelDriverError::ChecksumError(expected, received))
+                    if self.checksum_policy == ChecksumPolicy::Retry && !checksum_retried =>
+                {
+                    checksum_retried = true;
+                    self.checksum_retries += 1;
+                    warn!(
+                        "retransmitting after checksum error, expected {:?} received {:?}",
+                        expected, received
+                    );
+                }
+                Err(error) if error.is_recoverable() && attempt < self.retries => {
+                    attempt += 1;
+                    warn!("retrying transaction after error {}", error);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn send_and_receive(&mut self, instruction: Instruction) -> Result<Status> {
+        if self.flush_policy == FlushPolicy::BeforeTransaction {
+            self.port.clear_io_buffers().await?;
+        }
+        let result = self.exchange(instruction).await;
+        if result.is_err() && self.flush_policy == FlushPolicy::OnError {
+            self.port.clear_io_buffers().await?;
+        }
+        result
+    }
+
+    async fn exchange(&mut self, instruction: Instruction) -> Result<Status> {
+        let id = instruction.response_id();
+        let expected_params = instruction.expected_response_params();
+        self.port.send(instruction).await?;
+        let mut response = self.port.receive().await?;
+        StatusError::check_error(response.error_flags())?;
+        let mut discarded = 0;
+        while id != response.id() {
+            match self.id_mismatch_policy {
+                IdMismatchPolicy::AcceptAny => break,
+                IdMismatchPolicy::Discard if discarded < MAX_DISCARDED_RESPONSES => {
+                    warn!(
+                        "discarding status from {} while waiting for {}",
+                        response.id(),
+                        id
+                    );
+                    discarded += 1;
+                    response = self.port.receive().await?;
+                }
+                _ => return Err(DynamixelDriverError::IdMismatchError(id, response.id())),
+            }
+        }
+        if response.params().len() != expected_params {
+            // most likely a stale answer to an earlier instruction, drop whatever else is buffered
+            self.port.clear_io_buffers().await?;
+            return Err(DynamixelDriverError::ResponseLengthMismatch {
+                id,
+                expected: expected_params,
+                actual: response.params().len(),
+            });
+        }
+        Ok(response)
+    }
+
+    /// Sends any instruction and returns the status answering it, for instructions the
+    /// driver has no method for
+    ///
+    /// Retries, circuit breaker and id checks apply like for every other transaction.
+    /// WRITE and REG_WRITE instructions are held to the EEPROM lock and journaled.
+    /// Servos never answer broadcast instructions, these end in a timeout.
+    pub async fn send_instruction(&mut self, instruction: Instruction) -> Result<Status> {
+        let journal = match instruction.opcode() {
+            protocol::WRITE | protocol::REG_WRITE => {
+                let id = instruction.id();
+                let params = instruction.params();
+                // Protocol 2.0 addresses are two bytes wide
+                let (addr, data) = match (instruction.is_protocol2(), params.as_ref()) {
+                    (true, [low, high, data @ ..]) => (u16::from_le_bytes([*low, *high]), data),
+                    (false, [addr, data @ ..]) => ((*addr).into(), data),
+                    _ => return Err(DynamixelDriverError::DecodingError("write without address")),
+                };
+                if let Ok(addr) = u8::try_from(addr) {
+                    self.check_eeprom_write(id, addr).await?;
+                }
+                self.journal_entries(id, addr, data).await?
+            }
+            _ => vec![],
+        };
+        let status = self.transaction(instruction).await?;
+        for entry in journal {
+            self.append_journal(entry)?;
+        }
+        Ok(status)
+    }
+
+    async fn read_u8(&mut self, id: u8, addr: u8) -> Result<u8> {
+        let addr = self.register_address(id, addr)?;
+        Ok(self.read_address(id, addr, 1).await? as u8)
+    }
+
+    async fn read_u16(&mut self, id: u8, addr: u8) -> Result<u16> {
+        let addr = self.register_address(id, addr)?;
+        self.read_address(id, addr, 2).await
+    }
+
+    // reads a u8 or u16 at an address of the servo's own control table
+    async fn read_address(&mut self, id: u8, addr: u8, len: u8) -> Result<u16> {
+        let command = self.protocol_for(id).read(id, addr.into(), len.into());
+        let status = self.transaction(command).await?;
+        match len {
+            1 => status.as_u8().map(u16::from),
+            _ => status.as_u16(),
+        }
+    }
+
+    async fn read_u16_many(&mut self, ids: &[u8], addr: u8) -> Vec<(u8, Result<u16>)> {
+        self.read_many(ids, addr, 2).await
+    }
+
+    async fn read_u8_many(&mut self, ids: &[u8], addr: u8) -> Vec<(u8, Result<u8>)> {
+        self.read_many(ids, addr, 1)
+            .await
+            .into_iter()
+            .map(|(id, value)| (id, value.map(|value| value as u8)))
+            .collect()
+    }
+
+    /// Reads the same u8 or u16 register of several servos, with a single USB2AX sync read
+    /// when enabled and falling back to individual reads to attribute failures
+    async fn read_many(&mut self, ids: &[u8], addr: u8, len: u8) -> Vec<(u8, Result<u16>)> {
+        // the USB2AX only speaks Protocol 1.0
+        if self.usb2ax_sync_read
+            && !ids.is_empty()
+            && ids.iter().all(|&id| self.protocol_for(id).version() == 1)
+        {
+            match self.usb2ax_sync_read(ids, addr, len).await {
+                Ok(values) => {
+                    return ids
+                        .iter()
+                        .copied()
+                        .zip(values.into_iter().map(Ok))
+                        .collect()
+                }
+                Err(error) => warn!(
+                    "USB2AX sync read failed, reading servos one by one: {}",
+                    error
+                ),
+            }
+        }
+        let mut values = Vec::with_capacity(ids.len());
+        for &id in ids {
+            let value = match len {
+                1 => self.read_u8(id, addr).await.map(u16::from),
+                _ => self.read_u16(id, addr).await,
+            };
+            values.push((id, value));
+        }
+        values
+    }
+
+    /// Reads a different register range of every servo with one BULK_READ instruction,
+    /// returning a result per entry of `reads` in the same order
+    ///
+    /// Only the MX series supports bulk reads. Servos that don't answer are reported as
+    /// timeouts without losing the statuses of the servos after them.
+    pub async fn bulk_read(&mut self, reads: &[BulkRead]) -> Result<Vec<(u8, Result<Status>)>> {
+        for read in reads {
+            self.require_ax_table(read.id, read.addr)?;
+            self.require(read.id, Capability::BulkRead).await?;
+        }
+        if self.flush_policy == FlushPolicy::BeforeTransaction {
+            self.port.clear_io_buffers().await?;
+        }
+        self.port.send(Instruction::bulk_read(reads)).await?;
+        let ids: Vec<u8> = reads.iter().map(|read| read.id).collect();
+        let statuses = self.port.receive_batch(&ids).await;
+        let mut results = Vec::with_capacity(statuses.len());
+        // alarms are handled once every status is in, torque off would interleave otherwise
+        for ((id, result), read) in statuses.into_iter().zip(reads) {
+            let result = result
+                .and_then(|status| StatusError::check_error(status.error_flags()).map(|_| status));
+            let result = match result {
+                Ok(status) if status.params().len() != read.len as usize => {
+                    Err(DynamixelDriverError::ResponseLengthMismatch {
+                        id,
+                        expected: read.len.into(),
+                        actual: status.params().len(),
+                    })
+                }
+                Err(DynamixelDriverError::StatusError(error)) => Err(
+                    DynamixelDriverError::StatusError(self.handle_alarm(id, error).await),
+                ),
+                result => result,
+            };
+            results.push((id, result.map_err(|error| self.with_bus_name(error))));
+        }
+        Ok(results)
+    }
+
+    /// Writes a different register of every servo with one Protocol 2.0 BULK_WRITE
+    ///
+    /// Entries are checked before anything is sent: every servo has to speak Protocol 2.0
+    /// and be listed once, and the written range has to fit the control table of servos
+    /// of known model. Goal positions, EEPROM locks and the EEPROM journal apply like they
+    /// do to single writes.
+    pub async fn bulk_write(&mut self, writes: &BulkWrite) -> Result<()> {
+        let mut checked = BulkWrite::new();
+        let mut journal = vec![];
+        for (index, entry) in writes.entries().iter().enumerate() {
+            if self.protocol_for(entry.id).version() != 2 {
+                return Err(DynamixelDriverError::Protocol2Required(entry.id));
+            }
+            if writes.entries()[..index]
+                .iter()
+                .any(|earlier| earlier.id == entry.id)
+            {
+                return Err(DynamixelDriverError::DuplicateBulkWrite(entry.id));
+            }
+            if let Some(model) = self.detected_model(entry.id).await? {
+                let end = entry.addr as usize + entry.data.len();
+                if model
+                    .control_table_len()
+                    .is_some_and(|table_len| end > table_len.into())
+                {
+                    return Err(DynamixelDriverError::RegisterOutOfRange {
+                        id: entry.id,
+                        model,
+                        addr: entry.addr,
+                        len: entry.data.len(),
+                    });
+                }
+            }
+            let data = self.limit_bulk_goal(entry)?;
+            if let Ok(addr) = u8::try_from(entry.addr) {
+                self.check_eeprom_write(entry.id, addr).await?;
+            }
+            journal.extend(self.journal_entries(entry.id, entry.addr, &data).await?);
+            checked = checked.bytes(entry.id, entry.addr, &data);
+        }
+        if checked.is_empty() {
+            return Ok(());
+        }
+        self.port.send(Instruction::bulk_write(&checked)).await?;
+        // position limits of the X series are four bytes wide
+        for entry in checked.entries() {
+            for (offset, chunk) in entry.data.chunks_exact(4).enumerate() {
+                if let Ok(addr) = u8::try_from(entry.addr as usize + offset * 4) {
+                    let value = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                    self.record_angle_limit_write(
+                        entry.id,
+                        addr,
+                        value.min(u16::MAX.into()) as u16,
+                    );
+                }
+            }
+        }
+        for entry in journal {
+            self.append_journal(entry)?;
+        }
+        Ok(())
+    }
+
+    // goal position of the X series is four bytes, shorter writes only set its lower bytes
+    fn limit_bulk_goal(&self, entry: &BulkWriteEntry) -> Result<Vec<u8>> {
+        let len = entry.data.len();
+        if entry.addr != PROTOCOL2_GOAL_POSITION.into() || !(2..=4).contains(&len) {
+            return Ok(entry.data.clone());
+        }
+        let mut value = [0; 4];
+        value[..len].copy_from_slice(&entry.data);
+        let value =
+            self.limit_goal(entry.id, PROTOCOL2_GOAL_POSITION, u32::from_le_bytes(value))?;
+        Ok(value.to_le_bytes()[..len].to_vec())
+    }
+
+    async fn usb2ax_sync_read(&mut self, ids: &[u8], addr: u8, len: u8) -> Result<Vec<u16>> {
+        // status parameters have to fit in a single packet
+        let max_servos = 250 / len as usize;
+        let mut values = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(max_servos) {
+            let command = Instruction::usb2ax_sync_read(addr, len, chunk);
+            let status = self.transaction(command).await?;
+            values.extend(
+                status
+                    .params()
+                    .chunks_exact(len.into())
+                    .map(|value| match value {
+                        [low, high] => u16::from_le_bytes([*low, *high]),
+                        [value] => (*value).into(),
+                        _ => unreachable!("registers are one or two bytes"),
+                    }),
+            );
+        }
+        Ok(values)
+    }
+
+    async fn write_u8(&mut self, id: u8, addr: u8, value: u8) -> Result<()> {
+        let addr = self.register_address(id, addr)?;
+        self.check_eeprom_write(id, addr).await?;
+        let entry = self.journal_entry(id, addr, 1, value.into()).await?;
+        let msg = self.protocol_for(id).write(id, addr.into(), &[value]);
+        self.transaction(msg).await?;
+        self.append_journal(entry)
+    }
+
+    async fn write_u16(&mut self, id: u8, addr: u8, value: u16) -> Result<()> {
+        let addr = self.register_address(id, addr)?;
+        let value = self.limit_goal(id, addr, value.into())? as u16;
+        self.check_eeprom_write(id, addr).await?;
+        let entry = self.journal_entry(id, addr, 2, value).await?;
+        let msg = self
+            .protocol_for(id)
+            .write(id, addr.into(), &value.to_le_bytes());
+        self.transaction(msg).await?;
+        self.record_angle_limit_write(id, addr, value);
+        self.append_journal(entry)
+    }
+
+    pub async fn ping(&mut self, id: u8) -> Result<()> {
+        let ping = self.protocol_for(id).ping(id);
+        self.transaction(ping).await?;
+        Ok(())
+    }
+
+    /// Restarts a Protocol 2.0 servo, clearing a latched hardware error without power
+    /// cycling the bus
+    ///
+    /// The servo acknowledges before restarting, use [`Self::reboot_and_wait`] to wait
+    /// until it answers again.
+    pub async fn reboot(&mut self, id: u8) -> Result<()> {
+        if self.protocol_for(id).version() != 2 {
+            return Err(DynamixelDriverError::Protocol2Required(id));
+        }
+        let reboot = self.protocol_for(id).reboot(id);
+        // the acknowledgment still carries the error that is being cleared
+        match self.send_and_receive(reboot).await {
+            Ok(_) | Err(DynamixelDriverError::StatusError(_)) => Ok(()),
+            Err(error) => Err(self.with_bus_name(error)),
+        }
+    }
+
+    /// Reboots a servo and pings it until it answers again, failing with
+    /// [`DynamixelDriverError::ServoOffline`] if it doesn't within `timeout`
+    ///
+    /// A servo that answers with a status error is back but still reports an alarm,
+    /// that error is returned right away.
+    pub async fn reboot_and_wait(&mut self, id: u8, timeout: Duration) -> Result<()> {
+        self.reboot(id).await?;
+        let poll_interval = self.motion_poll_interval;
+        let wait = async {
+            loop {
+                match self.ping(id).await {
+                    Ok(()) => return Ok(()),
+                    Err(error) if matches!(error.root(), DynamixelDriverError::StatusError(_)) => {
+                        return Err(error)
+                    }
+                    Err(_) => tokio::time::sleep(poll_interval).await,
+                }
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| DynamixelDriverError::ServoOffline(id))?
+    }
+
+    /// Reboots a Protocol 2.0 servo like [`Self::reboot_and_wait`] and returns the
+    /// hardware errors the reboot cleared
+    pub async fn reboot_and_clear(
+        &mut self,
+        id: u8,
+        timeout: Duration,
+    ) -> Result<HardwareErrorStatus> {
+        let status = self.read_hardware_error_status(id).await?;
+        self.reboot_and_wait(id, timeout).await?;
+        Ok(status)
+    }
+
+    pub async fn read_model(&mut self, id: u8) -> Result<ServoModel> {
+        let model_number = self.read_u16(id, MODEL_NUMBER).await?;
+        let model = ServoModel::from_model_number(model_number);
+        self.models.insert(id, model);
+        Ok(model)
+    }
+
+    /// Changes servo id, refusing if another servo already responds on `new_id`, then pings
+    /// the servo at its new id
+    ///
+    /// The broadcast id and 255 are refused as either would leave the servo unaddressable.
+    pub async fn write_id(&mut self, id: u8, new_id: u8) -> Result<IdChange> {
+        check_id_change(id, new_id)?;
+        if !self.eeprom_writes_allowed {
+            return Err(DynamixelDriverError::EepromWriteLocked(
+                self.register_address(id, ID)?,
+            ));
+        }
+        if id != new_id {
+            match self.ping(new_id).await {
+                Err(error)
+                    if matches!(
+                        error.root(),
+                        DynamixelDriverError::Timeout | DynamixelDriverError::ServoOffline(_)
+                    ) => {}
+                Err(error) if matches!(error.root(), DynamixelDriverError::IoError(_)) => {
+                    return Err(error)
+                }
+                // anything answering, even with an error, means the id is taken
+                _ => return Err(DynamixelDriverError::IdInUse(new_id)),
+            }
+        }
+        self.write_u8(id, ID, new_id).await?;
+        self.models.remove(&id);
+        let verified = match self.ping(new_id).await {
+            Ok(()) => true,
+            Err(error) if matches!(error.root(), DynamixelDriverError::IoError(_)) => {
+                return Err(error)
+            }
+            Err(_) => false,
+        };
+        Ok(IdChange {
+            old_id: id,
+            new_id,
+            verified,
+        })
+    }
+
+    /// Changes servo id without checking whether `new_id` is already in use,
+    /// reserved ids are still refused
+    pub async fn force_write_id(&mut self, id: u8, new_id: u8) -> Result<()> {
+        check_id_change(id, new_id)?;
+        self.write_u8(id, ID, new_id).await?;
+        Ok(())
+    }
+
+    pub async fn write_torque(&mut self, id: u8, torque_enabled: bool) -> Result<()> {
+        if torque_enabled {
+            Ok(self.write_u8(id, TORQUE_ENABLED, 1).await?)
+        } else {
+            Ok(self.write_u8(id, TORQUE_ENABLED, 0).await?)
+        }
+    }
+
+    pub async fn write_torque_named(&mut self, name: &str, torque_enabled: bool) -> Result<()> {
+        let id = self.joint_id(name)?;
+        self.write_torque(id, torque_enabled).await
+    }
+
+    pub async fn write_led(&mut self, id: u8, on: bool) -> Result<()> {
+        self.write_u8(id, LED, on as u8).await
+    }
+
+    /// Blinks the LED of a servo for `duration` so it can be found on the robot
+    pub async fn identify(&mut self, id: u8, duration: Duration) -> Result<()> {
+        let start = tokio::time::Instant::now();
+        let mut on = true;
+        while start.elapsed() < duration {
+            self.write_led(id, on).await?;
+            on = !on;
+            tokio::time::sleep(IDENTIFY_INTERVAL).await;
+        }
+        self.write_led(id, false).await
+    }
+
+    /// Wiggles a servo a few degrees around its present position for `duration`,
+    /// for servos whose LED isn't visible once mounted
+    pub async fn identify_by_wiggle(&mut self, id: u8, duration: Duration) -> Result<()> {
+        let position = self.read_position_degrees(id).await?;
+        let start = tokio::time::Instant::now();
+        let mut offset = IDENTIFY_WIGGLE_DEGREES;
+        while start.elapsed() < duration {
+            self.write_position_degrees(id, position + offset).await?;
+            offset = -offset;
+            tokio::time::sleep(IDENTIFY_INTERVAL).await;
+        }
+        self.write_position_degrees(id, position).await
+    }
+
+    pub async fn read_temperature(&mut self, id: u8) -> Result<u8> {
+        let temperature = self.read_u8(id, PRESENT_TEMPERATURE).await?;
+        if let Some(monitor) = &mut self.temperature_monitor {
+            monitor.record(id, temperature, Instant::now());
+        }
+        Ok(temperature)
+    }
+
+    /// Reads temperature of several servos with a USB2AX sync read when enabled,
+    /// reporting failures per id
+    pub async fn read_temperatures(&mut self, ids: &[u8]) -> Vec<(u8, Result<u8>)> {
+        let temperatures = self.read_u8_many(ids, PRESENT_TEMPERATURE).await;
+        if let Some(monitor) = &mut self.temperature_monitor {
+            let now = Instant::now();
+            for (id, temperature) in &temperatures {
+                if let Ok(temperature) = temperature {
+                    monitor.record(*id, *temperature, now);
+                }
+            }
+        }
+        temperatures
+    }
+
+    pub async fn read_voltage(&mut self, id: u8) -> Result<f32> {
+        let voltage = self.read_u8(id, PRESENT_VOLTAGE).await?;
+        self.record_voltage(id, voltage);
+        Ok(voltage as f32 / 10.0)
+    }
+
+    fn record_voltage(&mut self, id: u8, voltage: u8) {
+        if let Some(brownout) = self
+            .voltage_monitor
+            .as_mut()
+            .and_then(|monitor| monitor.record(id, voltage))
+        {
+            events::emit(&self.events, DriverEvent::Brownout(brownout));
+        }
+    }
+
+    /// Reads voltage of several servos with a USB2AX sync read when enabled, meant to be
+    /// polled while the robot moves with voltage monitoring enabled
+    pub async fn read_voltages(&mut self, ids: &[u8]) -> Vec<(u8, Result<f32>)> {
+        let voltages = self.read_u8_many(ids, PRESENT_VOLTAGE).await;
+        let mut volts = Vec::with_capacity(voltages.len());
+        for (id, voltage) in voltages {
+            if let Ok(voltage) = voltage {
+                self.record_voltage(id, voltage);
+            }
+            volts.push((id, voltage.map(|voltage| voltage as f32 / 10.0)));
+        }
+        volts
+    }
+
+    /// Returns true while an instruction sent with REG_WRITE is waiting for ACTION.
+    pub async fn read_registered_instruction(&mut self, id: u8) -> Result<bool> {
+        Ok(self.read_u8(id, REGISTERED_INSTRUCTION).await? != 0)
+    }
+
+    /// Millisecond clock of a Protocol 2.0 servo, wrapping after 32767, for timestamping
+    /// data read from it
+    pub async fn read_realtime_tick(&mut self, id: u8) -> Result<u16> {
+        if self.protocol_for(id).version() != 2 {
+            return Err(DynamixelDriverError::Protocol2Required(id));
+        }
+        self.read_address(id, PROTOCOL2_REALTIME_TICK, 2).await
+    }
+
+    pub async fn read_position(&mut self, id: u8) -> Result<u16> {
+        let position = self.read_u16(id, PRESENT_POSITION).await?;
+        Ok(position)
+    }
+
+    pub async fn read_position_degrees(&mut self, id: u8) -> Result<f32> {
+        let position = self.read_u16(id, PRESENT_POSITION).await?;
+        let position = self.steps_to_joint_degrees(id, position).await?;
+        self.position_watchers.record(id, position);
+        Ok(position)
+    }
+
+    pub async fn read_position_rad(&mut self, id: u8) -> Result<f32> {
+        Ok(self.read_angle(id).await?.radians())
+    }
+
+    /// Present joint angle with the calibration of the servo applied
+    pub async fn read_angle(&mut self, id: u8) -> Result<Angle> {
+        Ok(Angle::from_degrees(self.read_position_degrees(id).await?))
+    }
+
+    pub async fn read_position_degrees_named(&mut self, name: &str) -> Result<f32> {
+        let id = self.joint_id(name)?;
+        self.read_position_degrees(id).await
+    }
+
+    pub async fn read_position_rad_named(&mut self, name: &str) -> Result<f32> {
+        let id = self.joint_id(name)?;
+        self.read_position_rad(id).await
+    }
+
+    /// Present speed in rpm, positive CCW
+    pub async fn read_present_speed_rpm(&mut self, id: u8) -> Result<f32> {
+        Ok(speed_from_raw(self.read_u16(id, PRESENT_SPEED).await?))
+    }
+
+    /// Reads positions of all servos, reporting failures per id instead of
+    /// aborting on the first servo that doesn't respond
+    pub async fn read_positions(&mut self, ids: &[u8]) -> Vec<(u8, Result<u16>)> {
+        self.read_u16_many(ids, PRESENT_POSITION).await
+    }
+
+    pub async fn read_positions_degrees(&mut self, ids: &[u8]) -> Vec<(u8, Result<f32>)> {
+        let positions = self.read_positions(ids).await;
+        let mut degrees = Vec::with_capacity(positions.len());
+        for (id, position) in positions {
+            let position = match position {
+                Ok(position) => self.steps_to_joint_degrees(id, position).await,
+                Err(error) => Err(error),
+            };
+            if let Ok(position) = position {
+                self.position_watchers.record(id, position);
+            }
+            degrees.push((id, position));
+        }
+        degrees
+    }
+
+    pub async fn read_positions_rad(&mut self, ids: &[u8]) -> Vec<(u8, Result<f32>)> {
+        self.read_positions_degrees(ids)
+            .await
+            .into_iter()
+            .map(|(id, position)| {
+                let position = position.map(|degrees| Angle::from_degrees(degrees).radians());
+                (id, position)
+            })
+            .collect()
+    }
+
+    /// Stream of positions of `id` in joint degrees, yielding only when the servo moved more
+    /// than `threshold` degrees since the last yielded position
+    ///
+    /// The stream is fed by every position this driver reads, including
+    /// [`Self::update_group_feedback`], so one polling loop such as [`Self::poll_positions`]
+    /// can serve many consumers.
+    pub fn position_changes(&mut self, id: u8, threshold: f32) -> PositionChanges {
+        self.position_watchers.subscribe(id, threshold)
+    }
+
+    /// Reads positions of `ids` every `interval` to feed [`Self::position_changes`]
+    ///
+    /// Runs until the future is dropped, servos that fail to answer are skipped until the next tick.
+    pub async fn poll_positions(&mut self, ids: &[u8], interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            for (id, position) in self.read_positions_degrees(ids).await {
+                if let Err(error) = position {
+                    warn!("polling position of servo {} failed: {}", id, error);
+                }
+            }
+        }
+    }
+
+    /// Refreshes position, speed and load of every servo in `feedback` with one read per servo
+    ///
+    /// Servos that fail to answer keep their previous values, check their age with
+    /// [`GroupFeedback::is_stale`] before using them.
+    pub async fn update_group_feedback(&mut self, feedback: &mut GroupFeedback) {
+        let ids: Vec<u8> = feedback.ids().collect();
+        for id in ids {
+            if let Err(error) = self.require_ax_table(id, PRESENT_POSITION) {
+                warn!("failed reading feedback of servo {}: {}", id, error);
+                feedback.record_failure(id);
+                continue;
+            }
+            // present position, speed and load are adjacent u16 registers
+            let command = self.protocol_for(id).read(id, PRESENT_POSITION.into(), 6);
+            let response = match self.model(id).await {
+                Ok(model) => self
+                    .transaction(command)
+                    .await
+                    .map(|status| (model, status)),
+                Err(error) => Err(error),
+            };
+            match response {
+                Ok((model, status)) => {
+                    let params = status.params();
+                    let register = |index: usize| {
+                        u16::from_le_bytes([params[index * 2], params[index * 2 + 1]])
+                    };
+                    let joint = JointFeedback {
+                        position: self.servo_to_joint_degrees(
+                            id,
+                            register(0) as f32 / model.steps_per_degree(),
+                        ),
+                        speed: speed_from_raw(register(1)),
+                        load: signed_from_raw(register(2)) / 1023.0,
+                        updated: Instant::now(),
+                    };
+                    self.position_watchers.record(id, joint.position);
+                    feedback.update(id, joint);
+                }
+                Err(error) => {
+                    warn!("failed reading feedback of servo {}: {}", id, error);
+                    feedback.record_failure(id);
+                }
+            }
+        }
+    }
+
+    /// Feedback of every servo in `feedback` that answered at least once, in the layout of
+    /// ROS 2 `sensor_msgs/JointState`
+    ///
+    /// Joints are named after [`ServoDescription::name`], `servo_<id>` for unnamed servos,
+    /// efforts scale load by the stall torque of the known model.
+    #[cfg(feature = "ros")]
+    pub fn joint_state(&self, feedback: &GroupFeedback) -> ros::JointState {
+        ros::JointState::from_feedback(feedback, |id| {
+            let servo = self.servos.get(&id);
+            ros::JointInfo {
+                name: servo
+                    .and_then(|servo| servo.name.clone())
+                    .unwrap_or_else(|| format!("servo_{id}")),
+                inverted: servo.is_some_and(|servo| servo.inverted),
+                stall_torque: self.known_model(id).and_then(|model| model.stall_torque()),
+            }
+        })
+    }
+
+    /// Reads the whole RAM area of the control table in a single transaction
+    pub async fn read_ram(&mut self, id: u8) -> Result<RamTable> {
+        self.require_ax_table(id, RAM_START)?;
+        let command = self
+            .protocol_for(id)
+            .read(id, RAM_START.into(), RAM_LEN.into());
+        RamTable::from_bytes(self.transaction(command).await?.params())
+    }
+
+    /// Writes the fields that are set, one write instruction per run of adjacent registers
+    ///
+    /// The goal position is checked against the angle limits like [`Self::write_position`].
+    pub async fn write_ram(&mut self, id: u8, mut fields: RamWrite) -> Result<()> {
+        self.require_ax_table(id, RAM_START)?;
+        if let Some(goal) = fields.goal_position {
+            fields.goal_position = Some(self.limit_goal(id, GOAL_POSITION, goal.into())? as u16);
+        }
+        for (addr, data) in fields.runs() {
+            self.transaction(self.protocol_for(id).write(id, addr.into(), &data))
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn read_moving(&mut self, id: u8) -> Result<bool> {
+        Ok(self.read_u8(id, MOVING).await? != 0)
+    }
+
+    /// True if `position` is within `tolerance` degrees of `target`
+    ///
+    /// Tolerance is never tighter than one position step of the servo model,
+    /// since a servo can't be expected to land closer than its resolution.
+    pub fn position_matches(&self, id: u8, position: f32, target: f32, tolerance: f32) -> bool {
+        let resolution = self
+            .known_model(id)
+            .unwrap_or(ServoModel::Ax12A)
+            .resolution_degrees();
+        (position - target).abs() <= tolerance.max(resolution)
+    }
+
+    /// Reads present position and compares it with `target` using [`Self::position_matches`]
+    pub async fn is_at(&mut self, id: u8, target: f32, tolerance: f32) -> Result<bool> {
+        let position = self.read_position_degrees(id).await?;
+        Ok(self.position_matches(id, position, target, tolerance))
+    }
+
+    /// Polls present position until it is within `tolerance` degrees of `target`
+    pub async fn wait_until_reached(
+        &mut self,
+        id: u8,
+        target: f32,
+        tolerance: f32,
+        timeout: Duration,
+    ) -> Result<()> {
+        let poll_interval = self.motion_poll_interval;
+        let wait = async {
+            loop {
+                let position = self.read_position_degrees(id).await?;
+                if self.position_matches(id, position, target, tolerance) {
+                    return Ok(());
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| DynamixelDriverError::MotionTimeout(id))?
+    }
+
+    /// Polls the moving flag until the servo reports it has stopped
+    pub async fn wait_until_stopped(&mut self, id: u8, timeout: Duration) -> Result<()> {
+        let poll_interval = self.motion_poll_interval;
+        let wait = async {
+            while self.read_moving(id).await? {
+                tokio::time::sleep(poll_interval).await;
+            }
+            Ok(())
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| DynamixelDriverError::MotionTimeout(id))?
+    }
+
+    /// Writes goal position and waits until the servo is within `tolerance` degrees of it
+    ///
+    /// Fails with [`DynamixelDriverError::MotionStalled`] if the servo stops short of the goal
+    /// and with [`DynamixelDriverError::StatusError`] if it reports overload or another alarm.
+    pub async fn move_to_and_wait(
+        &mut self,
+        id: u8,
+        degrees: f32,
+        tolerance: f32,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.write_position_degrees(id, degrees).await?;
+        let poll_interval = self.motion_poll_interval;
+        let wait = async {
+            let mut last_position = None;
+            loop {
+                let position = self.read_position_degrees(id).await?;
+                if self.position_matches(id, position, degrees, tolerance) {
+                    return Ok(());
+                }
+                // moving flag may not be raised yet right after the goal write
+                // so only treat the servo as stalled once position stopped changing
+                if !self.read_moving(id).await? && last_position == Some(position) {
+                    return Err(DynamixelDriverError::MotionStalled { id, position });
+                }
+                last_position = Some(position);
+                tokio::time::sleep(poll_interval).await;
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| DynamixelDriverError::MotionTimeout(id))?
+    }
+
+    pub async fn write_moving_speed(&mut self, id: u8, speed: u16) -> Result<()> {
+        self.write_u16(id, MOVING_SPEED, speed).await?;
+        Ok(())
+    }
+
+    /// Moves to `target` degrees at the speed needed to arrive after `duration`
+    pub async fn move_over(&mut self, id: u8, target: f32, duration: Duration) -> Result<()> {
+        let position = self.read_position_degrees(id).await?;
+        let speed = moving_speed_for(target - position, duration);
+        self.write_moving_speed(id, speed).await?;
+        self.write_position_degrees(id, target).await?;
+        Ok(())
+    }
+
+    /// Moves all servos so that they arrive at their targets at the same time
+    ///
+    /// Speeds are computed from each servo's present position and sent together with
+    /// goal positions in one sync write.
+    pub async fn move_over_group(
+        &mut self,
+        targets: Vec<SyncCommandFloat>,
+        duration: Duration,
+    ) -> Result<()> {
+        let mut moves = Vec::with_capacity(targets.len());
+        for target in targets {
+            let id = target.id();
+            let steps = self.read_position(id).await?;
+            let from = steps as f32 / self.model(id).await?.steps_per_degree();
+            let to = self.goal_to_servo_degrees(id, target.value())?;
+            let mut joint_move = JointMove::plan(id, from, to, duration);
+            joint_move.goal_position = self.degrees_to_steps(id, to).await?;
+            moves.push(joint_move);
+        }
+        self.sync_write_joint_moves(moves).await
+    }
+
+    /// Sends a prepared sync write after updating its checksum
+    ///
+    /// Prepared packets are Protocol 1.0, servos speaking 2.0 are refused and EEPROM writes
+    /// are held to the lock. Goal positions are checked against the angle limits, clamped
+    /// goals are patched into the packet.
+    pub async fn send_prepared(&mut self, packet: &mut PreparedSyncWrite) -> Result<()> {
+        for &id in packet.ids() {
+            if self.protocol_for(id).version() != 1 {
+                return Err(DynamixelDriverError::SyncProtocolMismatch(id));
+            }
+            self.check_eeprom_write(id, packet.addr()).await?;
+        }
+        if packet.addr() == GOAL_POSITION {
+            for index in 0..packet.ids().len() {
+                let goal =
+                    self.limit_goal(packet.ids()[index], GOAL_POSITION, packet.value(index))?;
+                packet.set_value(index, goal)?;
+            }
+        }
+        self.port.send(packet.instruction().clone()).await?;
+        Ok(())
+    }
+
+    pub async fn sync_write_joint_moves(&mut self, moves: Vec<JointMove>) -> Result<()> {
+        let commands: Vec<SyncCommand> = moves.into_iter().map(|command| command.into()).collect();
+        self.send_sync_write(GOAL_POSITION, 4, commands).await
+    }
+
+    /// Moves to `target` degrees by streaming intermediate goal positions following `profile`
+    ///
+    /// Intended for servos without hardware motion profiles, such as the AX series.
+    pub async fn move_profiled(
+        &mut self,
+        id: u8,
+        target: f32,
+        duration: Duration,
+        profile: VelocityProfile,
+    ) -> Result<()> {
+        let from = self.read_position_degrees(id).await?;
+        let update_interval = self.profile_update_interval;
+        let mut ticker = tokio::time::interval(update_interval);
+        for waypoint in profile.waypoints(from, target, duration, update_interval) {
+            ticker.tick().await;
+            self.write_position_degrees(id, waypoint).await?;
+        }
+        Ok(())
+    }
+
+    /// Streams the goal positions of `primitive` with one sync write per profile update interval
+    ///
+    /// Returns once a finite primitive has sent its final pose, endless ones run until
+    /// the future is dropped or a write fails.
+    #[cfg(feature = "trajectory")]
+    pub async fn run_primitive(&mut self, primitive: &dyn MotionPrimitive) -> Result<()> {
+        let update_interval = self.profile_update_interval;
+        let mut ticker = tokio::time::interval(update_interval);
+        let mut elapsed = Duration::ZERO;
+        loop {
+            let (sample_at, finished) = match primitive.duration() {
+                Some(duration) => (elapsed.min(duration), elapsed >= duration),
+                None => (elapsed, false),
+            };
+            ticker.tick().await;
+            self.sync_write_position_degrees(primitive.sample(sample_at))
+                .await?;
+            if finished {
+                return Ok(());
+            }
+            elapsed += update_interval;
+        }
+    }
+
+    /// Sync writes the current pose of `buffer` every profile update interval, switching
+    /// to a newly committed pose only at the start of a tick
+    ///
+    /// Nothing is sent before the first commit. Runs until the future is dropped or a
+    /// write fails.
+    #[cfg(feature = "trajectory")]
+    pub async fn run_pose_buffer(&mut self, buffer: &PoseBuffer) -> Result<()> {
+        let mut ticker = tokio::time::interval(self.profile_update_interval);
+        let mut pose = vec![];
+        loop {
+            ticker.tick().await;
+            if let Some(committed) = buffer.take_committed() {
+                pose = committed;
+            }
+            if !pose.is_empty() {
+                self.sync_write_position_degrees(pose.clone()).await?;
+            }
+        }
+    }
+
+    /// Reads present positions and moves smoothly to `home` joint degrees over `duration`
+    #[cfg(feature = "trajectory")]
+    pub async fn return_to_home(
+        &mut self,
+        home: Vec<SyncCommandFloat>,
+        duration: Duration,
+    ) -> Result<()> {
+        let mut present = Vec::with_capacity(home.len());
+        for goal in &home {
+            let position = self.read_position_degrees(goal.id()).await?;
+            present.push(SyncCommandFloat::new(goal.id(), position));
+        }
+        self.run_primitive(&ReturnToHome::new(&present, &home, duration))
+            .await
+    }
+
+    /// Reads the leader's present position once and sync writes it to the followers
+    #[cfg(feature = "trajectory")]
+    pub async fn mirror_step(&mut self, mirror: &Mirror) -> Result<()> {
+        let position = self.read_position_degrees(mirror.leader).await?;
+        self.sync_write_position_degrees(mirror.goals(position))
+            .await
+    }
+
+    /// Keeps the followers of `mirror` tracking their leader every profile update interval
+    ///
+    /// Runs until the future is dropped, a failed step is logged and retried on the next tick.
+    #[cfg(feature = "trajectory")]
+    pub async fn run_mirror(&mut self, mirror: &Mirror) {
+        let mut ticker = tokio::time::interval(self.profile_update_interval);
+        loop {
+            ticker.tick().await;
+            if let Err(error) = self.mirror_step(mirror).await {
+                warn!("mirroring servo {} failed: {}", mirror.leader, error);
+            }
+        }
+    }
+
+    /// Disables torque on `ids` so the robot can be moved by hand and samples their
+    /// positions every `interval` until `stop` completes
+    ///
+    /// `stop` can be anything from a timer to a Ctrl-C handler. Samples where any servo
+    /// fails to answer are skipped, frames keep the time they were taken at.
+    #[cfg(feature = "trajectory")]
+    pub async fn record_teach(
+        &mut self,
+        ids: &[u8],
+        interval: Duration,
+        stop: impl Future<Output = ()>,
+    ) -> Result<Recording> {
+        let torque_off: Vec<SyncCommand> = ids.iter().map(|&id| (id, false).into()).collect();
+        self.sync_write_torque(torque_off).await?;
+        let mut recording = Recording::new(ids.to_vec());
+        let mut ticker = tokio::time::interval(interval);
+        let mut stop = std::pin::pin!(stop);
+        let start = tokio::time::Instant::now();
+        loop {
+            let tick = std::pin::pin!(ticker.tick());
+            if let futures::future::Either::Left(_) =
+                futures::future::select(stop.as_mut(), tick).await
+            {
+                return Ok(recording);
+            }
+            let time = start.elapsed();
+            let positions: Result<Vec<f32>> = self
+                .read_positions_degrees(ids)
+                .await
+                .into_iter()
+                .map(|(_, position)| position)
+                .collect();
+            match positions {
+                Ok(positions) => recording.push(time, positions),
+                Err(error) => warn!("skipping teach sample: {}", error),
+            }
+        }
+    }
+
+    /// Enables torque, moves to the first recorded pose over `approach` and plays the
+    /// recording back with [`Self::run_primitive`]
+    #[cfg(feature = "trajectory")]
+    pub async fn replay(&mut self, recording: &Recording, approach: Duration) -> Result<()> {
+        let torque_on: Vec<SyncCommand> =
+            recording.ids.iter().map(|&id| (id, true).into()).collect();
+        self.sync_write_torque(torque_on).await?;
+        self.return_to_home(recording.first_pose(), approach)
+            .await?;
+        self.run_primitive(recording).await
+    }
+
+    /// Seeds `teleop` with present positions and joint limits of its servos
+    #[cfg(feature = "teleop")]
+    pub async fn start_teleop(&mut self, teleop: &mut teleop::Teleop) -> Result<()> {
+        let ids: Vec<u8> = teleop.ids().collect();
+        for id in ids {
+            teleop.seed(id, self.read_position_degrees(id).await?);
+            if let Some(servo) = self.servos.get(&id) {
+                teleop.set_limits(id, servo.min_degrees, servo.max_degrees);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sync writes the goals of `teleop` for the current axis values, `dt` since the last step
+    #[cfg(feature = "teleop")]
+    pub async fn teleop_step(
+        &mut self,
+        teleop: &mut teleop::Teleop,
+        axes: &[f32],
+        dt: Duration,
+    ) -> Result<()> {
+        let goals = teleop.update(axes, dt);
+        if goals.is_empty() {
+            return Ok(());
+        }
+        self.sync_write_position_degrees(goals).await
+    }
+
+    pub async fn write_compliance_margin_both(&mut self, id: u8, compliance: u8) -> Result<()> {
+        self.require(id, Capability::Compliance).await?;
+        self.write_u8(id, CW_COMPLIANCE_MARGIN, compliance).await?;
+        self.write_u8(id, CCW_COMPLIANCE_MARGIN, compliance).await?;
+        Ok(())
+    }
+
+    pub async fn write_compliance_slope_both(&mut self, id: u8, compliance: u8) -> Result<()> {
+        self.require(id, Capability::Compliance).await?;
+        self.write_u8(id, CW_COMPLIANCE_SLOPE, compliance).await?;
+        self.write_u8(id, CCW_COMPLIANCE_SLOPE, compliance).await?;
+        Ok(())
+    }
+
+    pub async fn sync_write_compliance_margin_both<T: Into<SyncCommand>>(
+        &mut self,
+        compliance: Vec<T>,
+    ) -> Result<()> {
+        let compliance: Vec<SyncCommand> = compliance
+            .into_iter()
+            .map(|command| command.into())
+            .collect();
+        self.require_all(&compliance, Capability::Compliance)
+            .await?;
+        self.send_sync_write(CW_COMPLIANCE_MARGIN, 1, compliance.clone())
+            .await?;
+        self.send_sync_write(CCW_COMPLIANCE_MARGIN, 1, compliance)
+            .await
+    }
+
+    pub async fn sync_write_compliance_slope_both<T: Into<SyncCommand>>(
+        &mut self,
+        compliance: Vec<T>,
+    ) -> Result<()> {
+        let compliance: Vec<SyncCommand> = compliance
+            .into_iter()
+            .map(|command| command.into())
+            .collect();
+        self.require_all(&compliance, Capability::Compliance)
+            .await?;
+        self.send_sync_write(CW_COMPLIANCE_SLOPE, 1, compliance.clone())
+            .await?;
+        self.send_sync_write(CCW_COMPLIANCE_SLOPE, 1, compliance)
+            .await
+    }
+
+    pub async fn sync_write_torque<T: Into<SyncCommand>>(&mut self, torque: Vec<T>) -> Result<()> {
+        let torque_commands: Vec<SyncCommand> =
+            torque.into_iter().map(|command| command.into()).collect();
+        self.send_sync_write(TORQUE_ENABLED, 1, torque_commands)
+            .await
+    }
+
+    /// Disables torque one group at a time, waiting `delay` between groups
+    ///
+    /// Relaxing legs before hips lets an articulated robot settle instead of collapsing at once.
+    /// Groups with rest positions are first moved there over their lowering duration.
+    pub async fn relax_sequenced<T: Into<RelaxGroup>>(
+        &mut self,
+        groups: Vec<T>,
+        delay: Duration,
+    ) -> Result<()> {
+        let groups: Vec<RelaxGroup> = groups.into_iter().map(|group| group.into()).collect();
+        for (index, group) in groups.into_iter().enumerate() {
+            if index > 0 {
+                tokio::time::sleep(delay).await;
+            }
+            if !group.rest_positions.is_empty() {
+                self.move_over_group(group.rest_positions, group.lowering_duration)
+                    .await?;
+                tokio::time::sleep(group.lowering_duration).await;
+            }
+            let torque: Vec<SyncCommand> = group
+                .ids
+                .iter()
+                .map(|&id| SyncCommand::from((id, false)))
+                .collect();
+            self.sync_write_torque(torque).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn write_position(&mut self, id: u8, pos: u16) -> Result<()> {
+        self.write_u16(id, GOAL_POSITION, pos).await?;
+        Ok(())
+    }
+
+    pub async fn write_position_degrees(&mut self, id: u8, pos: f32) -> Result<()> {
+        let pos = self.goal_to_servo_degrees(id, pos)?;
+        let goal_position = self.degrees_to_steps(id, pos).await?;
+        self.write_u16(id, GOAL_POSITION, goal_position).await?;
+        Ok(())
+    }
+
+    pub async fn write_position_rad(&mut self, id: u8, pos: f32) -> Result<()> {
+        self.write_angle(id, Angle::from_radians(pos)).await
+    }
+
+    /// Goal joint angle, failing with [`DynamixelDriverError::PositionOutOfRange`] for angles
+    /// the servo can't reach instead of wrapping
+    pub async fn write_angle(&mut self, id: u8, angle: Angle) -> Result<()> {
+        self.write_position_degrees(id, angle.degrees()).await
+    }
+
+    pub async fn write_position_degrees_named(&mut self, name: &str, pos: f32) -> Result<()> {
+        let id = self.joint_id(name)?;
+        self.write_position_degrees(id, pos).await
+    }
+
+    pub async fn write_position_rad_named(&mut self, name: &str, pos: f32) -> Result<()> {
+        let id = self.joint_id(name)?;
+        self.write_position_rad(id, pos).await
+    }
+
+    pub async fn sync_write_position<T: Into<SyncCommand>>(
+        &mut self,
+        positions: Vec<T>,
+    ) -> Result<()> {
+        let positions: Vec<SyncCommand> = positions
+            .into_iter()
+            .map(|command| command.into())
+            .collect();
+        self.send_sync_write(GOAL_POSITION, 2, positions).await
+    }
+
+    pub async fn sync_write_position_degrees(
+        &mut self,
+        positions: Vec<SyncCommandFloat>,
+    ) -> Result<()> {
+        let mut positions_dyn_units = Vec::with_capacity(positions.len());
+        for command in positions {
+            let pos = self.goal_to_servo_degrees(command.id(), command.value())?;
+            let goal_position = self.degrees_to_steps(command.id(), pos).await?;
+            positions_dyn_units.push(SyncCommand::new(command.id(), goal_position as u32));
+        }
+        self.send_sync_write(GOAL_POSITION, 2, positions_dyn_units)
+            .await
+    }
+
+    /// Sync writes goal positions in degrees after passing them through `limiter`
+    ///
+    /// Goals are timed with tokio's clock, seed `limiter` with
+    /// `tokio::time::Instant::now().into_std()` so paused test time applies to both.
+    pub async fn sync_write_position_degrees_limited(
+        &mut self,
+        limiter: &mut VelocityLimiter,
+        positions: Vec<SyncCommandFloat>,
+    ) -> Result<()> {
+        let now = tokio::time::Instant::now().into_std();
+        let positions = positions
+            .into_iter()
+            .map(|command| {
+                let goal = limiter.limit(command.id(), command.value(), now);
+                SyncCommandFloat::new(command.id(), goal)
+            })
+            .collect();
+        self.sync_write_position_degrees(positions).await
+    }
+
+    pub async fn sync_write_position_rad(
+        &mut self,
+        positions: Vec<SyncCommandFloat>,
+    ) -> Result<()> {
+        let positions_degrees: Vec<SyncCommandFloat> = positions
+            .into_iter()
+            .map(|command| {
+                let angle = Angle::from_radians(command.value());
+                SyncCommandFloat::new(command.id(), angle.degrees())
+            })
+            .collect();
+        self.sync_write_position_degrees(positions_degrees).await?;
+        Ok(())
+    }
+
+    /// Sends a typed batch such as [`GoalPositionBatch`] as one sync write
+    pub async fn sync_write_batch(&mut self, batch: impl Into<SyncBatch>) -> Result<()> {
+        let batch = batch.into();
+        let mut commands = Vec::with_capacity(batch.entries.len());
+        for (id, value) in batch.entries {
+            let value = match value {
+                BatchValue::Raw(value) => value,
+                BatchValue::Degrees(degrees) => {
+                    let degrees = self.goal_to_servo_degrees(id, degrees)?;
+                    self.degrees_to_steps(id, degrees).await?.into()
+                }
+            };
+            commands.push(SyncCommand::new(id, value));
+        }
+        self.send_sync_write(batch.addr, batch.data_len, commands)
+            .await
+    }
+
+    pub async fn sync_write_moving_speed<T: Into<SyncCommand>>(
+        &mut self,
+        speeds: Vec<T>,
+    ) -> Result<()> {
+        let speeds: Vec<SyncCommand> = speeds.into_iter().map(|command| command.into()).collect();
+        self.send_sync_write(MOVING_SPEED, 2, speeds).await
+    }
+
+    /// Executes mixed register writes in order, returning one result per operation
+    ///
+    /// With `registered` every write is sent as REG_WRITE and a single broadcast ACTION
+    /// makes all servos apply their writes at the same time. Failing to send the ACTION
+    /// fails the whole batch since none of the registered writes took effect.
+    pub async fn write_many(
+        &mut self,
+        ops: Vec<WriteOp>,
+        registered: bool,
+    ) -> Result<Vec<Result<()>>> {
+        let mut results = Vec::with_capacity(ops.len());
+        for op in &ops {
+            results.push(self.write_op(op, registered).await);
+        }
+        if registered {
+            // one ACTION per protocol generation that registered a write
+            let mut actions: Vec<Instruction> = vec![];
+            let mut versions = vec![];
+            for (op, _) in ops
+                .iter()
+                .zip(&results)
+                .filter(|(_, result)| result.is_ok())
+            {
+                let protocol = self.protocol_for(op.id());
+                if !versions.contains(&protocol.version()) {
+                    versions.push(protocol.version());
+                    actions.push(protocol.action(BROADCAST_ID));
+                }
+            }
+            for action in actions {
+                self.port.send(action).await?;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Applies writes all or nothing
+    ///
+    /// Current values are read first, then every write is verified by read-back. If any
+    /// write fails the values read at the start are restored on everything written so far.
+    pub async fn write_transaction(&mut self, ops: Vec<WriteOp>) -> Result<()> {
+        let mut snapshot = Vec::with_capacity(ops.len());
+        for op in &ops {
+            let value = self.read_register(op.id(), op.addr(), op.len()).await?;
+            snapshot.push(op.with_value(value));
+        }
+        for (index, op) in ops.iter().enumerate() {
+            let result = match self.write_op(op, false).await {
+                Ok(()) => {
+                    self.verify_register(op.id(), op.addr(), op.len(), op.value() as u32)
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+            if let Err(cause) = result {
+                let mut failed = vec![];
+                for original in snapshot[..=index].iter().rev() {
+                    if let Err(error) = self.write_op(original, false).await {
+                        warn!(
+                            "failed restoring address {} on servo {}: {}",
+                            original.addr(),
+                            original.id(),
+                            error
+                        );
+                        failed.push((original.id(), original.addr()));
+                    }
+                }
+                if failed.is_empty() {
+                    return Err(cause);
+                }
+                return Err(DynamixelDriverError::RollbackIncomplete {
+                    cause: Box::new(cause),
+                    failed,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_op(&mut self, op: &WriteOp, registered: bool) -> Result<()> {
+        let id = op.id();
+        let addr = self.register_address(id, op.addr())?;
+        let value = self.limit_goal(id, addr, op.value().into())? as u16;
+        self.check_eeprom_write(id, addr).await?;
+        let entry = self.journal_entry(id, addr, op.len(), value).await?;
+        let instruction = op
+            .with_address(addr)
+            .with_value(value)
+            .instruction(self.protocol_for(id), registered);
+        self.transaction(instruction).await?;
+        self.record_angle_limit_write(id, addr, value);
+        self.append_journal(entry)
+    }
+
+    pub async fn sync_write_torque_verified<T: Into<SyncCommand>>(
+        &mut self,
+        torque: Vec<T>,
+        verification: SyncVerification,
+    ) -> Result<SyncOutcome> {
+        let torque: Vec<SyncCommand> = torque.into_iter().map(|command| command.into()).collect();
+        self.sync_write_verified(TORQUE_ENABLED, 1, torque, verification)
+            .await
+    }
+
+    /// Enables torque with one sync write, then reads back the torque enable register of
+    /// every servo
+    ///
+    /// A servo only succeeds if it reports torque enabled with an empty error byte,
+    /// check [`SyncOutcome::is_success`] before starting a motion that relies on all of them.
+    pub async fn enable_torque_group(&mut self, ids: &[u8]) -> Result<SyncOutcome> {
+        let torque = ids.iter().map(|&id| SyncCommand::new(id, 1)).collect();
+        self.sync_write_verified(TORQUE_ENABLED, 1, torque, SyncVerification::ReadBack)
+            .await
+    }
+
+    pub async fn sync_write_position_verified<T: Into<SyncCommand>>(
+        &mut self,
+        positions: Vec<T>,
+        verification: SyncVerification,
+    ) -> Result<SyncOutcome> {
+        let positions: Vec<SyncCommand> = positions
+            .into_iter()
+            .map(|command| command.into())
+            .collect();
+        self.sync_write_verified(GOAL_POSITION, 2, positions, verification)
+            .await
+    }
+
+    pub async fn sync_write_moving_speed_verified<T: Into<SyncCommand>>(
+        &mut self,
+        speeds: Vec<T>,
+        verification: SyncVerification,
+    ) -> Result<SyncOutcome> {
+        let speeds: Vec<SyncCommand> = speeds.into_iter().map(|command| command.into()).collect();
+        self.sync_write_verified(MOVING_SPEED, 2, speeds, verification)
+            .await
+    }
+
+    /// Sends a sync write and, with spot checks enabled, reads the register back
+    /// from the next servo of the batch in turn
+    async fn send_sync_write(
+        &mut self,
+        addr: u8,
+        data_len: u8,
+        commands: Vec<SyncCommand>,
+    ) -> Result<()> {
+        let table_addr = self.sync_address(addr, &commands)?;
+        let commands = self.limit_goals(table_addr, commands)?;
+        let message = self
+            .protocol
+            .sync_write(table_addr.into(), data_len.into(), &commands);
+        self.port.send(message).await?;
+        if !self.sync_spot_check || commands.is_empty() {
+            return Ok(());
+        }
+        let command = commands[self.spot_check_index % commands.len()];
+        self.spot_check_index = self.spot_check_index.wrapping_add(1);
+        // wider blocks start with a u16 register, such as goal position followed by speed
+        let (len, expected) = match data_len {
+            1 => (1, command.value()),
+            _ => (2, command.value() & 0xFFFF),
+        };
+        self.spot_checks += 1;
+        if let Err(error) = self
+            .verify_register(command.id(), addr, len, expected)
+            .await
+        {
+            self.spot_check_failures += 1;
+            warn!(
+                "sync write spot check of servo {} failed: {}",
+                command.id(),
+                error
+            );
+        }
+        Ok(())
+    }
+
+    async fn sync_write_verified(
+        &mut self,
+        addr: u8,
+        data_len: u8,
+        commands: Vec<SyncCommand>,
+        verification: SyncVerification,
+    ) -> Result<SyncOutcome> {
+        let table_addr = self.sync_address(addr, &commands)?;
+        let commands = self.limit_goals(table_addr, commands)?;
+        let message = self
+            .protocol
+            .sync_write(table_addr.into(), data_len.into(), &commands);
+        self.port.send(message).await?;
+        let mut outcome = SyncOutcome::default();
+        for command in commands {
+            let result = match verification {
+                SyncVerification::Ping => self.ping(command.id()).await,
+                SyncVerification::ReadBack => {
+                    self.verify_register(command.id(), addr, data_len, command.value())
+                        .await
+                }
+            };
+            outcome.push(command.id(), result);
+        }
+        Ok(outcome)
+    }
+
+    async fn verify_register(
+        &mut self,
+        id: u8,
+        addr: u8,
+        data_len: u8,
+        expected: u32,
+    ) -> Result<()> {
+        let actual = if data_len == 1 {
+            self.read_u8(id, addr).await? as u32
+        } else {
+            self.read_u16(id, addr).await? as u32
+        };
+        if actual != expected {
+            return Err(DynamixelDriverError::VerificationError {
+                id,
+                addr,
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// Applies register profiles servo by servo, reading every register back after writing it
+    ///
+    /// Stops at the first register that doesn't match and returns a
+    /// [`DynamixelDriverError::VerificationError`] naming the servo and address.
+    /// With `rollback` the original values are restored on every servo touched so far.
+    pub async fn apply_config_to_bus<F>(
+        &mut self,
+        configs: &[(u8, ServoConfig)],
+        rollback: bool,
+        mut progress: F,
+    ) -> Result<()>
+    where
+        F: FnMut(ConfigProgress),
+    {
+        // original values of every servo touched so far
+        let mut touched: Vec<(u8, Vec<WriteOp>)> = vec![];
+        for (index, (id, config)) in configs.iter().enumerate() {
+            let id = *id;
+            progress(ConfigProgress::Applying {
+                id,
+                index,
+                total: configs.len(),
+            });
+            let result = self
+                .apply_servo_config(id, &config.write_ops(id), &mut touched)
+                .await;
+            if let Err(error) = result {
+                if rollback {
+                    self.rollback_config(touched, &mut progress).await;
+                }
+                return Err(error);
+            }
+            progress(ConfigProgress::Applied { id });
+        }
+        Ok(())
+    }
+
+    async fn apply_servo_config(
+        &mut self,
+        id: u8,
+        ops: &[WriteOp],
+        touched: &mut Vec<(u8, Vec<WriteOp>)>,
+    ) -> Result<()> {
+        let mut original = Vec::with_capacity(ops.len());
+        for op in ops {
+            let value = self.read_register(id, op.addr(), op.len()).await?;
+            original.push(op.with_value(value));
+        }
+        touched.push((id, original));
+        for op in ops {
+            self.write_op(op, false).await?;
+            self.verify_register(id, op.addr(), op.len(), op.value() as u32)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn rollback_config<F>(&mut self, touched: Vec<(u8, Vec<WriteOp>)>, progress: &mut F)
+    where
+        F: FnMut(ConfigProgress),
+    {
+        for (id, original) in touched.into_iter().rev() {
+            let mut restored = true;
+            for op in original {
+                if let Err(error) = self.write_op(&op, false).await {
+                    warn!(
+                        "failed restoring address {} on servo {}: {}",
+                        op.addr(),
+                        id,
+                        error
+                    );
+                    restored = false;
+                }
+            }
+            if restored {
+                progress(ConfigProgress::RolledBack { id });
+            } else {
+                progress(ConfigProgress::RollbackFailed { id });
+            }
+        }
+    }
+
+    async fn read_register(&mut self, id: u8, addr: u8, len: u8) -> Result<u16> {
+        if len == 1 {
+            Ok(self.read_u8(id, addr).await? as u16)
+        } else {
+            self.read_u16(id, addr).await
+        }
+    }
+
+    pub async fn write_pid_gains(&mut self, id: u8, gains: PidGains) -> Result<()> {
+        self.require_ax_table(id, D_GAIN)?;
+        self.require(id, Capability::PidGains).await?;
+        let msg = self
+            .protocol_for(id)
+            .write(id, D_GAIN.into(), &[gains.d, gains.i, gains.p]);
+        self.transaction(msg).await?;
+        Ok(())
+    }
+
+    pub async fn read_pid_gains(&mut self, id: u8) -> Result<PidGains> {
+        self.require_ax_table(id, D_GAIN)?;
+        self.require(id, Capability::PidGains).await?;
+        let command = self.protocol_for(id).read(id, D_GAIN.into(), 3);
+        let status = self.transaction(command).await?;
+        let params = status.params();
+        Ok(PidGains {
+            d: params[0],
+            i: params[1],
+            p: params[2],
+        })
+    }
+
+    pub async fn write_torque_control_mode(&mut self, id: u8, enabled: bool) -> Result<()> {
+        self.require(id, Capability::TorqueControl).await?;
+        self.write_u8(id, TORQUE_CONTROL_MODE_ENABLE, enabled as u8)
+            .await?;
+        Ok(())
+    }
+
+    /// Torque as a fraction of maximum in range -1.0 to 1.0.
+    /// Positive values turn CCW and negative values CW.
+    /// Only has effect on MX-64/106 servos with torque control mode enabled.
+    /// NaN and infinite torques are rejected instead of commanding full torque.
+    pub async fn write_goal_torque(&mut self, id: u8, torque: f32) -> Result<()> {
+        let raw = goal_torque_to_raw(torque).ok_or(DynamixelDriverError::InvalidTorque(torque))?;
+        self.require(id, Capability::TorqueControl).await?;
+        self.write_u16(id, GOAL_TORQUE, raw).await?;
+        Ok(())
+    }
+
+    // X series registers have no equivalent on Protocol 1.0 servos
+    async fn read_x_register<const N: usize>(&mut self, id: u8, addr: u8) -> Result<[u8; N]> {
+        self.require_protocol2(id)?;
+        let command = self.protocol_for(id).read(id, addr.into(), N as u16);
+        self.transaction(command).await?.read_bytes()
+    }
+
+    async fn write_x_register(&mut self, id: u8, addr: u8, data: &[u8]) -> Result<()> {
+        self.require_protocol2(id)?;
+        self.check_eeprom_write(id, addr).await?;
+        let journal = self.journal_entries(id, addr.into(), data).await?;
+        let command = self.protocol_for(id).write(id, addr.into(), data);
+        self.transaction(command).await?;
+        for entry in journal {
+            self.append_journal(entry)?;
+        }
+        Ok(())
+    }
+
+    // the sync write goes out in the protocol of the driver, which has to be 2.0 as well
+    async fn sync_write_x_register(
+        &mut self,
+        addr: u8,
+        data_len: u8,
+        commands: Vec<SyncCommand>,
+    ) -> Result<()> {
+        for command in &commands {
+            let id = command.id();
+            self.require_protocol2(id)?;
+            if self.protocol.version() != 2 {
+                return Err(DynamixelDriverError::SyncProtocolMismatch(id));
+            }
+            self.check_eeprom_write(id, addr).await?;
+        }
+        let mut journal = vec![];
+        for command in &commands {
+            let data = &command.value().to_le_bytes()[..data_len.into()];
+            journal.extend(
+                self.journal_entries(command.id(), addr.into(), data)
+                    .await?,
+            );
+        }
+        let message = self
+            .protocol
+            .sync_write(addr.into(), data_len.into(), &commands);
+        self.port.send(message).await?;
+        for entry in journal {
+            self.append_journal(entry)?;
+        }
+        Ok(())
+    }
+
+    /// Current drawn by an X series servo in mA, negative while driving CW
+    pub async fn read_present_current(&mut self, id: u8) -> Result<f32> {
+        let raw = self.read_x_register(id, PROTOCOL2_PRESENT_CURRENT).await?;
+        Ok(i16::from_le_bytes(raw) as f32 * PROTOCOL2_CURRENT_UNIT_MA)
+    }
+
+    /// Reads present current of several servos in mA, reporting failures per id
+    pub async fn read_present_currents(&mut self, ids: &[u8]) -> Vec<(u8, Result<f32>)> {
+        let mut currents = Vec::with_capacity(ids.len());
+        for &id in ids {
+            currents.push((id, self.read_present_current(id).await));
+        }
+        currents
+    }
+
+    pub async fn read_current_limit(&mut self, id: u8) -> Result<f32> {
+        let raw = self.read_x_register(id, PROTOCOL2_CURRENT_LIMIT).await?;
+        Ok(u16::from_le_bytes(raw) as f32 * PROTOCOL2_CURRENT_UNIT_MA)
+    }
+
+    /// Limits the current of an X series servo in mA, the limit is stored in EEPROM
+    pub async fn write_current_limit(&mut self, id: u8, milliamps: f32) -> Result<()> {
+        let raw =
+            current_to_raw(milliamps).ok_or(DynamixelDriverError::InvalidCurrent(milliamps))?;
+        self.write_x_register(id, PROTOCOL2_CURRENT_LIMIT, &raw.to_le_bytes())
+            .await
+    }
+
+    pub async fn sync_write_current_limit(&mut self, limits: Vec<SyncCommandFloat>) -> Result<()> {
+        let mut commands = Vec::with_capacity(limits.len());
+        for limit in limits {
+            let raw = current_to_raw(limit.value())
+                .ok_or(DynamixelDriverError::InvalidCurrent(limit.value()))?;
+            commands.push(SyncCommand::new(limit.id(), raw.into()));
+        }
+        self.sync_write_x_register(PROTOCOL2_CURRENT_LIMIT, 2, commands)
+            .await
+    }
+
+    /// Raw PWM an X series servo applies in PWM control mode, 885 is full duty
+    pub async fn read_goal_pwm(&mut self, id: u8) -> Result<i16> {
+        let raw = self.read_x_register(id, PROTOCOL2_GOAL_PWM).await?;
+        Ok(i16::from_le_bytes(raw))
+    }
+
+    /// Writes raw goal PWM, the servo refuses values beyond its PWM limit
+    pub async fn write_goal_pwm(&mut self, id: u8, pwm: i16) -> Result<()> {
+        self.write_x_register(id, PROTOCOL2_GOAL_PWM, &pwm.to_le_bytes())
+            .await
+    }
+
+    pub async fn read_pwm_limit(&mut self, id: u8) -> Result<u16> {
+        let raw = self.read_x_register(id, PROTOCOL2_PWM_LIMIT).await?;
+        Ok(u16::from_le_bytes(raw))
+    }
+
+    /// Drives an X series servo in PWM control mode at `duty` from -1.0 to 1.0 of its
+    /// PWM limit
+    pub async fn write_pwm(&mut self, id: u8, duty: f32) -> Result<()> {
+        // NaN fails the range check
+        if !(-1.0..=1.0).contains(&duty) {
+            return Err(DynamixelDriverError::InvalidPwm(duty));
+        }
+        let limit = self.read_pwm_limit(id).await?;
+        let pwm = (duty * limit.min(i16::MAX as u16) as f32).round() as i16;
+        self.write_goal_pwm(id, pwm).await
+    }
+
+    /// Velocity in rpm that position moves of an X series servo are limited to, 0 for
+    /// no limit
+    pub async fn read_profile_velocity(&mut self, id: u8) -> Result<f32> {
+        let raw = self.read_x_register(id, PROTOCOL2_PROFILE_VELOCITY).await?;
+        Ok(u32::from_le_bytes(raw) as f32 * PROTOCOL2_VELOCITY_UNIT_RPM)
+    }
+
+    pub async fn write_profile_velocity(&mut self, id: u8, rpm: f32) -> Result<()> {
+        let raw = profile_to_raw(rpm, PROTOCOL2_VELOCITY_UNIT_RPM)?;
+        self.write_x_register(id, PROTOCOL2_PROFILE_VELOCITY, &raw.to_le_bytes())
+            .await
+    }
+
+    /// Acceleration in rev/min² of position moves of an X series servo, 0 for no limit
+    pub async fn read_profile_acceleration(&mut self, id: u8) -> Result<f32> {
+        let raw = self
+            .read_x_register(id, PROTOCOL2_PROFILE_ACCELERATION)
+            .await?;
+        Ok(u32::from_le_bytes(raw) as f32 * PROTOCOL2_ACCELERATION_UNIT)
+    }
+
+    pub async fn write_profile_acceleration(&mut self, id: u8, acceleration: f32) -> Result<()> {
+        let raw = profile_to_raw(acceleration, PROTOCOL2_ACCELERATION_UNIT)?;
+        self.write_x_register(id, PROTOCOL2_PROFILE_ACCELERATION, &raw.to_le_bytes())
+            .await
+    }
+
+    /// Moves an X series servo to `goal_position` along a trapezoidal profile of
+    /// `velocity` rpm and `acceleration` rev/min², all three written in one instruction
+    ///
+    /// The goal position is checked against the angle limits like [`Self::write_position`].
+    pub async fn move_with_profile(
+        &mut self,
+        id: u8,
+        goal_position: u32,
+        velocity: f32,
+        acceleration: f32,
+    ) -> Result<()> {
+        let acceleration = profile_to_raw(acceleration, PROTOCOL2_ACCELERATION_UNIT)?;
+        let velocity = profile_to_raw(velocity, PROTOCOL2_VELOCITY_UNIT_RPM)?;
+        let goal_position = self.limit_goal(id, PROTOCOL2_GOAL_POSITION, goal_position)?;
+        let data: Vec<u8> = [acceleration, velocity, goal_position]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        self.write_x_register(id, PROTOCOL2_PROFILE_ACCELERATION, &data)
+            .await
+    }
+
+    pub async fn read_operating_mode(&mut self, id: u8) -> Result<OperatingMode> {
+        let [mode] = self.read_x_register(id, PROTOCOL2_OPERATING_MODE).await?;
+        OperatingMode::from_register(mode).ok_or(DynamixelDriverError::DecodingError(
+            "unknown operating mode",
+        ))
+    }
+
+    /// Switches the control mode of an X series servo, refused while its torque is
+    /// enabled as the servo ignores the write then
+    pub async fn write_operating_mode(&mut self, id: u8, mode: OperatingMode) -> Result<()> {
+        self.require_torque_disabled(id).await?;
+        self.write_x_register(id, PROTOCOL2_OPERATING_MODE, &[mode.register_value()])
+            .await
+    }
+
+    // X series servos refuse EEPROM writes while their torque is enabled
+    async fn require_torque_disabled(&mut self, id: u8) -> Result<()> {
+        self.require_protocol2(id)?;
+        if self.read_u8(id, TORQUE_ENABLED).await? != 0 {
+            return Err(DynamixelDriverError::TorqueEnabled(id));
+        }
+        Ok(())
+    }
+
+    /// Offset in position steps an X series servo adds to its present position
+    pub async fn read_homing_offset(&mut self, id: u8) -> Result<i32> {
+        let raw = self.read_x_register(id, PROTOCOL2_HOMING_OFFSET).await?;
+        Ok(i32::from_le_bytes(raw))
+    }
+
+    /// Moves the zero of an X series servo by `steps` in firmware, refused while its
+    /// torque is enabled
+    pub async fn write_homing_offset(&mut self, id: u8, steps: i32) -> Result<()> {
+        if !(-PROTOCOL2_MAX_HOMING_OFFSET..=PROTOCOL2_MAX_HOMING_OFFSET).contains(&steps) {
+            return Err(DynamixelDriverError::HomingOffsetOutOfRange(steps));
+        }
+        self.require_torque_disabled(id).await?;
+        self.write_x_register(id, PROTOCOL2_HOMING_OFFSET, &steps.to_le_bytes())
+            .await
+    }
+
+    pub async fn read_drive_mode(&mut self, id: u8) -> Result<DriveMode> {
+        let [mode] = self.read_x_register(id, PROTOCOL2_DRIVE_MODE).await?;
+        Ok(DriveMode::from_register(mode))
+    }
+
+    /// Sets direction and profile type of an X series servo, refused while its torque is
+    /// enabled
+    pub async fn write_drive_mode(&mut self, id: u8, mode: DriveMode) -> Result<()> {
+        self.require_torque_disabled(id).await?;
+        self.write_x_register(id, PROTOCOL2_DRIVE_MODE, &[mode.register_value()])
+            .await
+    }
+
+    /// Secondary id an X series servo listens on next to its own, None if disabled
+    pub async fn read_secondary_id(&mut self, id: u8) -> Result<Option<u8>> {
+        let [secondary] = self.read_x_register(id, PROTOCOL2_SECONDARY_ID).await?;
+        // ids above 252 disable the secondary id
+        let secondary = (secondary < 253).then_some(secondary);
+        self.record_secondary_id(id, secondary);
+        Ok(secondary)
+    }
+
+    /// Sets or with None clears the secondary id of an X series servo, servos sharing one
+    /// can be written together with [`Self::write_secondary_group`]
+    pub async fn write_secondary_id(&mut self, id: u8, secondary: Option<u8>) -> Result<()> {
+        let value = match secondary {
+            Some(secondary) if secondary > 252 => {
+                return Err(DynamixelDriverError::ReservedId(secondary))
+            }
+            Some(secondary) => secondary,
+            None => 255,
+        };
+        self.write_x_register(id, PROTOCOL2_SECONDARY_ID, &[value])
+            .await?;
+        self.record_secondary_id(id, secondary);
+        Ok(())
+    }
+
+    fn record_secondary_id(&mut self, id: u8, secondary: Option<u8>) {
+        match secondary {
+            Some(secondary) => self.secondary_ids.insert(id, secondary),
+            None => self.secondary_ids.remove(&id),
+        };
+    }
+
+    /// Servos known to listen on `secondary`, from earlier secondary id reads and writes
+    pub fn secondary_group(&self, secondary: u8) -> Vec<u8> {
+        let mut ids: Vec<u8> = self
+            .secondary_ids
+            .iter()
+            .filter(|(_, &group)| group == secondary)
+            .map(|(&id, _)| id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Writes `data` to every servo listening on secondary id `secondary` with a single
+    /// Protocol 2.0 instruction, the servos don't answer it
+    ///
+    /// EEPROM lock, journal and goal position limits apply for every known member of the
+    /// group, see [`Self::secondary_group`].
+    pub async fn write_secondary_group(
+        &mut self,
+        secondary: u8,
+        addr: u16,
+        data: &[u8],
+    ) -> Result<()> {
+        let members = self.secondary_group(secondary);
+        let mut data = data.to_vec();
+        if addr == PROTOCOL2_GOAL_POSITION.into() && data.len() == 4 {
+            let mut goal = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            // each member narrows the goal further
+            for &id in &members {
+                goal = self.limit_goal(id, PROTOCOL2_GOAL_POSITION, goal)?;
+            }
+            data = goal.to_le_bytes().to_vec();
+        }
+        let mut journal = vec![];
+        if let Ok(addr) = u8::try_from(addr) {
+            if addr < PROTOCOL2_EEPROM_END && !self.eeprom_writes_allowed {
+                return Err(DynamixelDriverError::EepromWriteLocked(addr));
+            }
+            for &id in &members {
+                self.check_eeprom_write(id, addr).await?;
+            }
+        }
+        for &id in &members {
+            journal.extend(self.journal_entries(id, addr, &data).await?);
+        }
+        self.port
+            .send(Protocol2.write(secondary, addr, &data))
+            .await?;
+        for entry in journal {
+            self.append_journal(entry)?;
+        }
+        Ok(())
+    }
+
+    /// Maps `registers`, given as address and length, one after another onto the indirect
+    /// data registers of an X series servo so they can be accessed in a single read or
+    /// write with [`Self::read_indirect`] and [`Self::write_indirect`]
+    ///
+    /// The servo only accepts new indirect addresses while its torque is disabled. Mapping
+    /// EEPROM registers is held to the EEPROM lock like writing them.
+    pub async fn map_indirect(&mut self, id: u8, registers: &[(u16, u8)]) -> Result<()> {
+        self.require_protocol2(id)?;
+        let mut addresses = vec![];
+        for &(addr, len) in registers {
+            if let Ok(addr) = u8::try_from(addr) {
+                self.check_eeprom_write(id, addr).await?;
+            }
+            for offset in 0..u16::from(len) {
+                addresses.push(addr.checked_add(offset).ok_or(
+                    DynamixelDriverError::AddressOverflow {
+                        id,
+                        addr,
+                        len: len.into(),
+                    },
+                )?);
+            }
+        }
+        if addresses.len() > PROTOCOL2_INDIRECT_LEN {
+            return Err(DynamixelDriverError::TooManyIndirectBytes(addresses.len()));
+        }
+        if self.read_u8(id, TORQUE_ENABLED).await? != 0 {
+            return Err(DynamixelDriverError::TorqueEnabled(id));
+        }
+        let data: Vec<u8> = addresses
+            .iter()
+            .flat_map(|addr| addr.to_le_bytes())
+            .collect();
+        self.write_x_register(id, PROTOCOL2_INDIRECT_ADDRESS, &data)
+            .await
+    }
+
+    /// Reads the first `len` bytes of the registers mapped with [`Self::map_indirect`]
+    pub async fn read_indirect(&mut self, id: u8, len: u8) -> Result<Vec<u8>> {
+        if usize::from(len) > PROTOCOL2_INDIRECT_LEN {
+            return Err(DynamixelDriverError::TooManyIndirectBytes(len.into()));
+        }
+        self.require_protocol2(id)?;
+        let command = self
+            .protocol_for(id)
+            .read(id, PROTOCOL2_INDIRECT_DATA.into(), len.into());
+        let status = self.transaction(command).await?;
+        Ok(status.params().to_vec())
+    }
+
+    /// Writes the registers mapped with [`Self::map_indirect`] from their first byte on
+    pub async fn write_indirect(&mut self, id: u8, data: &[u8]) -> Result<()> {
+        if data.len() > PROTOCOL2_INDIRECT_LEN {
+            return Err(DynamixelDriverError::TooManyIndirectBytes(data.len()));
+        }
+        self.write_x_register(id, PROTOCOL2_INDIRECT_DATA, data)
+            .await
+    }
+
+    /// Writes the first `data_len` bytes of the indirect data registers of several servos
+    /// with one sync write
+    pub async fn sync_write_indirect<T: Into<SyncCommand>>(
+        &mut self,
+        data_len: u8,
+        values: Vec<T>,
+    ) -> Result<()> {
+        if !matches!(data_len, 1 | 2 | 4) {
+            return Err(DynamixelDriverError::InvalidSyncDataLength(data_len));
+        }
+        let values = values.into_iter().map(|value| value.into()).collect();
+        self.sync_write_x_register(PROTOCOL2_INDIRECT_DATA, data_len, values)
+            .await
+    }
+
+    pub async fn read_max_torque(&mut self, id: u8) -> Result<f32> {
+        let max_torque = self.read_u16(id, MAX_TORQUE).await? as f32;
+        let max_torque_percentage = max_torque / 2013.0;
+        Ok(max_torque_percentage)
+    }
+
+    /// Present load as a fraction of maximum torque in range -1.0 to 1.0, positive CCW
+    pub async fn read_load(&mut self, id: u8) -> Result<f32> {
+        Ok(signed_from_raw(self.read_u16(id, PRESENT_LOAD).await?) / 1023.0)
+    }
+
+    /// Torque limit as a fraction of maximum torque
+    pub async fn read_torque_limit(&mut self, id: u8) -> Result<f32> {
+        Ok(self.read_u16(id, TORQUE_LIMIT).await? as f32 / 1023.0)
+    }
+
+    /// Approximate output torque in N·m, positive CCW
+    ///
+    /// Scales present load by torque limit and the stall torque of the model declared
+    /// with [`Self::configure_servo`], or the model read from the servo otherwise.
+    /// Load is not a calibrated measurement so treat this as a rough estimate.
+    pub async fn read_effort(&mut self, id: u8) -> Result<f32> {
+        let model = match self.known_model(id) {
+            Some(model) => model,
+            None => self.read_model(id).await?,
+        };
+        let stall_torque = model
+            .stall_torque()
+            .ok_or(DynamixelDriverError::UnknownStallTorque(model))?;
+        let load = self.read_load(id).await?;
+        let torque_limit = self.read_torque_limit(id).await?;
+        Ok(load * torque_limit * stall_torque)
+    }
+
+    pub async fn search_all(&mut self) -> Result<Vec<u8>> {
+        let mut ids = vec![];
+        for i in 1..254 {
+            let found = if self.detect_protocols {
+                self.detect_protocol(i).await
+            } else {
+                self.ping(i).await.is_ok()
+            };
+            if found {
+                ids.push(i);
+            }
+        }
+        events::emit(&self.events, DriverEvent::Discovered(ids.clone()));
+        Ok(ids)
+    }
+
+    async fn detect_protocol(&mut self, id: u8) -> bool {
+        let protocols: [Arc<dyn Protocol>; 2] = [Arc::new(Protocol1), Arc::new(Protocol2)];
+        for protocol in protocols {
+            if self.transaction(protocol.ping(id)).await.is_ok() {
+                self.servo_protocols.insert(id, protocol);
+                return true;
+            }
+        }
+        self.servo_protocols.remove(&id);
+        false
+    }
+
+    /// Finds servos like [`Self::search_all`], waiting only `initial_timeout` for each
+    /// missing id instead of the full receive timeout
+    ///
+    /// The wait grows to twice the slowest answer seen, answers that arrive after their
+    /// ping timed out are still counted. Servos found are pinged again with the regular
+    /// timeout, so a too short `initial_timeout` costs missed servos but never phantom ones.
+    pub async fn search_fast(&mut self, initial_timeout: Duration) -> Result<Vec<u8>> {
+        let mut timeout = AdaptiveTimeout::new(initial_timeout);
+        let mut candidates = vec![];
+        for id in 1..BROADCAST_ID {
+            self.port.send(self.protocol.ping(id)).await?;
+            let start = Instant::now();
+            match tokio::time::timeout(timeout.get(), self.port.receive()).await {
+                Ok(Ok(status)) if status.id() == id => {
+                    timeout.record_answer(start.elapsed());
+                    candidates.push(id);
+                }
+                Ok(Ok(status)) => {
+                    timeout.record_late_answer();
+                    candidates.push(status.id());
+                }
+                Ok(Err(DynamixelDriverError::IoError(error))) => return Err(error.into()),
+                Ok(Err(_)) | Err(_) => (),
+            }
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+        let mut ids = vec![];
+        for id in candidates {
+            if self.ping(id).await.is_ok() {
+                ids.push(id);
+            }
+        }
+        events::emit(&self.events, DriverEvent::Discovered(ids.clone()));
+        Ok(ids)
+    }
+
+    /// Pings every id and records model and firmware version of the servos that answer
+    pub async fn scan_inventory(&mut self) -> Result<Inventory> {
+        let mut inventory = Inventory::default();
+        for id in self.search_all().await? {
+            let firmware = match self.protocol_for(id).version() {
+                2 => PROTOCOL2_FIRMWARE_VERSION,
+                _ => FIRMWARE_VERSION,
+            } as usize;
+            // model number is followed by the firmware version
+            let len = firmware + 1;
+            let command = self
+                .protocol_for(id)
+                .read(id, MODEL_NUMBER.into(), len as u16);
+            let status = self.transaction(command).await?;
+            let params = status.params();
+            if params.len() != len {
+                return Err(DynamixelDriverError::ResponseLengthMismatch {
+                    id,
+                    expected: len,
+                    actual: params.len(),
+                });
+            }
+            let model = ServoModel::from_model_number(u16::from_le_bytes([params[0], params[1]]));
+            self.models.insert(id, model);
+            inventory.servos.push(InventoryEntry {
+                id,
+                model,
+                firmware: Some(params[firmware]),
+            });
+        }
+        Ok(inventory)
+    }
+
+    /// Scans the bus and compares it with `expected`, check the result before moving the robot
+    pub async fn check_inventory(&mut self, expected: &Inventory) -> Result<InventoryDiff> {
+        Ok(expected.compare(&self.scan_inventory().await?))
+    }
+
+    /// Alternates reads of `payload_size` bytes with writes that put back unchanged RAM
+    /// registers for `duration`, reporting error rate and latency at the current baud rate
+    ///
+    /// Retries are bypassed so every failure is counted. Writes are limited to the
+    /// writable registers from torque enable to torque limit, the servo should be idle.
+    pub async fn stress_test(
+        &mut self,
+        id: u8,
+        duration: Duration,
+        payload_size: u8,
+    ) -> Result<StressReport> {
+        self.require_ax_table(id, RAM_START)?;
+        // whole control table of AX servos
+        let read_len = payload_size.clamp(1, RAM_START + RAM_LEN);
+        let write_len = payload_size.clamp(1, TORQUE_LIMIT + 2 - RAM_START);
+        let snapshot = self
+            .transaction(
+                self.protocol_for(id)
+                    .read(id, RAM_START.into(), write_len.into()),
+            )
+            .await?
+            .params()
+            .to_vec();
+        let mut report = StressReport::default();
+        let start = tokio::time::Instant::now();
+        let mut write = false;
+        while start.elapsed() < duration {
+            let instruction = if write {
+                self.protocol_for(id).write(id, RAM_START.into(), &snapshot)
+            } else {
+                self.protocol_for(id)
+                    .read(id, MODEL_NUMBER.into(), read_len.into())
+            };
+            write = !write;
+            let sent = tokio::time::Instant::now();
+            match self.send_and_receive(instruction).await {
+                Ok(_) => report.record(Ok(sent.elapsed())),
+                Err(DynamixelDriverError::IoError(error)) => return Err(error.into()),
+                Err(error) => {
+                    report.record(Err(&error));
+                    self.port.clear_io_buffers().await?;
+                }
+            }
+        }
+        Ok(report.finish())
+    }
+
+    pub async fn clear_io_buffers(&mut self) -> Result<()> {
+        self.port.clear_io_buffers().await?;
+        Ok(())
+    }
+}
+
+fn check_id_change(id: u8, new_id: u8) -> Result<()> {
+    // writing to the broadcast id would rename every servo on the bus
+    if id >= BROADCAST_ID {
+        return Err(DynamixelDriverError::ReservedId(id));
+    }
+    if new_id >= BROADCAST_ID {
+        return Err(DynamixelDriverError::ReservedId(new_id));
+    }
+    Ok(())
+}
+
+// speed and load use 10 bits of magnitude with bit 10 selecting CW direction
+fn signed_from_raw(raw: u16) -> f32 {
+    let magnitude = (raw & 0x3FF) as f32;
+    if raw & 0x400 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+// speed units are about 0.111 rpm
+fn speed_from_raw(raw: u16) -> f32 {
+    signed_from_raw(raw) * 0.111
+}
+
+fn goal_torque_to_raw(torque: f32) -> Option<u16> {
+    if !torque.is_finite() {
+        return None;
+    }
+    let magnitude = (torque.abs().min(1.0) * 1023.0).round() as u16;
+    if torque < 0.0 {
+        // bit 10 selects CW direction
+        Some(magnitude | 1024)
+    } else {
+        Some(magnitude)
+    }
+}
+
+fn current_to_raw(milliamps: f32) -> Option<u16> {
+    let raw = (milliamps / PROTOCOL2_CURRENT_UNIT_MA).round();
+    // NaN fails both comparisons
+    (milliamps >= 0.0 && raw <= u16::MAX as f32).then_some(raw as u16)
+}
+
+fn profile_to_raw(value: f32, unit: f32) -> Result<u32> {
+    let raw = (value / unit).round();
+    match value >= 0.0 && raw <= u32::MAX as f32 {
+        true => Ok(raw as u32),
+        false => Err(DynamixelDriverError::InvalidProfile(value)),
+    }
+}
+
+impl Drop for DynamixelDriver {
+    fn drop(&mut self) {
+        if !self.closed {
+            if self.shutdown_sequence.is_some() {
+                warn!("driver dropped without close, shutdown sequence was skipped");
+            } else {
+                warn!("driver dropped without close, pending writes may be lost");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use instructions::Instruction;
+    use std::sync::{Arc, Mutex};
+    use test_utils::{assert_sync_write, decode_traffic, SentInstruction};
+
+    struct MockFramedDriver {
+        written_data: Arc<Mutex<Vec<Vec<u8>>>>,
+        mock_read_data: Vec<Result<Status>>,
+    }
+
+    impl MockFramedDriver {
+        fn new(mock_read_data: Vec<Status>, written_data: Arc<Mutex<Vec<Vec<u8>>>>) -> Self {
+            MockFramedDriver::with_results(
+                mock_read_data.into_iter().map(Ok).collect(),
+                written_data,
+            )
+        }
+
+        fn with_results(
+            mock_read_data: Vec<Result<Status>>,
+            written_data: Arc<Mutex<Vec<Vec<u8>>>>,
+        ) -> Self {
+            MockFramedDriver {
+                written_data,
+                mock_read_data,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl FramedDriver for MockFramedDriver {
+        async fn send(&mut self, message: Instruction) -> Result<()> {
+            let payload = message.serialize();
+            self.written_data.lock().unwrap().push(payload);
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<Status> {
+            if self.mock_read_data.is_empty() {
+                return Err(DynamixelDriverError::Timeout);
+            }
+            self.mock_read_data.remove(0)
+        }
+
+        async fn clear_io_buffers(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_write_compliance_writes() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let commands = vec![(1_u8, 0_u32), (2, 0), (3, 0), (4, 0)];
+        driver
+            .sync_write_compliance_slope_both(commands)
+            .await
+            .unwrap();
+
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![255, 255, 254, 12, 131, 28, 1, 1, 0, 2, 0, 3, 0, 4, 0, 75]
+        );
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![255, 255, 254, 12, 131, 29, 1, 1, 0, 2, 0, 3, 0, 4, 0, 74]
+        );
+        assert!(writing_buffer_guard.is_empty());
+    }
+
+    #[tokio::test]
+    async fn sync_write_positions_writes() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let commands = vec![(1_u8, 0_u32), (2, 0), (3, 0), (4, 0)];
+        driver.sync_write_position(commands).await.unwrap();
+
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![255, 255, 254, 16, 131, 30, 2, 1, 0, 0, 2, 0, 0, 3, 0, 0, 4, 0, 0, 68]
+        );
+        assert!(writing_buffer_guard.is_empty());
+    }
+
+    #[tokio::test]
+    async fn write_positions_writes() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![Status::new(1, vec![])], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.write_position(1, 150).await.unwrap();
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![255, 255, 1, 5, 3, 30, 150, 0, 66]
+        );
+        assert!(writing_buffer_guard.is_empty());
+    }
+
+    #[tokio::test]
+    async fn sync_write_torque_writes() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![Status::new(1, vec![])], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let input = vec![(1, 0), (2, 0), (3, 1), (4, 1)];
+        driver.sync_write_torque(input).await.unwrap();
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_sync_write(
+            &writing_buffer_guard,
+            TORQUE_ENABLED,
+            &[(1, 0), (2, 0), (3, 1), (4, 1)],
+        );
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![255, 255, 254, 12, 131, 24, 1, 1, 0, 2, 0, 3, 1, 4, 1, 77]
+        );
+        assert!(writing_buffer_guard.is_empty());
+    }
+
+    #[tokio::test]
+    async fn safe_mode_rejects_eeprom_writes() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Err(DynamixelDriverError::Timeout),
+                Ok(Status::new(1, vec![])),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.enable_safe_mode();
+        assert!(matches!(
+            driver.write_id(1, 2).await.unwrap_err(),
+            DynamixelDriverError::EepromWriteLocked(ID)
+        ));
+        assert!(writing_buffer.lock().unwrap().is_empty());
+
+        driver.allow_eeprom_writes();
+        driver.write_id(1, 2).await.unwrap();
+        assert_eq!(
+            writing_buffer.lock().unwrap().remove(1),
+            vec![255, 255, 1, 4, 3, 3, 2, 242]
+        );
+    }
+
+    #[tokio::test]
+    async fn write_id_refuses_taken_id() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(2, vec![]), Status::new(1, vec![])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        assert!(matches!(
+            driver.write_id(1, 2).await.unwrap_err(),
+            DynamixelDriverError::IdInUse(2)
+        ));
+        assert_eq!(
+            *writing_buffer.lock().unwrap(),
+            vec![vec![255, 255, 2, 2, 1, 250]]
+        );
+        writing_buffer.lock().unwrap().clear();
+
+        driver.force_write_id(1, 2).await.unwrap();
+        assert_eq!(
+            writing_buffer.lock().unwrap().remove(0),
+            vec![255, 255, 1, 4, 3, 3, 2, 242]
+        );
+    }
+
+    #[tokio::test]
+    async fn eeprom_writes_are_journaled() {
+        let path =
+            std::env::temp_dir().join(format!("dynamixel-journal-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                Status::new(1, vec![1]),
+                Status::new(1, vec![]),
+                Status::new(2, vec![]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.enable_eeprom_journal(&path).unwrap();
+        driver.force_write_id(1, 2).await.unwrap();
+        // RAM writes are not journaled
+        driver.write_led(2, true).await.unwrap();
+        assert_eq!(
+            writing_buffer.lock().unwrap()[0],
+            Instruction::read_instruction(1, ID, 1).serialize()
+        );
+
+        let entries = read_journal(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            (
+                entries[0].id,
+                entries[0].register,
+                entries[0].old,
+                entries[0].new
+            ),
+            (1, ID, Some(1), 2)
+        );
+        assert_eq!(entries[0].revert(), Some(WriteOp::u8(1, ID, 1)));
+    }
+
+    #[tokio::test]
+    async fn write_id_refuses_reserved_ids_and_verifies() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Err(DynamixelDriverError::Timeout),
+                Ok(Status::new(1, vec![])),
+                Ok(Status::new(3, vec![])),
+                Err(DynamixelDriverError::Timeout),
+                Ok(Status::new(3, vec![])),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        for (id, new_id) in [(1, 254), (1, 255), (254, 3)] {
+            assert!(matches!(
+                driver.write_id(id, new_id).await.unwrap_err(),
+                DynamixelDriverError::ReservedId(reserved) if reserved != 1 && reserved != 3
+            ));
+        }
+        assert!(driver.force_write_id(1, 254).await.is_err());
+        assert!(writing_buffer.lock().unwrap().is_empty());
+
+        let change = driver.write_id(1, 3).await.unwrap();
+        assert_eq!(
+            change,
+            IdChange {
+                old_id: 1,
+                new_id: 3,
+                verified: true
+            }
+        );
+        assert_eq!(
+            writing_buffer.lock().unwrap()[2],
+            Instruction::ping(3).serialize()
+        );
+        // nothing answers the verification ping
+        assert!(!driver.write_id(3, 4).await.unwrap().verified);
+    }
+
+    #[tokio::test]
+    async fn capabilities_gate_model_specific_registers() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![Status::new(2, vec![])], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.configure_servo(ServoDescription {
+            model: Some(ServoModel::Ax12A),
+            ..ServoDescription::new(1)
+        });
+        driver.configure_servo(ServoDescription {
+            model: Some(ServoModel::Mx28),
+            ..ServoDescription::new(2)
+        });
+        assert!(matches!(
+            driver.write_pid_gains(1, PidGains::default()).await,
+            Err(DynamixelDriverError::Unsupported {
+                id: 1,
+                model: ServoModel::Ax12A,
+                capability: Capability::PidGains
+            })
+        ));
+        assert!(matches!(
+            driver.write_goal_torque(2, 0.5).await,
+            Err(DynamixelDriverError::Unsupported { id: 2, .. })
+        ));
+        assert!(matches!(
+            driver
+                .sync_write_compliance_slope_both(vec![(1, 32), (2, 32)])
+                .await,
+            Err(DynamixelDriverError::Unsupported { id: 2, .. })
+        ));
+        assert!(writing_buffer.lock().unwrap().is_empty());
+
+        driver
+            .write_pid_gains(2, PidGains { p: 32, i: 0, d: 8 })
+            .await
+            .unwrap();
+        assert_eq!(
+            writing_buffer.lock().unwrap()[0],
+            Instruction::write(2, D_GAIN, &[8, 0, 32]).serialize()
+        );
+        // servos of unknown model aren't gated, nothing answers this one
+        assert!(matches!(
+            driver.write_goal_torque(3, 0.5).await,
+            Err(DynamixelDriverError::Timeout)
+        ));
+        assert_eq!(writing_buffer.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn identity_verification_guards_eeprom_writes() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                // MX-28
+                Status::new(1, vec![29, 0]),
+                Status::new(1, vec![12, 0]),
+                Status::new(1, vec![]),
+                Status::new(1, vec![]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_identity_verification(true);
+        assert!(matches!(
+            driver.force_write_id(1, 2).await.unwrap_err(),
+            DynamixelDriverError::UnknownExpectedModel(1)
+        ));
+        assert!(writing_buffer.lock().unwrap().is_empty());
+
+        driver.configure_servo(ServoDescription {
+            model: Some(ServoModel::Ax12A),
+            ..ServoDescription::new(1)
+        });
+        assert!(matches!(
+            driver.force_write_id(1, 2).await.unwrap_err(),
+            DynamixelDriverError::ModelMismatch {
+                id: 1,
+                expected: ServoModel::Ax12A,
+                actual: ServoModel::Mx28
+            }
+        ));
+        driver.force_write_id(1, 2).await.unwrap();
+        // RAM writes are not verified
+        driver.write_position(1, 150).await.unwrap();
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(writing_buffer_guard.len(), 4);
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![255, 255, 1, 4, 2, 0, 2, 246]
+        );
+    }
+
+    #[tokio::test]
+    async fn safe_mode_allows_ram_writes() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![Status::new(1, vec![])], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.enable_safe_mode();
+        driver.write_position(1, 150).await.unwrap();
+        assert_eq!(writing_buffer.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn read_realtime_tick_of_protocol2_servos() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        handle.set_register(2, PROTOCOL2_REALTIME_TICK, &[0x34, 0x12]);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        assert_eq!(driver.read_realtime_tick(2).await.unwrap(), 0x1234);
+        assert!(matches!(
+            driver.read_realtime_tick(1).await,
+            Err(DynamixelDriverError::Protocol2Required(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn read_registered_instruction_reads() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, vec![1]), Status::new(1, vec![0])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        assert!(driver.read_registered_instruction(1).await.unwrap());
+        assert!(!driver.read_registered_instruction(1).await.unwrap());
+        assert_eq!(
+            writing_buffer.lock().unwrap().remove(0),
+            vec![255, 255, 1, 4, 2, 44, 1, 203]
+        );
+    }
+
+    #[test]
+    fn goal_torque_encoding() {
+        assert_eq!(goal_torque_to_raw(0.0), Some(0));
+        assert_eq!(goal_torque_to_raw(1.0), Some(1023));
+        assert_eq!(goal_torque_to_raw(-1.0), Some(2047));
+        assert_eq!(goal_torque_to_raw(0.5), Some(512));
+        assert_eq!(goal_torque_to_raw(-0.5), Some(1536));
+        assert_eq!(goal_torque_to_raw(3.0), Some(1023));
+        assert_eq!(goal_torque_to_raw(f32::NAN), None);
+        assert_eq!(goal_torque_to_raw(f32::INFINITY), None);
+        assert_eq!(goal_torque_to_raw(f32::NEG_INFINITY), None);
+    }
+
+    #[tokio::test]
+    async fn write_goal_torque_writes() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![Status::new(1, vec![])], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.write_goal_torque(1, -1.0).await.unwrap();
+        assert_eq!(
+            writing_buffer.lock().unwrap().remove(0),
+            vec![255, 255, 1, 5, 3, 71, 255, 7, 169]
+        );
+        assert!(matches!(
+            driver.write_goal_torque(1, f32::NAN).await,
+            Err(DynamixelDriverError::InvalidTorque(_))
+        ));
+        assert!(writing_buffer.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn transaction_retries_recoverable_errors() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Err(DynamixelDriverError::Timeout),
+                Ok(Status::new(1, vec![42])),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_retries(1);
+        assert_eq!(driver.read_temperature(1).await.unwrap(), 42);
+        assert_eq!(writing_buffer.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn send_instruction_holds_writes_to_eeprom_lock_and_journal() {
+        let path =
+            std::env::temp_dir().join(format!("dynamixel-raw-journal-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        driver.enable_safe_mode();
+        assert!(matches!(
+            driver
+                .send_instruction(Instruction::write_u8(1, ID, 5))
+                .await,
+            Err(DynamixelDriverError::EepromWriteLocked(ID))
+        ));
+        assert!(matches!(
+            driver
+                .send_instruction(Protocol2.reg_write(2, PROTOCOL2_ID.into(), &[5]))
+                .await,
+            Err(DynamixelDriverError::EepromWriteLocked(PROTOCOL2_ID))
+        ));
+        assert_eq!(handle.register(1, ID), Some(1));
+        assert_eq!(handle.register(2, PROTOCOL2_ID), Some(2));
+        driver
+            .send_instruction(Protocol2.write(2, PROTOCOL2_TORQUE_ENABLE.into(), &[1]))
+            .await
+            .unwrap();
+        assert_eq!(handle.register(2, PROTOCOL2_TORQUE_ENABLE), Some(1));
+
+        driver.allow_eeprom_writes();
+        driver.enable_eeprom_journal(&path).unwrap();
+        driver
+            .send_instruction(Protocol2.write(2, PROTOCOL2_MAX_POSITION_LIMIT.into(), &[0, 8, 0]))
+            .await
+            .unwrap();
+        let entries = read_journal(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let changes: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.id, entry.register, entry.old, entry.new))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (2, PROTOCOL2_MAX_POSITION_LIMIT, Some(0x0FFF), 0x0800),
+                (2, PROTOCOL2_MAX_POSITION_LIMIT + 2, Some(0), 0),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_instruction_reports_status_error_flags() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                Status::new(1, vec![0x0C, 0x00]),
+                Status::with_error(1, 1 << 5, vec![]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let status = driver
+            .send_instruction(Instruction::read_instruction(1, MODEL_NUMBER, 2))
+            .await
+            .unwrap();
+        assert_eq!(status.as_u16().unwrap(), 12);
+        match driver.send_instruction(Instruction::ping(1)).await {
+            Err(DynamixelDriverError::StatusError(error)) => assert!(error.overload_error),
+            other => panic!("expected status error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn transaction_gives_up_after_retries() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_retries(2);
+        assert!(matches!(
+            driver.ping(1).await.unwrap_err(),
+            DynamixelDriverError::Timeout
+        ));
+        assert_eq!(writing_buffer.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn calibration_applies_to_degree_positions() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, vec![]), Status::new(1, vec![0, 2])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.configure_servo(ServoDescription {
+            offset_degrees: 150.0,
+            inverted: true,
+            ..ServoDescription::new(1)
+        });
+        // 150 - 50 = 100 degrees => 341 ticks
+        driver.write_position_degrees(1, 50.0).await.unwrap();
+        assert_eq!(
+            writing_buffer.lock().unwrap().remove(0),
+            vec![255, 255, 1, 5, 3, 30, 85, 1, 130]
+        );
+        // 512 ticks => 150.15 degrees on the servo
+        let position = driver.read_position_degrees(1).await.unwrap();
+        assert!((position - -0.146).abs() < 0.01);
+    }
+
+    #[tokio::test]
+    async fn circuit_breaker_fails_fast_for_offline_servo() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.enable_circuit_breaker(CircuitBreakerConfig {
+            failure_threshold: 2,
+            ..Default::default()
+        });
+        assert!(driver.ping(1).await.is_err());
+        assert!(driver.ping(1).await.is_err());
+        assert_eq!(driver.circuit_state(1), CircuitState::Open);
+        assert!(matches!(
+            driver.ping(1).await.unwrap_err(),
+            DynamixelDriverError::ServoOffline(1)
+        ));
+        // offline servo is not touched on the bus
+        assert_eq!(writing_buffer.lock().unwrap().len(), 2);
+        assert_eq!(driver.circuit_state(2), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn goals_outside_joint_limits_are_rejected() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.configure_servo(ServoDescription {
+            min_degrees: Some(-10.0),
+            max_degrees: Some(10.0),
+            offset_degrees: 150.0,
+            ..ServoDescription::new(1)
+        });
+        assert!(matches!(
+            driver.write_position_degrees(1, 20.0).await,
+            Err(DynamixelDriverError::JointLimitExceeded { id: 1, .. })
+        ));
+        assert!(driver
+            .sync_write_position_degrees(vec![SyncCommandFloat::new(1, -11.0)])
+            .await
+            .is_err());
+        assert!(writing_buffer.lock().unwrap().is_empty());
+        driver
+            .sync_write_position_degrees(vec![SyncCommandFloat::new(1, 5.0)])
+            .await
+            .unwrap();
+        assert_eq!(writing_buffer.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "urdf")]
+    #[test]
+    fn urdf_limits_apply_to_named_servos() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![], writing_buffer);
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.configure_servo(ServoDescription::new(3).with_name("knee"));
+        let urdf = r#"<robot name="leg">
+              <joint name="knee" type="revolute"><limit lower="0" upper="1.5707963"/></joint>
+              <joint name="ankle" type="revolute"><limit lower="-1" upper="1"/></joint>
+            </robot>"#;
+        assert_eq!(driver.apply_urdf_limits(urdf).unwrap(), vec!["ankle"]);
+        let knee = driver.servo_description(3).unwrap();
+        assert_eq!(knee.min_degrees, Some(0.0));
+        assert!((knee.max_degrees.unwrap() - 90.0).abs() < 1e-3);
+    }
+
+    #[tokio::test]
+    async fn read_and_write_ram() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mut ram = vec![0; RAM_LEN as usize];
+        ram[12] = 0x00;
+        ram[13] = 0x02;
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, ram), Status::new(1, vec![])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        assert_eq!(driver.read_ram(1).await.unwrap().present_position, 512);
+        let fields = RamWrite {
+            goal_position: Some(512),
+            moving_speed: Some(0),
+            ..Default::default()
+        };
+        driver.write_ram(1, fields).await.unwrap();
+        let written = writing_buffer.lock().unwrap();
+        assert_eq!(
+            written[0],
+            Instruction::read_instruction(1, 24, 26).serialize()
+        );
+        assert_eq!(
+            written[1],
+            Instruction::write(1, GOAL_POSITION, &[0x00, 0x02, 0x00, 0x00]).serialize()
+        );
+    }
+
+    #[tokio::test]
+    async fn id_mismatch_policies() {
+        let responses = || vec![Status::new(2, vec![0x20]), Status::new(1, vec![0x21])];
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(responses(), writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        assert!(matches!(
+            driver.read_temperature(1).await,
+            Err(DynamixelDriverError::IdMismatchError(1, 2))
+        ));
+
+        let mock_port = MockFramedDriver::new(responses(), writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_id_mismatch_policy(IdMismatchPolicy::Discard);
+        assert_eq!(driver.read_temperature(1).await.unwrap(), 0x21);
+
+        let mock_port = MockFramedDriver::new(responses(), writing_buffer);
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_id_mismatch_policy(IdMismatchPolicy::AcceptAny);
+        assert_eq!(driver.read_temperature(1).await.unwrap(), 0x20);
+    }
+
+    /// Answers every instruction behind a stale status left in the receive buffer
+    struct StaleStatus(Vec<Status>);
+
+    #[async_trait]
+    impl FramedDriver for StaleStatus {
+        async fn send(&mut self, instruction: Instruction) -> Result<()> {
+            self.0.push(Status::new(instruction.id(), vec![0x21]));
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<Status> {
+            if self.0.is_empty() {
+                return Err(DynamixelDriverError::Timeout);
+            }
+            Ok(self.0.remove(0))
+        }
+
+        async fn clear_io_buffers(&mut self) -> Result<()> {
+            self.0.clear();
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn flush_policies_drop_stale_statuses() {
+        let stale = || Box::new(StaleStatus(vec![Status::new(2, vec![0x20])]));
+        let mut driver = DynamixelDriver::with_driver(stale());
+        assert!(driver.read_temperature(1).await.is_err());
+        // every following answer is read one transaction late
+        assert!(matches!(
+            driver.read_temperature(3).await,
+            Err(DynamixelDriverError::IdMismatchError(3, 1))
+        ));
+
+        let mut driver = DynamixelDriver::with_driver(stale());
+        driver.set_flush_policy(FlushPolicy::OnError);
+        assert!(driver.read_temperature(1).await.is_err());
+        assert_eq!(driver.read_temperature(1).await.unwrap(), 0x21);
+
+        let mut driver = DynamixelDriver::with_driver(stale());
+        driver.set_flush_policy(FlushPolicy::BeforeTransaction);
+        assert_eq!(driver.read_temperature(1).await.unwrap(), 0x21);
+    }
+
+    #[cfg(feature = "ros")]
+    #[tokio::test]
+    async fn joint_state_names_and_scales_feedback() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1, 2]);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.configure_servo(ServoDescription {
+            name: Some("knee".to_owned()),
+            model: Some(ServoModel::Ax12A),
+            ..ServoDescription::new(1)
+        });
+        let mut feedback = GroupFeedback::new(&[1, 2, 3]);
+        driver.update_group_feedback(&mut feedback).await;
+        let state = driver.joint_state(&feedback);
+        assert_eq!(state.name, vec!["knee", "servo_2"]);
+        let expected = (512.0 / ServoModel::Ax12A.steps_per_degree()).to_radians();
+        assert!((state.position[0] - f64::from(expected)).abs() < 1e-6);
+        assert_eq!(state.effort[0], 0.0);
+        assert!(state.effort[1].is_nan());
+    }
+
+    #[tokio::test]
+    async fn update_group_feedback_keeps_failed_joints() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Ok(Status::new(1, vec![0x00, 0x02, 0x64, 0x00, 0x00, 0x06])),
+                Err(DynamixelDriverError::Timeout),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let mut feedback = GroupFeedback::new(&[1, 2]);
+        driver.update_group_feedback(&mut feedback).await;
+        let joint = feedback.get(1).unwrap();
+        assert!((joint.position - 512.0 / 3.41).abs() < 1e-3);
+        assert!((joint.speed - 11.1).abs() < 1e-4);
+        assert!((joint.load + 512.0 / 1023.0).abs() < 1e-6);
+        assert!(feedback.get(2).is_none());
+        assert_eq!(feedback.consecutive_failures(2), 1);
+        assert_eq!(
+            writing_buffer.lock().unwrap()[0],
+            Instruction::read_instruction(1, PRESENT_POSITION, 6).serialize()
+        );
+    }
+
+    #[tokio::test]
+    async fn read_effort_scales_load_by_torque_limit_and_stall_torque() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                // 1023 CW load with half torque limit
+                Status::new(1, vec![0xFF, 0x07]),
+                Status::new(1, vec![0xFF, 0x01]),
+            ],
+            writing_buffer,
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.configure_servo(ServoDescription {
+            model: Some(ServoModel::Mx64),
+            ..ServoDescription::new(1)
+        });
+        let effort = driver.read_effort(1).await.unwrap();
+        assert!((effort + 6.0 * 511.0 / 1023.0).abs() < 1e-4);
+    }
+
+    #[tokio::test]
+    async fn enable_torque_group_reports_each_servo() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Ok(Status::new(1, vec![1])),
+                Err(StatusError::check_error(1 << 2).unwrap_err()),
+                Ok(Status::new(3, vec![0])),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let outcome = driver.enable_torque_group(&[1, 2, 3]).await.unwrap();
+        assert_eq!(outcome.succeeded_ids(), vec![1]);
+        assert_eq!(outcome.failed_ids(), vec![2, 3]);
+        assert!(matches!(
+            outcome.results()[2].1,
+            Err(DynamixelDriverError::VerificationError {
+                id: 3,
+                expected: 1,
+                actual: 0,
+                ..
+            })
+        ));
+        assert_eq!(
+            writing_buffer.lock().unwrap()[0],
+            Instruction::sync_command(
+                TORQUE_ENABLED,
+                1,
+                vec![
+                    SyncCommand::new(1, 1),
+                    SyncCommand::new(2, 1),
+                    SyncCommand::new(3, 1)
+                ]
+            )
+            .serialize()
+        );
+    }
+
+    #[tokio::test]
+    async fn detected_model_selects_position_conversion() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                // MX-28 at its center position, model is read after the first position
+                Status::new(1, vec![0x00, 0x08]),
+                Status::new(1, vec![29, 0]),
+                Status::new(1, vec![0x00, 0x08]),
+                Status::new(1, vec![]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_auto_detect_models(true);
+        assert_eq!(driver.read_position_degrees(1).await.unwrap(), 180.0);
+        assert_eq!(driver.known_model(1), Some(ServoModel::Mx28));
+        // model is only read on first contact
+        assert_eq!(driver.read_position_degrees(1).await.unwrap(), 180.0);
+        driver.write_position_degrees(1, 90.0).await.unwrap();
+        assert!(matches!(
+            driver.write_position_degrees(1, 400.0).await,
+            Err(DynamixelDriverError::PositionOutOfRange { id: 1, .. })
+        ));
+        let written = writing_buffer.lock().unwrap();
+        assert_eq!(written.len(), 4);
+        assert_eq!(
+            written[3],
+            Instruction::write_u16(1, GOAL_POSITION, 1024).serialize()
+        );
+    }
+
+    #[tokio::test]
+    async fn alarms_and_circuit_changes_are_published() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Err(StatusError::check_error(1 << 5).unwrap_err()),
+                Err(DynamixelDriverError::Timeout),
+            ],
+            writing_buffer,
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.enable_circuit_breaker(CircuitBreakerConfig {
+            failure_threshold: 1,
+            ..Default::default()
+        });
+        let mut events = driver.subscribe();
+        assert!(driver.ping(3).await.is_err());
+        assert!(driver.ping(3).await.is_err());
+        match events.try_recv().unwrap() {
+            DriverEvent::Alarm { id: 3, error } => assert!(error.overload_error),
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert_eq!(
+            events.try_recv().unwrap(),
+            DriverEvent::CircuitStateChanged {
+                id: 3,
+                state: CircuitState::Open
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn search_detects_protocol_of_each_servo() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        handle.set_temperature(2, 45);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        assert_eq!(driver.search_all().await.unwrap(), vec![1]);
+        driver.set_detect_protocols(true);
+        assert_eq!(driver.search_all().await.unwrap(), vec![1, 2]);
+        assert_eq!(driver.servo_protocol(1), Some(1));
+        assert_eq!(driver.servo_protocol(2), Some(2));
+        assert_eq!(driver.servo_protocol(3), None);
+        assert_eq!(driver.read_temperature(2).await.unwrap(), 45);
+    }
+
+    #[tokio::test]
+    async fn bulk_write_validates_entries_before_sending() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        handle.add_protocol2_servo(3);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        driver
+            .bulk_write(&BulkWrite::new().u16(2, 30, 700).u8(3, 25, 1))
+            .await
+            .unwrap();
+        assert_eq!(handle.register_u16(2, GOAL_POSITION), Some(700));
+        assert_eq!(handle.register(3, 25), Some(1));
+
+        let error = driver
+            .bulk_write(&BulkWrite::new().u16(2, 30, 100).u16(1, 30, 100))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DynamixelDriverError::Protocol2Required(1)));
+        let error = driver
+            .bulk_write(&BulkWrite::new().u16(2, 30, 100).u8(2, 25, 1))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DynamixelDriverError::DuplicateBulkWrite(2)));
+        driver.configure_servo(ServoDescription {
+            model: Some(ServoModel::Ax12A),
+            ..ServoDescription::new(3)
+        });
+        let error = driver
+            .bulk_write(&BulkWrite::new().u16(2, 30, 100).u16(3, 49, 1))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            DynamixelDriverError::RegisterOutOfRange {
+                id: 3,
+                addr: 49,
+                len: 2,
+                ..
+            }
+        ));
+        // XM430-W350 detected from its model number
+        handle.set_register(2, 0, &1020u16.to_le_bytes());
+        driver.set_auto_detect_models(true);
+        let error = driver
+            .bulk_write(&BulkWrite::new().u32(2, 660, 1))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            DynamixelDriverError::RegisterOutOfRange {
+                id: 2,
+                model: ServoModel::Other(1020),
+                addr: 660,
+                len: 4,
+            }
+        ));
+        assert_eq!(handle.register_u16(2, GOAL_POSITION), Some(700));
+    }
+
+    #[tokio::test]
+    async fn reboot_clears_latched_errors() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        handle.set_register(2, PROTOCOL2_TORQUE_ENABLE, &[1]);
+        handle.set_error_flags(2, 1 << 2);
+        assert!(driver.ping(2).await.is_err());
+        driver
+            .reboot_and_wait(2, Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert_eq!(handle.register(2, PROTOCOL2_TORQUE_ENABLE), Some(0));
+        driver.ping(2).await.unwrap();
+        assert!(matches!(
+            driver.reboot(1).await,
+            Err(DynamixelDriverError::Protocol2Required(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn bulk_write_applies_goal_limits_eeprom_lock_and_journal() {
+        let path =
+            std::env::temp_dir().join(format!("dynamixel-bulk-journal-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let bus = crate::test_utils::SimulatedBus::new();
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        // max position limit 800 followed by min position limit 200
+        handle.set_register(
+            2,
+            PROTOCOL2_MAX_POSITION_LIMIT,
+            &[0x20, 0x03, 0, 0, 200, 0, 0, 0],
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        driver.read_angle_limits(2).await.unwrap();
+        driver.set_angle_limit_policy(AngleLimitPolicy::Clamp);
+        assert_eq!(
+            driver.known_angle_limits(2),
+            Some(AngleLimits { cw: 200, ccw: 800 })
+        );
+        driver
+            .bulk_write(&BulkWrite::new().u32(2, PROTOCOL2_GOAL_POSITION.into(), 1000))
+            .await
+            .unwrap();
+        assert_eq!(handle.register_u16(2, PROTOCOL2_GOAL_POSITION), Some(800));
+        assert_eq!(handle.register_u16(2, PROTOCOL2_GOAL_POSITION + 2), Some(0));
+
+        driver.enable_safe_mode();
+        let error = driver
+            .bulk_write(&BulkWrite::new().u8(2, PROTOCOL2_ID.into(), 9))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            DynamixelDriverError::EepromWriteLocked(PROTOCOL2_ID)
+        ));
+        assert_eq!(handle.register(2, PROTOCOL2_ID), Some(2));
+
+        driver.allow_eeprom_writes();
+        driver.enable_eeprom_journal(&path).unwrap();
+        driver
+            .bulk_write(&BulkWrite::new().bytes(
+                2,
+                PROTOCOL2_MAX_POSITION_LIMIT.into(),
+                &[0xFF, 0x03, 0, 0, 100, 0, 0, 0],
+            ))
+            .await
+            .unwrap();
+        let entries = read_journal(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let changes: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.register, entry.old, entry.new))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (PROTOCOL2_MAX_POSITION_LIMIT, Some(800), 1023),
+                (PROTOCOL2_MAX_POSITION_LIMIT + 2, Some(0), 0),
+                (PROTOCOL2_MIN_POSITION_LIMIT, Some(200), 100),
+                (PROTOCOL2_MIN_POSITION_LIMIT + 2, Some(0), 0),
+            ]
+        );
+        assert_eq!(
+            driver.known_angle_limits(2),
+            Some(AngleLimits { cw: 100, ccw: 1023 })
+        );
+    }
+
+    #[tokio::test]
+    async fn bulk_write_journals_long_entries_without_overflow() {
+        let path = std::env::temp_dir().join(format!(
+            "dynamixel-bulk-long-journal-{}.log",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let bus = crate::test_utils::SimulatedBus::new();
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        driver.allow_eeprom_writes();
+        driver.enable_eeprom_journal(&path).unwrap();
+        // runs past address 255, which used to overflow the journal offsets
+        let mut data = vec![0; 400];
+        // operating mode stays, secondary id is cleared
+        data[0] = 3;
+        driver
+            .bulk_write(&BulkWrite::new().bytes(2, 11, &data))
+            .await
+            .unwrap();
+        let entries = read_journal(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(entries.len(), 27);
+        assert_eq!(
+            (entries[0].register, entries[0].old, entries[0].new),
+            (11, Some(0xFF03), 3)
+        );
+        assert_eq!(entries[26].register, 63);
+    }
+
+    #[tokio::test]
+    async fn protocol2_eeprom_extends_to_address_63() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        bus.handle().add_protocol2_servo(2);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        driver.enable_safe_mode();
+        driver.write_torque(1, true).await.unwrap();
+        driver.write_torque(2, true).await.unwrap();
+        assert!(matches!(
+            driver.bulk_write(&BulkWrite::new().u8(2, 63, 0)).await,
+            Err(DynamixelDriverError::EepromWriteLocked(63))
+        ));
+        assert!(matches!(
+            driver.write_id(2, 5).await,
+            Err(DynamixelDriverError::EepromWriteLocked(PROTOCOL2_ID))
+        ));
+    }
+
+    #[tokio::test]
+    async fn scan_inventory_reads_firmware_per_protocol() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        // XM430-W350 with firmware 45, addresses 2 to 5 hold model information
+        handle.set_register(2, MODEL_NUMBER, &[0xFC, 0x03, 0, 0, 0, 0, 45]);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        let inventory = driver.scan_inventory().await.unwrap();
+        assert_eq!(
+            inventory.servos,
+            vec![
+                InventoryEntry {
+                    id: 1,
+                    model: ServoModel::Ax12A,
+                    firmware: Some(24),
+                },
+                InventoryEntry {
+                    id: 2,
+                    model: ServoModel::from_model_number(1020),
+                    firmware: Some(45),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn safety_policy_turns_off_torque() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1, 2]);
+        let handle = bus.handle();
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_safety_policy(SafetyPolicy::TorqueOffServo);
+        driver.write_torque(1, true).await.unwrap();
+        driver.write_torque(2, true).await.unwrap();
+        let mut events = driver.subscribe();
+        handle.set_error_flags(1, 1 << 2);
+        assert!(driver.read_temperature(1).await.is_err());
+        assert_eq!(handle.register(1, TORQUE_ENABLED), Some(0));
+        assert_eq!(handle.register(2, TORQUE_ENABLED), Some(1));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DriverEvent::Alarm { id: 1, .. }
+        ));
+        match events.try_recv().unwrap() {
+            DriverEvent::SafetyTorqueOff {
+                id: 1,
+                error,
+                policy,
+            } => {
+                assert!(error.overheating_error);
+                assert_eq!(policy, SafetyPolicy::TorqueOffServo);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+
+        handle.set_error_flags(1, 0);
+        driver.write_torque(1, true).await.unwrap();
+        driver.set_safety_policy(SafetyPolicy::TorqueOffBus);
+        handle.set_error_flags(2, 1);
+        assert!(driver.read_temperature(2).await.is_err());
+        assert_eq!(handle.register(1, TORQUE_ENABLED), Some(0));
+        assert_eq!(handle.register(2, TORQUE_ENABLED), Some(0));
+    }
+
+    #[tokio::test]
+    async fn protocol2_hardware_alert_reads_hardware_error_status() {
+        let bus = crate::test_utils::SimulatedBus::new();
+        let handle = bus.handle();
+        handle.add_protocol2_servo(1);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        driver.set_safety_policy(SafetyPolicy::TorqueOffServo);
+        driver.write_torque(1, true).await.unwrap();
+        assert_eq!(handle.register(1, PROTOCOL2_TORQUE_ENABLE), Some(1));
+        // the AX torque enable address is the homing offset on the X series
+        handle.set_register(1, TORQUE_ENABLED, &[10]);
+        let mut events = driver.subscribe();
+        handle.set_hardware_error(1, 1 << 2);
+        match driver.read_temperature(1).await {
+            Err(DynamixelDriverError::StatusError(error)) => {
+                assert!(error.hardware_alert);
+                assert!(error.overheating_error);
+                assert!(!error.overload_error);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert_eq!(handle.register(1, PROTOCOL2_TORQUE_ENABLE), Some(0));
+        assert_eq!(handle.register(1, TORQUE_ENABLED), Some(10));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DriverEvent::Alarm { id: 1, error } if error.overheating_error
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DriverEvent::SafetyTorqueOff { id: 1, .. }
+        ));
+
+        driver.reboot(1).await.unwrap();
+        assert!(driver.read_temperature(1).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn current_reads_and_limits_in_milliamps() {
+        let bus = crate::test_utils::SimulatedBus::new();
+        let handle = bus.handle();
+        handle.add_protocol2_servo(1);
+        handle.add_protocol2_servo(2);
+        handle.set_register(1, PROTOCOL2_PRESENT_CURRENT, &(-100_i16).to_le_bytes());
+        let mut driver = DynamixelDriver::with_protocol(Box::new(bus), Arc::new(Protocol2));
+        driver.enable_safe_mode();
+        let currents = driver.read_present_currents(&[1, 2]).await;
+        assert!((currents[0].1.as_ref().unwrap() + 269.0).abs() < 0.01);
+        assert_eq!(currents[1].1.as_ref().unwrap(), &0.0);
+        assert!((driver.read_current_limit(1).await.unwrap() - 3209.17).abs() < 0.01);
+        assert!(matches!(
+            driver.write_current_limit(1, 1000.0).await,
+            Err(DynamixelDriverError::EepromWriteLocked(
+                PROTOCOL2_CURRENT_LIMIT
+            ))
+        ));
+
+        driver.allow_eeprom_writes();
+        driver.write_current_limit(1, 1000.0).await.unwrap();
+        assert_eq!(handle.register_u16(1, PROTOCOL2_CURRENT_LIMIT), Some(372));
+        driver
+            .sync_write_current_limit(vec![(1, 269.0).into(), (2, 538.0).into()])
+            .await
+            .unwrap();
+        assert_eq!(handle.register_u16(1, PROTOCOL2_CURRENT_LIMIT), Some(100));
+        assert_eq!(handle.register_u16(2, PROTOCOL2_CURRENT_LIMIT), Some(200));
+        assert!(matches!(
+            driver.write_current_limit(1, f32::NAN).await,
+            Err(DynamixelDriverError::InvalidCurrent(_))
+        ));
+        assert!(matches!(
+            driver.write_current_limit(1, -1.0).await,
+            Err(DynamixelDriverError::InvalidCurrent(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn move_with_profile_writes_profile_and_goal_at_once() {
+        let bus = crate::test_utils::SimulatedBus::new();
+        let handle = bus.handle();
+        handle.add_protocol2_servo(1);
+        let mut driver = DynamixelDriver::with_protocol(Box::new(bus), Arc::new(Protocol2));
+        driver.read_angle_limits(1).await.unwrap();
+        driver.set_angle_limit_policy(AngleLimitPolicy::Clamp);
+        driver
+            .move_with_profile(1, 5000, 11.45, 2145.77)
+            .await
+            .unwrap();
+        assert_eq!(
+            handle.register_u16(1, PROTOCOL2_PROFILE_ACCELERATION),
+            Some(10)
+        );
+        assert_eq!(handle.register_u16(1, PROTOCOL2_PROFILE_VELOCITY), Some(50));
+        assert_eq!(handle.register_u16(1, PROTOCOL2_GOAL_POSITION), Some(4095));
+        assert!((driver.read_profile_velocity(1).await.unwrap() - 11.45).abs() < 0.01);
+
+        driver.write_profile_velocity(1, 0.0).await.unwrap();
+        driver.write_profile_acceleration(1, 429.154).await.unwrap();
+        assert_eq!(handle.register_u16(1, PROTOCOL2_PROFILE_VELOCITY), Some(0));
+        assert!((driver.read_profile_acceleration(1).await.unwrap() - 429.154).abs() < 0.01);
+        assert!(matches!(
+            driver.write_profile_velocity(1, -1.0).await,
+            Err(DynamixelDriverError::InvalidProfile(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn operating_mode_switches_only_without_torque() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        assert_eq!(
+            driver.read_operating_mode(2).await.unwrap(),
+            OperatingMode::Position
+        );
+        driver.write_torque(2, true).await.unwrap();
+        assert!(matches!(
+            driver
+                .write_operating_mode(2, OperatingMode::Velocity)
+                .await,
+            Err(DynamixelDriverError::TorqueEnabled(2))
+        ));
+        driver.write_torque(2, false).await.unwrap();
+        driver
+            .write_operating_mode(2, OperatingMode::Pwm)
+            .await
+            .unwrap();
+        assert_eq!(handle.register(2, PROTOCOL2_OPERATING_MODE), Some(16));
+        assert_eq!(
+            driver.read_operating_mode(2).await.unwrap(),
+            OperatingMode::Pwm
+        );
+        assert!(matches!(
+            driver.read_operating_mode(1).await,
+            Err(DynamixelDriverError::Protocol2Required(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn secondary_group_writes_reach_every_member() {
+        let bus = crate::test_utils::SimulatedBus::new();
+        let handle = bus.handle();
+        for id in 1..=3 {
+            handle.add_protocol2_servo(id);
+        }
+        let mut driver = DynamixelDriver::with_protocol(Box::new(bus), Arc::new(Protocol2));
+        driver.write_secondary_id(1, Some(10)).await.unwrap();
+        driver.write_secondary_id(2, Some(10)).await.unwrap();
+        assert_eq!(driver.read_secondary_id(3).await.unwrap(), None);
+        assert_eq!(driver.read_secondary_id(2).await.unwrap(), Some(10));
+        assert_eq!(driver.secondary_group(10), vec![1, 2]);
+        assert!(matches!(
+            driver.write_secondary_id(1, Some(253)).await,
+            Err(DynamixelDriverError::ReservedId(253))
+        ));
+
+        handle.set_register(1, PROTOCOL2_MAX_POSITION_LIMIT, &[0xE8, 0x03, 0, 0]);
+        driver.read_angle_limits(1).await.unwrap();
+        driver.set_angle_limit_policy(AngleLimitPolicy::Clamp);
+        driver
+            .write_secondary_group(10, PROTOCOL2_GOAL_POSITION.into(), &3000_u32.to_le_bytes())
+            .await
+            .unwrap();
+        assert_eq!(handle.register_u16(1, PROTOCOL2_GOAL_POSITION), Some(1000));
+        assert_eq!(handle.register_u16(2, PROTOCOL2_GOAL_POSITION), Some(1000));
+        assert_eq!(handle.register_u16(3, PROTOCOL2_GOAL_POSITION), Some(2048));
+
+        driver.enable_safe_mode();
+        assert!(matches!(
+            driver
+                .write_secondary_group(10, PROTOCOL2_OPERATING_MODE.into(), &[1])
+                .await,
+            Err(DynamixelDriverError::EepromWriteLocked(
+                PROTOCOL2_OPERATING_MODE
+            ))
+        ));
+        driver.write_secondary_id(2, None).await.unwrap_err();
+        driver.allow_eeprom_writes();
+        driver.write_secondary_id(2, None).await.unwrap();
+        assert_eq!(driver.secondary_group(10), vec![1]);
+        assert_eq!(handle.register(2, PROTOCOL2_SECONDARY_ID), Some(255));
+    }
+
+    #[tokio::test]
+    async fn indirect_addressing_remaps_scattered_registers() {
+        let bus = crate::test_utils::SimulatedBus::new();
+        let handle = bus.handle();
+        handle.add_protocol2_servo(1);
+        handle.add_protocol2_servo(2);
+        let mut driver = DynamixelDriver::with_protocol(Box::new(bus), Arc::new(Protocol2));
+        let registers = [
+            (PROTOCOL2_PRESENT_TEMPERATURE.into(), 1),
+            (PROTOCOL2_GOAL_POSITION.into(), 4),
+        ];
+        driver.map_indirect(1, &registers).await.unwrap();
+        assert_eq!(
+            driver.read_indirect(1, 5).await.unwrap(),
+            vec![30, 0x00, 0x08, 0, 0]
+        );
+        driver.write_indirect(1, &[30, 0x00, 0x04]).await.unwrap();
+        assert_eq!(handle.register_u16(1, PROTOCOL2_GOAL_POSITION), Some(1024));
+
+        driver
+            .map_indirect(2, &[(PROTOCOL2_GOAL_POSITION.into(), 4)])
+            .await
+            .unwrap();
+        driver
+            .map_indirect(1, &[(PROTOCOL2_GOAL_POSITION.into(), 4)])
+            .await
+            .unwrap();
+        driver
+            .sync_write_indirect(4, vec![(1, 100), (2, 200)])
+            .await
+            .unwrap();
+        assert_eq!(handle.register_u16(1, PROTOCOL2_GOAL_POSITION), Some(100));
+        assert_eq!(handle.register_u16(2, PROTOCOL2_GOAL_POSITION), Some(200));
+
+        assert!(matches!(
+            driver
+                .map_indirect(1, &[(PROTOCOL2_GOAL_POSITION.into(), 29)])
+                .await,
+            Err(DynamixelDriverError::TooManyIndirectBytes(29))
+        ));
+        driver.enable_safe_mode();
+        assert!(matches!(
+            driver.map_indirect(1, &[(PROTOCOL2_ID.into(), 1)]).await,
+            Err(DynamixelDriverError::EepromWriteLocked(PROTOCOL2_ID))
+        ));
+        driver.write_torque(1, true).await.unwrap();
+        assert!(matches!(
+            driver.map_indirect(1, &registers).await,
+            Err(DynamixelDriverError::TorqueEnabled(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn homing_offset_and_drive_mode_need_torque_disabled() {
+        let bus = crate::test_utils::SimulatedBus::new();
+        let handle = bus.handle();
+        handle.add_protocol2_servo(1);
+        let mut driver = DynamixelDriver::with_protocol(Box::new(bus), Arc::new(Protocol2));
+        assert_eq!(driver.read_homing_offset(1).await.unwrap(), 0);
+        assert_eq!(
+            driver.read_drive_mode(1).await.unwrap(),
+            DriveMode::default()
+        );
+        driver.write_homing_offset(1, -1024).await.unwrap();
+        let mode = DriveMode {
+            reverse: true,
+            time_based_profile: true,
+            ..DriveMode::default()
+        };
+        driver.write_drive_mode(1, mode).await.unwrap();
+        assert_eq!(driver.read_homing_offset(1).await.unwrap(), -1024);
+        assert_eq!(handle.register(1, PROTOCOL2_DRIVE_MODE), Some(0b101));
+        assert_eq!(driver.read_drive_mode(1).await.unwrap(), mode);
+
+        assert!(matches!(
+            driver.write_homing_offset(1, 1_044_480).await,
+            Err(DynamixelDriverError::HomingOffsetOutOfRange(1_044_480))
+        ));
+        driver.write_torque(1, true).await.unwrap();
+        assert!(matches!(
+            driver.write_drive_mode(1, DriveMode::default()).await,
+            Err(DynamixelDriverError::TorqueEnabled(1))
+        ));
+        assert!(matches!(
+            driver.write_homing_offset(1, 0).await,
+            Err(DynamixelDriverError::TorqueEnabled(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn write_pwm_scales_duty_by_pwm_limit() {
+        let bus = crate::test_utils::SimulatedBus::new();
+        let handle = bus.handle();
+        handle.add_protocol2_servo(1);
+        let mut driver = DynamixelDriver::with_protocol(Box::new(bus), Arc::new(Protocol2));
+        assert_eq!(driver.read_pwm_limit(1).await.unwrap(), 885);
+        driver.write_pwm(1, -0.5).await.unwrap();
+        assert_eq!(driver.read_goal_pwm(1).await.unwrap(), -443);
+
+        handle.set_register(1, PROTOCOL2_PWM_LIMIT, &400_u16.to_le_bytes());
+        driver.write_pwm(1, 1.0).await.unwrap();
+        assert_eq!(driver.read_goal_pwm(1).await.unwrap(), 400);
+        driver.write_goal_pwm(1, -12).await.unwrap();
+        assert_eq!(
+            handle.register_u16(1, PROTOCOL2_GOAL_PWM),
+            Some((-12_i16) as u16)
+        );
+
+        for duty in [1.5, f32::NAN, f32::NEG_INFINITY] {
+            assert!(matches!(
+                driver.write_pwm(1, duty).await,
+                Err(DynamixelDriverError::InvalidPwm(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn reboot_and_clear_returns_cleared_hardware_errors() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        assert!(driver
+            .read_hardware_error_status(2)
+            .await
+            .unwrap()
+            .is_clear());
+        handle.set_hardware_error(2, 1 << 3 | 1 << 4);
+        let expected = HardwareErrorStatus {
+            encoder_error: true,
+            electrical_shock_error: true,
+            ..HardwareErrorStatus::default()
+        };
+        assert_eq!(
+            driver.read_hardware_error_status(2).await.unwrap(),
+            expected
+        );
+        assert_eq!(
+            driver
+                .reboot_and_clear(2, Duration::from_millis(100))
+                .await
+                .unwrap(),
+            expected
+        );
+        assert!(driver
+            .read_hardware_error_status(2)
+            .await
+            .unwrap()
+            .is_clear());
+        driver.ping(2).await.unwrap();
+        assert!(matches!(
+            driver.read_hardware_error_status(1).await,
+            Err(DynamixelDriverError::Protocol2Required(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn goals_outside_angle_limits_are_rejected_or_clamped() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1, 2]);
+        let handle = bus.handle();
+        handle.set_register(1, CW_ANGLE_LIMIT, &[200, 0, 0x20, 0x03]);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        let limits = driver.read_angle_limits(1).await.unwrap();
+        assert_eq!(limits, AngleLimits { cw: 200, ccw: 800 });
+
+        driver.write_position(1, 900).await.unwrap();
+        assert_eq!(handle.register_u16(1, GOAL_POSITION), Some(900));
+
+        driver.set_angle_limit_policy(AngleLimitPolicy::Reject);
+        let error = driver.write_position(1, 100).await.unwrap_err();
+        assert!(matches!(
+            error,
+            DynamixelDriverError::AngleLimitExceeded {
+                id: 1,
+                goal: 100,
+                cw: 200,
+                ccw: 800
+            }
+        ));
+        assert_eq!(handle.register_u16(1, GOAL_POSITION), Some(900));
+
+        driver.set_angle_limit_policy(AngleLimitPolicy::Clamp);
+        driver
+            .sync_write_position(vec![SyncCommand::new(1, 1000), SyncCommand::new(2, 1000)])
+            .await
+            .unwrap();
+        assert_eq!(handle.register_u16(1, GOAL_POSITION), Some(800));
+        assert_eq!(handle.register_u16(2, GOAL_POSITION), Some(1000));
+
+        driver.write_u16(1, CCW_ANGLE_LIMIT, 1023).await.unwrap();
+        assert_eq!(
+            driver.known_angle_limits(1),
+            Some(AngleLimits { cw: 200, ccw: 1023 })
+        );
+        driver.write_position(1, 1000).await.unwrap();
+        assert_eq!(handle.register_u16(1, GOAL_POSITION), Some(1000));
+    }
+
+    #[tokio::test]
+    async fn write_ram_applies_angle_limits() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.set_register(1, CW_ANGLE_LIMIT, &[200, 0, 0x20, 0x03]);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.read_angle_limits(1).await.unwrap();
+        let fields = RamWrite {
+            goal_position: Some(100),
+            moving_speed: Some(300),
+            ..Default::default()
+        };
+
+        driver.set_angle_limit_policy(AngleLimitPolicy::Reject);
+        assert!(matches!(
+            driver.write_ram(1, fields).await,
+            Err(DynamixelDriverError::AngleLimitExceeded {
+                id: 1,
+                goal: 100,
+                ..
+            })
+        ));
+        assert_eq!(handle.register_u16(1, MOVING_SPEED), Some(0));
+
+        driver.set_angle_limit_policy(AngleLimitPolicy::Clamp);
+        driver.write_ram(1, fields).await.unwrap();
+        assert_eq!(handle.register_u16(1, GOAL_POSITION), Some(200));
+        assert_eq!(handle.register_u16(1, MOVING_SPEED), Some(300));
+    }
+
+    #[tokio::test]
+    async fn send_prepared_applies_angle_limits() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1, 2]);
+        let handle = bus.handle();
+        handle.set_register(1, CW_ANGLE_LIMIT, &[200, 0, 0x20, 0x03]);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.read_angle_limits(1).await.unwrap();
+        let mut packet = PreparedSyncWrite::new(GOAL_POSITION, 2, &[1, 2]).unwrap();
+        packet.set(1, 1000).unwrap();
+        packet.set(2, 1000).unwrap();
+
+        driver.set_angle_limit_policy(AngleLimitPolicy::Reject);
+        assert!(matches!(
+            driver.send_prepared(&mut packet).await,
+            Err(DynamixelDriverError::AngleLimitExceeded {
+                id: 1,
+                goal: 1000,
+                ..
+            })
+        ));
+        assert_eq!(handle.register_u16(2, GOAL_POSITION), Some(512));
+
+        driver.set_angle_limit_policy(AngleLimitPolicy::Clamp);
+        driver.send_prepared(&mut packet).await.unwrap();
+        assert_eq!(packet.value(0), 800);
+        assert_eq!(handle.register_u16(1, GOAL_POSITION), Some(800));
+        assert_eq!(handle.register_u16(2, GOAL_POSITION), Some(1000));
+    }
+
+    #[tokio::test]
+    async fn send_prepared_checks_eeprom_lock_and_protocol() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        handle.add_protocol2_servo(2);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_detect_protocols(true);
+        driver.search_all().await.unwrap();
+        driver.enable_safe_mode();
+        let mut packet = PreparedSyncWrite::new(CW_ANGLE_LIMIT, 2, &[1]).unwrap();
+        assert!(matches!(
+            driver.send_prepared(&mut packet).await,
+            Err(DynamixelDriverError::EepromWriteLocked(CW_ANGLE_LIMIT))
+        ));
+        assert_eq!(handle.register_u16(1, CW_ANGLE_LIMIT), Some(0));
+
+        let mut packet = PreparedSyncWrite::new(TORQUE_ENABLED, 1, &[1, 2]).unwrap();
+        packet.set(1, 1).unwrap();
+        packet.set(2, 1).unwrap();
+        assert!(matches!(
+            driver.send_prepared(&mut packet).await,
+            Err(DynamixelDriverError::SyncProtocolMismatch(2))
+        ));
+        assert_eq!(handle.register(1, TORQUE_ENABLED), Some(0));
+    }
+
+    #[tokio::test]
+    async fn named_driver_tags_errors_stats_and_events() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Err(DynamixelDriverError::Timeout),
+                Err(StatusError::check_error(1 << 5).unwrap_err()),
+            ],
+            writing_buffer,
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_name("left_leg_bus");
+        driver.set_retries(1);
+        let mut events = driver.subscribe_named();
+        let error = driver.ping(3).await.unwrap_err();
+        assert!(error.to_string().starts_with("left_leg_bus: status error"));
+        assert!(matches!(error.root(), DynamixelDriverError::StatusError(_)));
+        assert!(error.is_recoverable());
+        assert_eq!(driver.bus_stats().bus.as_deref(), Some("left_leg_bus"));
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.bus.as_deref(), Some("left_leg_bus"));
+        assert!(matches!(event.event, DriverEvent::Alarm { id: 3, .. }));
+    }
+
+    #[tokio::test]
+    async fn voltage_monitor_reports_brownout_and_minimum() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, vec![118]), Status::new(2, vec![96])],
+            writing_buffer,
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.enable_voltage_monitor(10.0);
+        let mut events = driver.subscribe();
+        let voltages = driver.read_voltages(&[1, 2]).await;
+        assert!((*voltages[1].1.as_ref().unwrap() - 9.6).abs() < 1e-6);
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DriverEvent::Brownout(Brownout { id: 2, .. })
+        ));
+        assert!(events.try_recv().is_err());
+        let stats = driver.bus_stats();
+        assert_eq!(stats.min_voltage(1), Some(11.8));
+        assert_eq!(stats.min_voltage(3), None);
+    }
+
+    #[tokio::test]
+    async fn identify_blinks_and_turns_led_off() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, vec![]), Status::new(1, vec![])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver
+            .identify(1, Duration::from_millis(100))
+            .await
+            .unwrap();
+        let written = writing_buffer.lock().unwrap();
+        assert_eq!(written[0], Instruction::write_u8(1, LED, 1).serialize());
+        assert_eq!(written[1], Instruction::write_u8(1, LED, 0).serialize());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn identify_runs_in_virtual_time() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        let wall_clock = std::time::Instant::now();
+        let start = tokio::time::Instant::now();
+        driver.identify(1, Duration::from_secs(10)).await.unwrap();
+        driver
+            .identify_by_wiggle(1, Duration::from_secs(10))
+            .await
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(20));
+        assert!(wall_clock.elapsed() < Duration::from_secs(5));
+        assert_eq!(handle.register(1, LED), Some(0));
+        assert_eq!(handle.register_u16(1, GOAL_POSITION), Some(512));
+    }
+
+    #[tokio::test]
+    async fn relax_sequenced_lowers_then_disables_groups_in_order() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(3, vec![0x00, 0x02])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let hips = RelaxGroup::new(vec![3]).lower_to(
+            vec![SyncCommandFloat::new(3, 150.0)],
+            Duration::from_millis(1),
+        );
+        driver
+            .relax_sequenced(
+                vec![RelaxGroup::new(vec![1, 2]), hips],
+                Duration::from_millis(1),
+            )
+            .await
+            .unwrap();
+        let written = writing_buffer.lock().unwrap();
+        assert_eq!(written.len(), 4);
+        let legs_off = Instruction::sync_command(
+            TORQUE_ENABLED,
+            1,
+            vec![SyncCommand::new(1, 0), SyncCommand::new(2, 0)],
+        );
+        assert_eq!(written[0], legs_off.serialize());
+        assert_eq!(
+            written[1],
+            Instruction::read_instruction(3, PRESENT_POSITION, 2).serialize()
+        );
+        assert!(matches!(
+            &decode_traffic(&written)[2],
+            SentInstruction::SyncWrite { addr: GOAL_POSITION, entries } if entries[0].0 == 3
+        ));
+        let hips_off = Instruction::sync_command(TORQUE_ENABLED, 1, vec![SyncCommand::new(3, 0)]);
+        assert_eq!(written[3], hips_off.serialize());
+    }
+
+    #[tokio::test]
+    async fn goal_position_batch_converts_degrees() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let batch = GoalPositionBatch::new()
+            .add(1, Degrees(100.0))
+            .add(2, Ticks(512));
+        driver.sync_write_batch(batch).await.unwrap();
+        let expected = Instruction::sync_command(
+            GOAL_POSITION,
+            2,
+            vec![SyncCommand::new(1, 341), SyncCommand::new(2, 512)],
+        );
+        assert_eq!(*writing_buffer.lock().unwrap(), vec![expected.serialize()]);
+    }
+
+    #[tokio::test]
+    async fn close_runs_shutdown_sequence() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_shutdown_sequence(vec![vec![1, 2]], Duration::ZERO);
+        driver.close().await.unwrap();
+        let torque_off = Instruction::sync_command(
+            TORQUE_ENABLED,
+            1,
+            vec![SyncCommand::new(1, 0), SyncCommand::new(2, 0)],
+        );
+        assert_eq!(
+            *writing_buffer.lock().unwrap(),
+            vec![torque_off.serialize()]
+        );
+    }
+
+    #[tokio::test]
+    async fn usb2ax_sync_read_positions() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(0xFD, vec![0x00, 0x02, 0xFF, 0x03])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_usb2ax_sync_read(true);
+        let positions = driver.read_positions(&[1, 2]).await;
+        assert_eq!(positions[0].0, 1);
+        assert_eq!(*positions[0].1.as_ref().unwrap(), 512);
+        assert_eq!(positions[1].0, 2);
+        assert_eq!(*positions[1].1.as_ref().unwrap(), 1023);
+        let written = writing_buffer.lock().unwrap();
+        assert_eq!(written.len(), 1);
+        assert_eq!(
+            written[0],
+            Instruction::usb2ax_sync_read(36, 2, &[1, 2]).serialize()
+        );
+    }
+
+    #[tokio::test]
+    async fn sweeps_read_single_byte_registers() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Ok(Status::new(0xFD, vec![40, 55])),
+                Ok(Status::new(1, vec![120])),
+                Err(DynamixelDriverError::Timeout),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_usb2ax_sync_read(true);
+        let temperatures = driver.read_temperatures(&[1, 2]).await;
+        assert_eq!(temperatures[0].1.as_ref().unwrap(), &40);
+        assert_eq!(temperatures[1].1.as_ref().unwrap(), &55);
+        driver.set_usb2ax_sync_read(false);
+        let voltages = driver.read_voltages(&[1, 2]).await;
+        assert_eq!(voltages[0].1.as_ref().unwrap(), &12.0);
+        assert!(matches!(voltages[1].1, Err(DynamixelDriverError::Timeout)));
+        assert_eq!(
+            writing_buffer.lock().unwrap()[0],
+            Instruction::usb2ax_sync_read(PRESENT_TEMPERATURE, 1, &[1, 2]).serialize()
+        );
+    }
+
+    #[tokio::test]
+    async fn bulk_read_collects_statuses_in_order() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Ok(Status::new(1, vec![0x00, 0x02])),
+                Ok(Status::new(3, vec![40])),
+                Err(StatusError::check_error(1 << 2).unwrap_err()),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let reads = [
+            BulkRead::new(1, PRESENT_POSITION, 2),
+            BulkRead::new(2, PRESENT_VOLTAGE, 1),
+            BulkRead::new(3, PRESENT_TEMPERATURE, 1),
+            BulkRead::new(4, PRESENT_TEMPERATURE, 1),
+        ];
+        let results = driver.bulk_read(&reads).await.unwrap();
+        assert_eq!(results[0].1.as_ref().unwrap().as_u16().unwrap(), 512);
+        assert!(matches!(
+            results[1],
+            (2, Err(DynamixelDriverError::Timeout))
+        ));
+        assert_eq!(results[2].1.as_ref().unwrap().as_u8().unwrap(), 40);
+        assert!(matches!(
+            &results[3],
+            (4, Err(DynamixelDriverError::StatusError(error))) if error.overheating_error
+        ));
+        assert_eq!(
+            *writing_buffer.lock().unwrap(),
+            vec![Instruction::bulk_read(&reads).serialize()]
+        );
+
+        driver.configure_servo(ServoDescription {
+            model: Some(ServoModel::Ax12A),
+            ..ServoDescription::new(5)
+        });
+        assert!(matches!(
+            driver.bulk_read(&[BulkRead::new(5, 36, 2)]).await,
+            Err(DynamixelDriverError::Unsupported { id: 5, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn usb2ax_sync_read_falls_back_to_individual_reads() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Err(DynamixelDriverError::Timeout),
+                Ok(Status::new(1, vec![0x00, 0x02])),
+                Err(DynamixelDriverError::Timeout),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_usb2ax_sync_read(true);
+        let positions = driver.read_positions(&[1, 2]).await;
+        assert_eq!(*positions[0].1.as_ref().unwrap(), 512);
+        assert!(matches!(positions[1].1, Err(DynamixelDriverError::Timeout)));
+        assert_eq!(writing_buffer.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn read_positions_reports_per_id_results() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Ok(Status::new(1, vec![0, 2])),
+                Err(DynamixelDriverError::Timeout),
+                Ok(Status::new(3, vec![255, 3])),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let positions = driver.read_positions(&[1, 2, 3]).await;
+        assert_eq!(positions.len(), 3);
+        assert_eq!(positions[0].0, 1);
+        assert_eq!(*positions[0].1.as_ref().unwrap(), 512);
+        assert_eq!(positions[1].0, 2);
+        assert!(matches!(positions[1].1, Err(DynamixelDriverError::Timeout)));
+        assert_eq!(positions[2].0, 3);
+        assert_eq!(*positions[2].1.as_ref().unwrap(), 1023);
+    }
+
+    #[tokio::test]
+    async fn stress_test_counts_errors() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mut results = vec![Ok(Status::new(1, vec![0; 4]))];
+        results.extend((0..100).map(|index| match index % 4 {
+            0 => Ok(Status::new(1, vec![0; 4])),
+            1 => Ok(Status::new(1, vec![])),
+            2 => Err(DynamixelDriverError::ChecksumError(1, 2)),
+            _ => Ok(Status::new(1, vec![])),
+        }));
+        let mock_port = MockFramedDriver::with_results(results, writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let report = driver
+            .stress_test(1, Duration::from_millis(20), 4)
+            .await
+            .unwrap();
+        assert!(report.transactions > 0);
+        assert!(report.checksum_errors > 0);
+        assert!(report.latency_percentile(99.0).is_some());
+        let written = writing_buffer.lock().unwrap();
+        assert_eq!(
+            written[0],
+            Instruction::read_instruction(1, 24, 4).serialize()
+        );
+        assert_eq!(
+            written[1],
+            Instruction::read_instruction(1, 0, 4).serialize()
+        );
+        assert_eq!(written[2], Instruction::write(1, 24, &[0; 4]).serialize());
+    }
+
+    #[tokio::test]
+    async fn sync_spot_check_rotates_through_batch() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Ok(Status::new(1, vec![0, 2])),
+                Ok(Status::new(2, vec![0, 1])),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_sync_spot_check(true);
+        for _ in 0..2 {
+            driver
+                .sync_write_position(vec![(1_u8, 512_u32), (2, 512)])
+                .await
+                .unwrap();
+        }
+        let written = writing_buffer.lock().unwrap();
+        assert_eq!(written.len(), 4);
+        assert_eq!(
+            written[1],
+            Instruction::read_instruction(1, GOAL_POSITION, 2).serialize()
+        );
+        assert_eq!(
+            written[3],
+            Instruction::read_instruction(2, GOAL_POSITION, 2).serialize()
+        );
+        let stats = driver.bus_stats();
+        assert_eq!(stats.spot_checks, 2);
+        assert_eq!(stats.spot_check_failures, 1);
+    }
+
+    #[tokio::test]
+    async fn sync_write_verified_reads_back_registers() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Ok(Status::new(1, vec![0, 2])),
+                Ok(Status::new(2, vec![0, 1])),
+                Err(DynamixelDriverError::Timeout),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let outcome = driver
+            .sync_write_position_verified(
+                vec![(1_u8, 512_u32), (2, 512), (3, 512)],
+                SyncVerification::ReadBack,
+            )
+            .await
+            .unwrap();
+        assert!(!outcome.is_success());
+        assert_eq!(outcome.succeeded_ids(), vec![1]);
+        assert_eq!(outcome.failed_ids(), vec![2, 3]);
+        assert!(matches!(
+            outcome.results()[1].1,
+            Err(DynamixelDriverError::VerificationError {
+                id: 2,
+                addr: GOAL_POSITION,
+                expected: 512,
+                actual: 256
+            })
+        ));
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        // sync write followed by one read per servo
+        assert_eq!(writing_buffer_guard.len(), 4);
+        assert_eq!(
+            writing_buffer_guard.remove(1),
+            vec![255, 255, 1, 4, 2, 30, 2, 216]
+        );
+    }
+
+    #[tokio::test]
+    async fn sync_write_verified_pings() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, vec![]), Status::new(2, vec![])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let outcome = driver
+            .sync_write_torque_verified(vec![(1, true), (2, true)], SyncVerification::Ping)
+            .await
+            .unwrap();
+        assert!(outcome.is_success());
+        assert_eq!(outcome.results().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn wait_until_reached_polls_position() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                Status::new(1, vec![0, 0]),
+                Status::new(1, vec![0, 1]),
+                Status::new(1, vec![85, 1]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_motion_poll_interval(Duration::from_millis(1));
+        driver
+            .wait_until_reached(1, 100.0, 1.0, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(writing_buffer.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn wait_until_reached_times_out() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            (0..1000).map(|_| Status::new(1, vec![0, 0])).collect(),
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_motion_poll_interval(Duration::from_millis(1));
+        assert!(matches!(
+            driver
+                .wait_until_reached(1, 100.0, 1.0, Duration::from_millis(20))
+                .await
+                .unwrap_err(),
+            DynamixelDriverError::MotionTimeout(1)
+        ));
+    }
+
+    #[tokio::test]
+    async fn wait_until_stopped_polls_moving_flag() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                Status::new(1, vec![1]),
+                Status::new(1, vec![1]),
+                Status::new(1, vec![0]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_motion_poll_interval(Duration::from_millis(1));
+        driver
+            .wait_until_stopped(1, Duration::from_secs(1))
+            .await
+            .unwrap();
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(writing_buffer_guard.len(), 3);
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![255, 255, 1, 4, 2, 46, 1, 201]
+        );
+    }
+
+    #[tokio::test]
+    async fn move_to_and_wait_reaches_goal() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                Status::new(1, vec![]),
+                Status::new(1, vec![0, 1]),
+                Status::new(1, vec![1]),
+                Status::new(1, vec![85, 1]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_motion_poll_interval(Duration::from_millis(1));
+        driver
+            .move_to_and_wait(1, 100.0, 1.0, Duration::from_secs(1))
+            .await
+            .unwrap();
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(writing_buffer_guard.len(), 4);
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![255, 255, 1, 5, 3, 30, 85, 1, 130]
+        );
+    }
+
+    #[tokio::test]
+    async fn move_to_and_wait_detects_stall() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                Status::new(1, vec![]),
+                Status::new(1, vec![0, 1]),
+                Status::new(1, vec![0]),
+                Status::new(1, vec![0, 1]),
+                Status::new(1, vec![0]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_motion_poll_interval(Duration::from_millis(1));
+        assert!(matches!(
+            driver
+                .move_to_and_wait(1, 100.0, 1.0, Duration::from_secs(1))
+                .await
+                .unwrap_err(),
+            DynamixelDriverError::MotionStalled { id: 1, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn move_to_and_wait_reports_overload() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let overload = StatusError::check_error(1 << 5).unwrap_err();
+        let mock_port = MockFramedDriver::with_results(
+            vec![Ok(Status::new(1, vec![])), Err(overload)],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        match driver
+            .move_to_and_wait(1, 100.0, 1.0, Duration::from_secs(1))
+            .await
+            .unwrap_err()
+        {
+            DynamixelDriverError::StatusError(status_error) => {
+                assert!(status_error.overload_error)
+            }
+            other => panic!("unexpected error {}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn move_over_writes_speed_and_goal() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                Status::new(1, vec![0, 0]),
+                Status::new(1, vec![]),
+                Status::new(1, vec![]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver
+            .move_over(1, 66.6, Duration::from_secs(1))
+            .await
+            .unwrap();
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(writing_buffer_guard.len(), 3);
+        assert_eq!(
+            writing_buffer_guard.remove(1),
+            vec![255, 255, 1, 5, 3, 32, 100, 0, 114]
+        );
+    }
+
+    #[tokio::test]
+    async fn move_over_group_synchronizes_speeds() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, vec![0, 0]), Status::new(2, vec![0, 0])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver
+            .move_over_group(
+                vec![(1, 66.6).into(), (2, 33.3).into()],
+                Duration::from_secs(1),
+            )
+            .await
+            .unwrap();
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        // two position reads followed by a single sync write of position and speed
+        assert_eq!(writing_buffer_guard.len(), 3);
+        assert_eq!(
+            writing_buffer_guard.remove(2),
+            vec![255, 255, 254, 14, 131, 30, 4, 1, 227, 0, 100, 0, 2, 114, 0, 50, 0, 96]
+        );
+    }
+
+    #[tokio::test]
+    async fn move_profiled_streams_waypoints() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mut responses = vec![Status::new(1, vec![85, 1])];
+        responses.extend((0..4).map(|_| Status::new(1, vec![])));
+        let mock_port = MockFramedDriver::new(responses, writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_profile_update_interval(Duration::from_millis(1));
+        driver
+            .move_profiled(1, 200.0, Duration::from_millis(4), VelocityProfile::SCurve)
+            .await
+            .unwrap();
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        // position read followed by one goal write per waypoint
+        assert_eq!(writing_buffer_guard.len(), 5);
+        // 200 degrees => 682 ticks
+        assert_eq!(
+            writing_buffer_guard.pop().unwrap(),
+            vec![255, 255, 1, 5, 3, 30, 170, 2, 44]
+        );
+    }
+
+    #[cfg(feature = "trajectory")]
+    #[tokio::test]
+    async fn pose_buffer_streams_committed_poses() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1, 2]);
+        let handle = bus.handle();
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        driver.set_profile_update_interval(Duration::from_millis(1));
+        let buffer = PoseBuffer::new();
+        buffer.set_all(&[(1, 100.0).into(), (2, 200.0).into()]);
+        buffer.commit();
+        // only half of the next pose is set when the streamer stops
+        buffer.set(1, 50.0);
+        let streaming = driver.run_pose_buffer(&buffer);
+        assert!(tokio::time::timeout(Duration::from_millis(20), streaming)
+            .await
+            .is_err());
+        assert_eq!(handle.register_u16(1, GOAL_POSITION), Some(341));
+        assert_eq!(handle.register_u16(2, GOAL_POSITION), Some(682));
+    }
+
+    #[cfg(feature = "trajectory")]
+    #[tokio::test(start_paused = true)]
+    async fn trajectories_run_in_virtual_time() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        // 100 degrees per second
+        handle.set_speed(1, 341.0);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        let wall_clock = std::time::Instant::now();
+        let start = tokio::time::Instant::now();
+        let sweep = SinusoidalSweep::new(vec![1], 150.0, 30.0, Duration::from_secs(2)).cycles(3);
+        driver.run_primitive(&sweep).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(6));
+
+        // 90 degrees away, moving takes 0.9 seconds
+        let start = tokio::time::Instant::now();
+        driver.write_position_degrees(1, 60.0).await.unwrap();
+        assert!(driver.read_moving(1).await.unwrap());
+        driver
+            .wait_until_reached(1, 60.0, 1.0, Duration::from_secs(5))
+            .await
+            .unwrap();
+        let moving = start.elapsed();
+        assert!(moving > Duration::from_millis(850) && moving < Duration::from_millis(950));
+        driver
+            .wait_until_stopped(1, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert!(wall_clock.elapsed() < Duration::from_secs(2));
+    }
+
+    #[cfg(feature = "trajectory")]
+    #[tokio::test]
+    async fn return_to_home_streams_until_home() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port =
+            MockFramedDriver::new(vec![Status::new(1, vec![85, 1])], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_profile_update_interval(Duration::from_millis(1));
+        driver
+            .return_to_home(vec![(1, 200.0).into()], Duration::from_micros(3500))
+            .await
+            .unwrap();
+        let written = writing_buffer.lock().unwrap();
+        // position read, goals at 0, 1, 2 and 3ms and the final pose
+        assert_eq!(written.len(), 6);
+        assert_eq!(
+            written[1],
+            Instruction::sync_command(GOAL_POSITION, 2, vec![SyncCommand::new(1, 341)]).serialize()
+        );
+        assert_eq!(
+            written[5],
+            Instruction::sync_command(GOAL_POSITION, 2, vec![SyncCommand::new(1, 682)]).serialize()
+        );
+    }
+
+    #[cfg(feature = "trajectory")]
+    #[tokio::test]
+    async fn mirror_step_writes_followers() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port =
+            MockFramedDriver::new(vec![Status::new(1, vec![85, 1])], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let mirror = Mirror::new(1).follower(2).follower_with(3, true, 300.0);
+        driver.mirror_step(&mirror).await.unwrap();
+        assert_eq!(
+            writing_buffer.lock().unwrap()[1],
+            Instruction::sync_command(
+                GOAL_POSITION,
+                2,
+                vec![SyncCommand::new(2, 341), SyncCommand::new(3, 682)]
+            )
+            .serialize()
+        );
+    }
+
+    #[cfg(feature = "trajectory")]
+    #[tokio::test]
+    async fn record_teach_disables_torque_and_samples() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let responses = (0..1000).map(|_| Status::new(1, vec![85, 1])).collect();
+        let mock_port = MockFramedDriver::new(responses, writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let recording = driver
+            .record_teach(
+                &[1],
+                Duration::from_millis(1),
+                // the timer only starts once recording does
+                async { tokio::time::sleep(Duration::from_millis(10)).await },
+            )
+            .await
+            .unwrap();
+        assert!(!recording.frames.is_empty());
+        assert_eq!(recording.frames[0].positions, vec![341.0 / 3.41]);
+        assert_eq!(
+            writing_buffer.lock().unwrap()[0],
+            Instruction::sync_command(TORQUE_ENABLED, 1, vec![SyncCommand::new(1, 0)]).serialize()
+        );
+    }
+
+    #[cfg(feature = "trajectory")]
+    #[tokio::test(start_paused = true)]
+    async fn record_teach_samples_in_virtual_time() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1]);
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        let recording = driver
+            .record_teach(
+                &[1],
+                Duration::from_millis(100),
+                tokio::time::sleep(Duration::from_millis(450)),
+            )
+            .await
+            .unwrap();
+        let times: Vec<u64> = recording.frames.iter().map(|frame| frame.time_ms).collect();
+        assert_eq!(times, vec![0, 100, 200, 300, 400]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limited_sync_write_uses_tokio_clock() {
+        let bus = crate::test_utils::SimulatedBus::with_servos(&[1, 2]);
+        let handle = bus.handle();
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        let mut limiter = VelocityLimiter::new(100.0);
+        limiter.reset(1, 100.0, tokio::time::Instant::now().into_std());
+        tokio::time::advance(Duration::from_millis(100)).await;
+        driver
+            .sync_write_position_degrees_limited(
+                &mut limiter,
+                vec![SyncCommandFloat::new(1, 200.0)],
+            )
+            .await
+            .unwrap();
+        driver.write_position_degrees(2, 110.0).await.unwrap();
+        assert_eq!(
+            handle.register_u16(1, GOAL_POSITION),
+            handle.register_u16(2, GOAL_POSITION)
+        );
+    }
+
+    #[tokio::test]
+    async fn named_joints_resolve_to_ids() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(vec![Status::new(1, vec![])], writing_buffer.clone());
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.configure_servo(ServoDescription::new(1).with_name("left_knee"));
+        assert_eq!(driver.joint_id("left_knee").unwrap(), 1);
+        driver
+            .write_position_degrees_named("left_knee", 100.0)
+            .await
+            .unwrap();
+        assert!(matches!(
+            driver
+                .write_position_degrees_named("right_knee", 100.0)
+                .await
+                .unwrap_err(),
+            DynamixelDriverError::UnknownJoint(name) if name == "right_knee"
+        ));
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(writing_buffer_guard.len(), 1);
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![255, 255, 1, 5, 3, 30, 85, 1, 130]
+        );
+    }
+
+    #[tokio::test]
+    async fn apply_config_to_bus_verifies_each_register() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![
+                // servo 1: read original, write, read back
+                Status::new(1, vec![250]),
+                Status::new(1, vec![]),
+                Status::new(1, vec![0]),
+                // servo 2: read original, write, read back a different value
+                Status::new(2, vec![250]),
+                Status::new(2, vec![]),
+                Status::new(2, vec![250]),
+                // rollback acknowledgements
+                Status::new(2, vec![]),
+                Status::new(1, vec![]),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let config = ServoConfig {
+            return_delay_time: Some(0),
+            ..Default::default()
+        };
+        let mut events = vec![];
+        let error = driver
+            .apply_config_to_bus(&[(1, config.clone()), (2, config)], true, |event| {
+                events.push(event)
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            DynamixelDriverError::VerificationError {
+                id: 2,
+                addr: 5,
+                expected: 0,
+                actual: 250
+            }
+        ));
+        assert_eq!(
+            events,
+            vec![
+                ConfigProgress::Applying {
+                    id: 1,
+                    index: 0,
+                    total: 2
+                },
+                ConfigProgress::Applied { id: 1 },
+                ConfigProgress::Applying {
+                    id: 2,
+                    index: 1,
+                    total: 2
+                },
+                ConfigProgress::RolledBack { id: 2 },
+                ConfigProgress::RolledBack { id: 1 },
+            ]
+        );
+        // last write restores the original return delay of servo 1
+        assert_eq!(
+            writing_buffer.lock().unwrap().pop().unwrap(),
+            vec![255, 255, 1, 4, 3, 5, 250, 248]
+        );
+    }
+
+    #[tokio::test]
+    async fn stale_write_ack_is_not_read_as_value() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, vec![]), Status::new(1, vec![0, 2])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        assert!(matches!(
+            driver.read_position(1).await.unwrap_err(),
+            DynamixelDriverError::ResponseLengthMismatch {
+                id: 1,
+                expected: 2,
+                actual: 0
+            }
+        ));
+        assert_eq!(driver.read_position(1).await.unwrap(), 512);
+    }
+
+    #[tokio::test]
+    async fn write_many_reports_per_op_results() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Ok(Status::new(1, vec![])),
+                Err(DynamixelDriverError::Timeout),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let results = driver
+            .write_many(
+                vec![
+                    WriteOp::u16(1, GOAL_POSITION, 512),
+                    WriteOp::u8(2, TORQUE_ENABLED, 1),
+                ],
+                false,
+            )
+            .await
+            .unwrap();
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(DynamixelDriverError::Timeout)));
+        assert_eq!(writing_buffer.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn write_many_registered_ends_with_action() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, vec![]), Status::new(2, vec![])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let results = driver
+            .write_many(
+                vec![
+                    WriteOp::u16(1, GOAL_POSITION, 512),
+                    WriteOp::u16(2, GOAL_POSITION, 512),
+                ],
+                true,
+            )
+            .await
+            .unwrap();
+        assert!(results.iter().all(|result| result.is_ok()));
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(
+            writing_buffer_guard.remove(0),
+            vec![0xFF, 0xFF, 0x01, 0x05, 0x04, 0x1E, 0x00, 0x02, 0xD5]
+        );
+        assert_eq!(
+            writing_buffer_guard.pop().unwrap(),
+            vec![0xFF, 0xFF, 0xFE, 0x02, 0x05, 0xFA]
+        );
+    }
+
+    #[tokio::test]
+    async fn write_transaction_restores_snapshot_on_failure() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                // snapshot
+                Ok(Status::new(1, vec![250])),
+                Ok(Status::new(2, vec![250])),
+                // servo 1 written and verified
+                Ok(Status::new(1, vec![])),
+                Ok(Status::new(1, vec![0])),
+                // servo 2 doesn't answer
+                Err(DynamixelDriverError::Timeout),
+                // restores
+                Ok(Status::new(2, vec![])),
+                Ok(Status::new(1, vec![])),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        let error = driver
+            .write_transaction(vec![WriteOp::u8(1, 5, 0), WriteOp::u8(2, 5, 0)])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DynamixelDriverError::Timeout));
+        let mut writing_buffer_guard = writing_buffer.lock().unwrap();
+        assert_eq!(writing_buffer_guard.len(), 7);
+        assert_eq!(
+            writing_buffer_guard.pop().unwrap(),
+            vec![255, 255, 1, 4, 3, 5, 250, 248]
+        );
+    }
+
+    #[tokio::test]
+    async fn write_transaction_reports_incomplete_rollback() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Ok(Status::new(1, vec![250])),
+                Ok(Status::new(1, vec![])),
+                Ok(Status::new(1, vec![1])),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        match driver
+            .write_transaction(vec![WriteOp::u8(1, 5, 0)])
+            .await
+            .unwrap_err()
+        {
+            DynamixelDriverError::RollbackIncomplete { cause, failed } => {
+                assert!(matches!(
+                    *cause,
+                    DynamixelDriverError::VerificationError { id: 1, addr: 5, .. }
+                ));
+                assert_eq!(failed, vec![(1, 5)]);
+            }
+            other => panic!("unexpected error {}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn is_at_respects_model_resolution() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::new(
+            vec![Status::new(1, vec![85, 1]), Status::new(1, vec![85, 1])],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        // 341 ticks is 100.0 degrees, a zero tolerance still accepts one AX step
+        assert!(driver.is_at(1, 100.2, 0.0).await.unwrap());
+        assert!(!driver.is_at(1, 100.5, 0.0).await.unwrap());
+        assert!(driver.position_matches(1, 100.0, 100.5, 1.0));
+        driver.configure_servo(ServoDescription {
+            model: Some(ServoModel::Mx28),
+            ..ServoDescription::new(2)
+        });
+        assert!(!driver.position_matches(2, 100.0, 100.2, 0.0));
+    }
+
+    #[test]
+    fn speed_decoding() {
+        assert_eq!(speed_from_raw(0), 0.0);
+        assert!((speed_from_raw(100) - 11.1).abs() < 1e-4);
+        assert!((speed_from_raw(1024 + 100) + 11.1).abs() < 1e-4);
+    }
+
+    #[tokio::test]
+    async fn checksum_retry_policy_retransmits_once() {
+        let writing_buffer = Arc::new(Mutex::new(vec![]));
+        let mock_port = MockFramedDriver::with_results(
+            vec![
+                Err(DynamixelDriverError::ChecksumError(1, 2)),
+                Ok(Status::new(1, vec![40])),
+                Err(DynamixelDriverError::ChecksumError(1, 2)),
+                Err(DynamixelDriverError::ChecksumError(1, 2)),
+            ],
+            writing_buffer.clone(),
+        );
+        let mut driver = DynamixelDriver::with_driver(Box::new(mock_port));
+        driver.set_checksum_policy(ChecksumPolicy::Retry);
+        assert_eq!(driver.read_temperature(1).await.unwrap(), 40);
+        assert!(matches!(
+            driver.read_temperature(1).await.unwrap_err(),
+            DynamixelDriverError::ChecksumError(1, 2)
+        ));
+        assert_eq!(writing_buffer.lock().unwrap().len(), 4);
+        assert_eq!(driver.bus_stats().checksum_retries, 2);
     }
 }
@@ -0,0 +1,15 @@
+//! Log macros that forward to `tracing` when the `tracing` feature is enabled
+//! and compile to nothing otherwise, still type checking their arguments.
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{info, trace, warn};
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! discard {
+    ($($arg:tt)*) => {{
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use {discard as info, discard as trace, discard as warn};
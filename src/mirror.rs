@@ -0,0 +1,80 @@
+use crate::instructions::SyncCommandFloat;
+
+/// Followers tracking the present position of a leader servo, run with
+/// [`crate::DynamixelDriver::run_mirror`]
+///
+/// Positions are mirrored in joint degrees, so offsets and inversion declared with
+/// [`crate::DynamixelDriver::configure_servo`] apply to leader and followers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mirror {
+    pub(crate) leader: u8,
+    followers: Vec<Follower>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Follower {
+    id: u8,
+    inverted: bool,
+    offset: f32,
+}
+
+impl Mirror {
+    pub fn new(leader: u8) -> Mirror {
+        Mirror {
+            leader,
+            followers: vec![],
+        }
+    }
+
+    /// Follower copying the leader's position
+    pub fn follower(self, id: u8) -> Self {
+        self.follower_with(id, false, 0.0)
+    }
+
+    /// Follower at the leader's position negated when `inverted`, plus `offset` degrees
+    ///
+    /// Dual-servo joints mounted back to back use inversion, with an offset of 300 degrees
+    /// an unconfigured AX servo mirrors around its center.
+    pub fn follower_with(mut self, id: u8, inverted: bool, offset: f32) -> Self {
+        self.followers.push(Follower {
+            id,
+            inverted,
+            offset,
+        });
+        self
+    }
+
+    /// Goal positions of the followers for a leader position in joint degrees
+    pub fn goals(&self, leader_position: f32) -> Vec<SyncCommandFloat> {
+        self.followers
+            .iter()
+            .map(|follower| {
+                let position = if follower.inverted {
+                    -leader_position
+                } else {
+                    leader_position
+                };
+                SyncCommandFloat::new(follower.id, position + follower.offset)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn followers_apply_inversion_and_offset() {
+        let mirror = Mirror::new(1)
+            .follower(2)
+            .follower_with(3, true, 300.0)
+            .follower_with(4, false, -10.0);
+        let goals: Vec<(u8, f32)> = mirror
+            .goals(100.0)
+            .into_iter()
+            .map(|goal| (goal.id(), goal.value()))
+            .collect();
+        assert_eq!(goals, vec![(2, 100.0), (3, 200.0), (4, 90.0)]);
+    }
+}
@@ -0,0 +1,241 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+use crate::instructions::DynamixelDriverError;
+
+/// Control table features that only some servo models have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Compliance margins and slopes of the AX series
+    Compliance,
+    /// PID gains that replace compliance on the MX series
+    PidGains,
+    /// Torque control mode and goal torque of the MX-64 and MX-106
+    TorqueControl,
+    /// BULK_READ instruction of the MX series
+    BulkRead,
+}
+
+/// Raw PID gains of an MX servo, the datasheet divides them by 8 for P and D and multiplies
+/// by 1000/2048 for I
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PidGains {
+    pub p: u8,
+    pub i: u8,
+    pub d: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServoModel {
+    Ax12A,
+    Ax12W,
+    Ax18A,
+    Mx28,
+    Mx64,
+    Mx106,
+    Other(u16),
+}
+
+impl ServoModel {
+    pub fn from_model_number(model_number: u16) -> ServoModel {
+        match model_number {
+            12 => ServoModel::Ax12A,
+            300 => ServoModel::Ax12W,
+            18 => ServoModel::Ax18A,
+            29 => ServoModel::Mx28,
+            310 => ServoModel::Mx64,
+            320 => ServoModel::Mx106,
+            other => ServoModel::Other(other),
+        }
+    }
+
+    pub fn model_number(&self) -> u16 {
+        match self {
+            ServoModel::Ax12A => 12,
+            ServoModel::Ax12W => 300,
+            ServoModel::Ax18A => 18,
+            ServoModel::Mx28 => 29,
+            ServoModel::Mx64 => 310,
+            ServoModel::Mx106 => 320,
+            ServoModel::Other(model_number) => *model_number,
+        }
+    }
+
+    /// Smallest position step in degrees
+    pub fn resolution_degrees(&self) -> f32 {
+        match self {
+            ServoModel::Mx28 | ServoModel::Mx64 | ServoModel::Mx106 => 360.0 / 4096.0,
+            // AX series and unknown models are assumed to use 1024 steps over 300 degrees
+            _ => 300.0 / 1024.0,
+        }
+    }
+
+    /// Position register steps per degree
+    pub fn steps_per_degree(&self) -> f32 {
+        match self {
+            ServoModel::Mx28 | ServoModel::Mx64 | ServoModel::Mx106 => 4096.0 / 360.0,
+            _ => 3.41,
+        }
+    }
+
+    /// Largest goal position the servo accepts
+    pub fn max_position(&self) -> u16 {
+        match self {
+            ServoModel::Mx28 | ServoModel::Mx64 | ServoModel::Mx106 => 4095,
+            _ => 1023,
+        }
+    }
+
+    /// Size of the control table in bytes, None for unknown models
+    ///
+    /// Protocol 2.0 servos report their own model numbers, which are only known here.
+    pub fn control_table_len(&self) -> Option<u16> {
+        match self {
+            ServoModel::Ax12A | ServoModel::Ax12W | ServoModel::Ax18A => Some(50),
+            ServoModel::Mx28 | ServoModel::Mx64 | ServoModel::Mx106 => Some(74),
+            // XL-320
+            ServoModel::Other(350) => Some(53),
+            // MX series with 2.0 firmware and the X series, up to the last indirect data
+            ServoModel::Other(
+                30 | 311 | 321 | 1000 | 1010 | 1020 | 1030 | 1040 | 1050 | 1060 | 1090 | 1100
+                | 1110 | 1120 | 1130,
+            ) => Some(662),
+            ServoModel::Other(_) => None,
+        }
+    }
+
+    /// Stall torque in N·m at 12V from the datasheet, None for unknown models
+    pub fn stall_torque(&self) -> Option<f32> {
+        match self {
+            ServoModel::Ax12A => Some(1.5),
+            ServoModel::Ax12W => Some(0.2),
+            ServoModel::Ax18A => Some(1.8),
+            ServoModel::Mx28 => Some(2.5),
+            ServoModel::Mx64 => Some(6.0),
+            ServoModel::Mx106 => Some(8.4),
+            ServoModel::Other(_) => None,
+        }
+    }
+
+    /// Unknown models are assumed to support everything
+    pub fn supports(&self, capability: Capability) -> bool {
+        match (capability, self) {
+            (_, ServoModel::Other(_)) => true,
+            (Capability::Compliance, model) => {
+                matches!(
+                    model,
+                    ServoModel::Ax12A | ServoModel::Ax12W | ServoModel::Ax18A
+                )
+            }
+            (Capability::PidGains | Capability::BulkRead, model) => {
+                matches!(
+                    model,
+                    ServoModel::Mx28 | ServoModel::Mx64 | ServoModel::Mx106
+                )
+            }
+            (Capability::TorqueControl, model) => {
+                matches!(model, ServoModel::Mx64 | ServoModel::Mx106)
+            }
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServoModel::Ax12A => "AX-12A",
+            ServoModel::Ax12W => "AX-12W",
+            ServoModel::Ax18A => "AX-18A",
+            ServoModel::Mx28 => "MX-28",
+            ServoModel::Mx64 => "MX-64",
+            ServoModel::Mx106 => "MX-106",
+            ServoModel::Other(_) => "unknown",
+        }
+    }
+}
+
+impl FromStr for ServoModel {
+    type Err = DynamixelDriverError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_uppercase().as_str() {
+            "AX-12" | "AX-12A" | "AX-12+" => Ok(ServoModel::Ax12A),
+            "AX-12W" => Ok(ServoModel::Ax12W),
+            "AX-18" | "AX-18A" | "AX-18F" => Ok(ServoModel::Ax18A),
+            "MX-28" => Ok(ServoModel::Mx28),
+            "MX-64" => Ok(ServoModel::Mx64),
+            "MX-106" => Ok(ServoModel::Mx106),
+            other => other
+                .parse::<u16>()
+                .map(ServoModel::from_model_number)
+                .map_err(|_| {
+                    DynamixelDriverError::InvalidConfig(format!("unknown servo model {:?}", name))
+                }),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ServoModel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ServoModel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ServoModel::Other(model_number) => serializer.serialize_str(&model_number.to_string()),
+            model => serializer.serialize_str(model.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolution_per_model() {
+        assert!((ServoModel::Ax12A.resolution_degrees() - 0.293).abs() < 0.001);
+        assert!((ServoModel::Mx64.resolution_degrees() - 0.088).abs() < 0.001);
+    }
+
+    #[test]
+    fn protocol2_models_have_control_tables() {
+        // MX-28 with 2.0 firmware and XM430-W350
+        assert_eq!(
+            ServoModel::from_model_number(30).control_table_len(),
+            Some(662)
+        );
+        assert_eq!(
+            ServoModel::from_model_number(1020).control_table_len(),
+            Some(662)
+        );
+        assert_eq!(
+            ServoModel::from_model_number(29).control_table_len(),
+            Some(74)
+        );
+        assert_eq!(ServoModel::Other(4242).control_table_len(), None);
+    }
+
+    #[test]
+    fn model_number_round_trip() {
+        for model_number in [12, 300, 18, 29, 310, 320, 1020] {
+            assert_eq!(
+                ServoModel::from_model_number(model_number).model_number(),
+                model_number
+            );
+        }
+    }
+
+    #[test]
+    fn parse_model_names() {
+        assert_eq!("AX-12A".parse::<ServoModel>().unwrap(), ServoModel::Ax12A);
+        assert_eq!("ax-12".parse::<ServoModel>().unwrap(), ServoModel::Ax12A);
+        assert_eq!("MX-64".parse::<ServoModel>().unwrap(), ServoModel::Mx64);
+        assert_eq!("320".parse::<ServoModel>().unwrap(), ServoModel::Mx106);
+        assert!("XL-9000".parse::<ServoModel>().is_err());
+    }
+}
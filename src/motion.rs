@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::angle::Angle;
+use crate::instructions::{SyncCommand, SyncCommandFloat};
+use crate::model::ServoModel;
+
+// one unit of moving speed is about 0.111 rpm
+const DEGREES_PER_SECOND_PER_SPEED_UNIT: f32 = 0.111 * 360.0 / 60.0;
+
+/// Goal position and moving speed of one servo in a coordinated move
+///
+/// Both registers are adjacent in the control table so a group of moves
+/// is sent as a single sync write of 4 byte blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JointMove {
+    pub id: u8,
+    pub goal_position: u16,
+    pub moving_speed: u16,
+}
+
+impl JointMove {
+    /// Plans a move between two servo angles in degrees that takes `duration`
+    ///
+    /// The goal is limited to the range of an AX servo.
+    pub fn plan(id: u8, from_degrees: f32, to_degrees: f32, duration: Duration) -> JointMove {
+        JointMove {
+            id,
+            goal_position: Angle::from_degrees(to_degrees).to_ticks_saturating(ServoModel::Ax12A),
+            moving_speed: moving_speed_for(to_degrees - from_degrees, duration),
+        }
+    }
+}
+
+impl From<JointMove> for SyncCommand {
+    fn from(joint_move: JointMove) -> Self {
+        SyncCommand::new(
+            joint_move.id,
+            joint_move.goal_position as u32 | (joint_move.moving_speed as u32) << 16,
+        )
+    }
+}
+
+/// Shape of software generated motion for servos without hardware profiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VelocityProfile {
+    /// Constant acceleration for the first third, cruise, constant deceleration for the last third
+    Trapezoidal,
+    /// Minimum jerk curve with smooth acceleration at both ends
+    SCurve,
+}
+
+impl VelocityProfile {
+    /// Fraction of the distance covered at `progress` (0.0 to 1.0) of the move duration
+    pub fn sample(&self, progress: f32) -> f32 {
+        let t = progress.clamp(0.0, 1.0);
+        match self {
+            VelocityProfile::Trapezoidal => {
+                const ACCELERATION_FRACTION: f32 = 1.0 / 3.0;
+                let max_velocity = 1.0 / (1.0 - ACCELERATION_FRACTION);
+                let acceleration = max_velocity / ACCELERATION_FRACTION;
+                if t < ACCELERATION_FRACTION {
+                    0.5 * acceleration * t * t
+                } else if t <= 1.0 - ACCELERATION_FRACTION {
+                    0.5 * max_velocity * ACCELERATION_FRACTION
+                        + max_velocity * (t - ACCELERATION_FRACTION)
+                } else {
+                    1.0 - 0.5 * acceleration * (1.0 - t) * (1.0 - t)
+                }
+            }
+            VelocityProfile::SCurve => t * t * t * (10.0 - 15.0 * t + 6.0 * t * t),
+        }
+    }
+
+    /// Intermediate goal positions from `from` to `to` taking `duration` when one is sent every `update_interval`
+    pub fn waypoints(
+        &self,
+        from: f32,
+        to: f32,
+        duration: Duration,
+        update_interval: Duration,
+    ) -> Vec<f32> {
+        let steps = (duration.as_secs_f32() / update_interval.as_secs_f32())
+            .ceil()
+            .max(1.0) as usize;
+        (1..=steps)
+            .map(|step| from + (to - from) * self.sample(step as f32 / steps as f32))
+            .collect()
+    }
+}
+
+/// Servos that are relaxed together by [`crate::DynamixelDriver::relax_sequenced`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelaxGroup {
+    pub(crate) ids: Vec<u8>,
+    pub(crate) rest_positions: Vec<SyncCommandFloat>,
+    pub(crate) lowering_duration: Duration,
+}
+
+impl RelaxGroup {
+    pub fn new(ids: Vec<u8>) -> RelaxGroup {
+        RelaxGroup {
+            ids,
+            rest_positions: vec![],
+            lowering_duration: Duration::ZERO,
+        }
+    }
+
+    /// Slowly move the group to rest positions in degrees before torque is disabled
+    pub fn lower_to(mut self, rest_positions: Vec<SyncCommandFloat>, duration: Duration) -> Self {
+        self.rest_positions = rest_positions;
+        self.lowering_duration = duration;
+        self
+    }
+}
+
+impl From<Vec<u8>> for RelaxGroup {
+    fn from(ids: Vec<u8>) -> Self {
+        RelaxGroup::new(ids)
+    }
+}
+
+/// Limits how fast streamed goal positions may change, regardless of what a planner commands
+///
+/// Goals move towards the commanded position by at most `max_speed` degrees per second
+/// since the previous goal of the same joint. Seed joints with their present position
+/// with [`Self::reset`], the first goal of an unseeded joint passes through unchanged.
+#[derive(Debug, Clone)]
+pub struct VelocityLimiter {
+    default_max_speed: f32,
+    max_speeds: HashMap<u8, f32>,
+    last_goals: HashMap<u8, (f32, Instant)>,
+}
+
+impl VelocityLimiter {
+    /// `max_speed` in degrees per second for all joints
+    pub fn new(max_speed: f32) -> VelocityLimiter {
+        VelocityLimiter {
+            default_max_speed: max_speed,
+            max_speeds: HashMap::new(),
+            last_goals: HashMap::new(),
+        }
+    }
+
+    /// Overrides the maximum speed in degrees per second of one joint
+    pub fn set_max_speed(&mut self, id: u8, max_speed: f32) {
+        self.max_speeds.insert(id, max_speed);
+    }
+
+    /// Forgets previous goals and starts limiting from `position` degrees
+    pub fn reset(&mut self, id: u8, position: f32, now: Instant) {
+        self.last_goals.insert(id, (position, now));
+    }
+
+    /// Returns the goal to send this tick for a commanded `goal` in degrees
+    pub fn limit(&mut self, id: u8, goal: f32, now: Instant) -> f32 {
+        let limited = match self.last_goals.get(&id) {
+            Some(&(last_goal, last_time)) => {
+                let max_speed = self
+                    .max_speeds
+                    .get(&id)
+                    .copied()
+                    .unwrap_or(self.default_max_speed);
+                let max_step = max_speed * now.saturating_duration_since(last_time).as_secs_f32();
+                last_goal + (goal - last_goal).clamp(-max_step, max_step)
+            }
+            None => goal,
+        };
+        self.last_goals.insert(id, (limited, now));
+        limited
+    }
+}
+
+pub(crate) fn moving_speed_for(distance_degrees: f32, duration: Duration) -> u16 {
+    let seconds = duration.as_secs_f32();
+    if seconds <= 0.0 {
+        return 1023;
+    }
+    let speed = distance_degrees.abs() / seconds / DEGREES_PER_SECOND_PER_SPEED_UNIT;
+    // 0 means maximum speed without control, so the slowest move is one unit
+    speed.round().clamp(1.0, 1023.0) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn velocity_limiter_caps_step_per_tick() {
+        let start = Instant::now();
+        let mut limiter = VelocityLimiter::new(100.0);
+        limiter.set_max_speed(2, 10.0);
+        limiter.reset(1, 0.0, start);
+        limiter.reset(2, 0.0, start);
+        let tick = start + Duration::from_millis(100);
+        assert!((limiter.limit(1, 50.0, tick) - 10.0).abs() < 1e-4);
+        assert!((limiter.limit(2, -50.0, tick) + 1.0).abs() < 1e-4);
+        let tick = tick + Duration::from_millis(100);
+        assert!((limiter.limit(1, 15.0, tick) - 15.0).abs() < 1e-4);
+        // unseeded joints pass through
+        assert_eq!(limiter.limit(3, 90.0, tick), 90.0);
+    }
+
+    #[test]
+    fn moving_speed_for_duration() {
+        // 66.6 degrees per second
+        assert_eq!(moving_speed_for(66.6, Duration::from_secs(1)), 100);
+        assert_eq!(moving_speed_for(-66.6, Duration::from_secs(2)), 50);
+        assert_eq!(moving_speed_for(0.0, Duration::from_secs(1)), 1);
+        assert_eq!(moving_speed_for(300.0, Duration::from_millis(10)), 1023);
+        assert_eq!(moving_speed_for(10.0, Duration::ZERO), 1023);
+    }
+
+    #[test]
+    fn joints_with_longer_travel_move_faster() {
+        let duration = Duration::from_secs(1);
+        let near = JointMove::plan(1, 100.0, 133.3, duration);
+        let far = JointMove::plan(2, 100.0, 166.6, duration);
+        assert_eq!(near.moving_speed, 50);
+        assert_eq!(far.moving_speed, 100);
+        assert_eq!(far.goal_position, 568);
+    }
+
+    #[test]
+    fn profiles_start_and_end_at_rest() {
+        for profile in [VelocityProfile::Trapezoidal, VelocityProfile::SCurve] {
+            assert_eq!(profile.sample(0.0), 0.0);
+            assert!((profile.sample(1.0) - 1.0).abs() < 1e-6);
+            assert!((profile.sample(0.5) - 0.5).abs() < 1e-6);
+            // slow start compared to linear motion
+            assert!(profile.sample(0.1) < 0.1);
+            assert!(profile.sample(0.9) > 0.9);
+        }
+    }
+
+    #[test]
+    fn profiles_are_monotonic() {
+        for profile in [VelocityProfile::Trapezoidal, VelocityProfile::SCurve] {
+            let samples: Vec<f32> = (0..=100)
+                .map(|i| profile.sample(i as f32 / 100.0))
+                .collect();
+            assert!(samples.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+
+    #[test]
+    fn waypoints_cover_whole_move() {
+        let waypoints = VelocityProfile::Trapezoidal.waypoints(
+            100.0,
+            200.0,
+            Duration::from_millis(100),
+            Duration::from_millis(20),
+        );
+        assert_eq!(waypoints.len(), 5);
+        assert_eq!(*waypoints.last().unwrap(), 200.0);
+        let waypoints = VelocityProfile::SCurve.waypoints(
+            100.0,
+            200.0,
+            Duration::ZERO,
+            Duration::from_millis(20),
+        );
+        assert_eq!(waypoints, vec![200.0]);
+    }
+
+    #[test]
+    fn joint_move_packs_position_and_speed() {
+        let command: SyncCommand = JointMove {
+            id: 3,
+            goal_position: 512,
+            moving_speed: 100,
+        }
+        .into();
+        assert_eq!(command.id(), 3);
+        assert_eq!(command.value(), 0x0064_0200);
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+
+/// Dead reckoning for a servo in wheel (continuous rotation) mode
+///
+/// Present position is meaningless once a servo rotates continuously, so distance is
+/// estimated by integrating wheel speed over time. Feed it measured speed from
+/// [`crate::DynamixelDriver::read_present_speed_rpm`] or the commanded speed.
+#[derive(Debug, Clone)]
+pub struct WheelOdometry {
+    wheel_radius: f32,
+    inverted: bool,
+    distance: f32,
+    last_sample: Option<(Instant, f32)>,
+}
+
+impl WheelOdometry {
+    /// `wheel_radius` in meters, `inverted` for wheels mounted so that CCW rotation drives backwards
+    pub fn new(wheel_radius: f32, inverted: bool) -> WheelOdometry {
+        WheelOdometry {
+            wheel_radius,
+            inverted,
+            distance: 0.0,
+            last_sample: None,
+        }
+    }
+
+    /// Records wheel speed in rpm, positive CCW, measured at `now`
+    ///
+    /// Distance since the previous sample is integrated with the average of both speeds.
+    pub fn update(&mut self, rpm: f32, now: Instant) {
+        if let Some((last_time, last_rpm)) = self.last_sample {
+            let elapsed = now.saturating_duration_since(last_time);
+            self.distance += self.linear_speed((last_rpm + rpm) / 2.0) * elapsed.as_secs_f32();
+        }
+        self.last_sample = Some((now, rpm));
+    }
+
+    /// Distance traveled in meters, negative when driving backwards
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Latest linear speed in meters per second
+    pub fn speed(&self) -> f32 {
+        self.last_sample
+            .map(|(_, rpm)| self.linear_speed(rpm))
+            .unwrap_or(0.0)
+    }
+
+    pub fn reset(&mut self) {
+        self.distance = 0.0;
+        self.last_sample = None;
+    }
+
+    fn linear_speed(&self, rpm: f32) -> f32 {
+        let speed = rpm / 60.0 * std::f32::consts::TAU * self.wheel_radius;
+        if self.inverted {
+            -speed
+        } else {
+            speed
+        }
+    }
+
+    /// Time since the last sample, useful to decide when to poll again
+    pub fn since_last_update(&self, now: Instant) -> Option<Duration> {
+        self.last_sample
+            .map(|(time, _)| now.saturating_duration_since(time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integrates_constant_speed() {
+        // one revolution per second on a wheel with 1m circumference
+        let radius = 1.0 / std::f32::consts::TAU;
+        let mut odometry = WheelOdometry::new(radius, false);
+        let start = Instant::now();
+        odometry.update(60.0, start);
+        odometry.update(60.0, start + Duration::from_secs(2));
+        assert!((odometry.distance() - 2.0).abs() < 1e-4);
+        assert!((odometry.speed() - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn integrates_speed_changes_and_direction() {
+        let radius = 1.0 / std::f32::consts::TAU;
+        let mut odometry = WheelOdometry::new(radius, true);
+        let start = Instant::now();
+        odometry.update(0.0, start);
+        odometry.update(60.0, start + Duration::from_secs(1));
+        // average of 0 and 1 m/s, backwards because the wheel is inverted
+        assert!((odometry.distance() + 0.5).abs() < 1e-4);
+        odometry.reset();
+        assert_eq!(odometry.distance(), 0.0);
+        assert_eq!(odometry.since_last_update(start), None);
+    }
+}
@@ -0,0 +1,68 @@
+/// Control mode of an X series servo, selected by its Operating Mode register
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperatingMode {
+    /// Holds the goal current, torque control
+    Current,
+    /// Holds the goal velocity, wheel mode
+    Velocity,
+    /// Position within a single turn, the factory default
+    Position,
+    /// Position over up to 512 turns
+    ExtendedPosition,
+    /// Extended position with the current limited to the goal current
+    CurrentBasedPosition,
+    /// Drives the motor with the goal PWM, no control loop
+    Pwm,
+}
+
+impl OperatingMode {
+    pub fn from_register(value: u8) -> Option<OperatingMode> {
+        match value {
+            0 => Some(OperatingMode::Current),
+            1 => Some(OperatingMode::Velocity),
+            3 => Some(OperatingMode::Position),
+            4 => Some(OperatingMode::ExtendedPosition),
+            5 => Some(OperatingMode::CurrentBasedPosition),
+            16 => Some(OperatingMode::Pwm),
+            _ => None,
+        }
+    }
+
+    pub fn register_value(self) -> u8 {
+        match self {
+            OperatingMode::Current => 0,
+            OperatingMode::Velocity => 1,
+            OperatingMode::Position => 3,
+            OperatingMode::ExtendedPosition => 4,
+            OperatingMode::CurrentBasedPosition => 5,
+            OperatingMode::Pwm => 16,
+        }
+    }
+}
+
+/// Drive Mode register of an X series servo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DriveMode {
+    /// Positive directions turn CW instead of CCW
+    pub reverse: bool,
+    /// Profile velocity and acceleration are durations in ms instead of rates
+    pub time_based_profile: bool,
+    /// Torque is enabled by the next goal update instead of right away
+    pub torque_on_by_goal_update: bool,
+}
+
+impl DriveMode {
+    pub fn from_register(value: u8) -> DriveMode {
+        DriveMode {
+            reverse: value & (1 << 0) != 0,
+            time_based_profile: value & (1 << 2) != 0,
+            torque_on_by_goal_update: value & (1 << 3) != 0,
+        }
+    }
+
+    pub fn register_value(self) -> u8 {
+        self.reverse as u8
+            | (self.time_based_profile as u8) << 2
+            | (self.torque_on_by_goal_update as u8) << 3
+    }
+}
@@ -0,0 +1,106 @@
+//! Double buffered goals for planners that build whole robot poses, such as gaits.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use crate::instructions::SyncCommandFloat;
+
+#[derive(Debug, Default)]
+struct Buffers {
+    // pose the planner is filling in
+    back: BTreeMap<u8, f32>,
+    // last committed pose, waiting for the next tick
+    committed: Option<Vec<SyncCommandFloat>>,
+    commits: u64,
+}
+
+/// Goal store shared between a planner and [`crate::DynamixelDriver::run_pose_buffer`]
+///
+/// The planner sets joints of the next pose one by one while the driver keeps
+/// transmitting the current one. [`Self::commit`] hands the whole next pose over, the
+/// driver swaps to it at its next tick, so a pose is never sync written half updated.
+/// Joints keep their last value in the next pose until they are set again.
+///
+/// Clones share the same buffers.
+#[derive(Debug, Clone, Default)]
+pub struct PoseBuffer {
+    buffers: Arc<Mutex<Buffers>>,
+}
+
+impl PoseBuffer {
+    pub fn new() -> PoseBuffer {
+        PoseBuffer::default()
+    }
+
+    /// Sets a joint of the next pose in joint degrees
+    pub fn set(&self, id: u8, degrees: f32) {
+        self.lock().back.insert(id, degrees);
+    }
+
+    /// Sets several joints of the next pose
+    pub fn set_all(&self, goals: &[SyncCommandFloat]) {
+        let mut buffers = self.lock();
+        for goal in goals {
+            buffers.back.insert(goal.id(), goal.value());
+        }
+    }
+
+    /// Publishes the next pose, replacing a committed pose that wasn't sent yet
+    pub fn commit(&self) {
+        let mut buffers = self.lock();
+        let pose = buffers
+            .back
+            .iter()
+            .map(|(&id, &degrees)| SyncCommandFloat::new(id, degrees))
+            .collect();
+        buffers.committed = Some(pose);
+        buffers.commits += 1;
+    }
+
+    /// Number of poses committed so far
+    pub fn commits(&self) -> u64 {
+        self.lock().commits
+    }
+
+    /// Committed pose not taken yet, called by the streamer at tick boundaries
+    pub(crate) fn take_committed(&self) -> Option<Vec<SyncCommandFloat>> {
+        self.lock().committed.take()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Buffers> {
+        self.buffers.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_committed_poses_are_taken() {
+        let buffer = PoseBuffer::new();
+        buffer.set(1, 10.0);
+        buffer.set(2, 20.0);
+        assert_eq!(buffer.take_committed(), None);
+        buffer.commit();
+        // later changes belong to the pose after
+        buffer.set(1, 15.0);
+        assert_eq!(
+            buffer.take_committed(),
+            Some(vec![
+                SyncCommandFloat::new(1, 10.0),
+                SyncCommandFloat::new(2, 20.0)
+            ])
+        );
+        assert_eq!(buffer.take_committed(), None);
+        buffer.commit();
+        assert_eq!(
+            buffer.take_committed(),
+            Some(vec![
+                SyncCommandFloat::new(1, 15.0),
+                SyncCommandFloat::new(2, 20.0)
+            ])
+        );
+        assert_eq!(buffer.commits(), 2);
+    }
+}
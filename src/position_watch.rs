@@ -0,0 +1,102 @@
+use futures::channel::mpsc;
+use futures::Stream;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// positions buffered per stream before further changes are held back
+const CHANGES_CAPACITY: usize = 16;
+
+/// Positions of a servo in joint degrees that moved more than a threshold since the
+/// previous item, see [`crate::DynamixelDriver::position_changes`]
+///
+/// The first position read after subscribing is always yielded. The stream ends when the
+/// driver is dropped.
+pub struct PositionChanges {
+    receiver: mpsc::Receiver<f32>,
+}
+
+impl Stream for PositionChanges {
+    type Item = f32;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<f32>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+struct Watcher {
+    threshold: f32,
+    last: Option<f32>,
+    sender: mpsc::Sender<f32>,
+}
+
+/// Subscribers to position changes, fed by every position the driver reads
+#[derive(Default)]
+pub(crate) struct PositionWatchers {
+    watchers: HashMap<u8, Vec<Watcher>>,
+}
+
+impl PositionWatchers {
+    pub fn subscribe(&mut self, id: u8, threshold: f32) -> PositionChanges {
+        let (sender, receiver) = mpsc::channel(CHANGES_CAPACITY);
+        self.watchers.entry(id).or_default().push(Watcher {
+            threshold: threshold.abs(),
+            last: None,
+            sender,
+        });
+        PositionChanges { receiver }
+    }
+
+    pub fn record(&mut self, id: u8, position: f32) {
+        let Some(watchers) = self.watchers.get_mut(&id) else {
+            return;
+        };
+        watchers.retain_mut(|watcher| {
+            let moved = match watcher.last {
+                Some(last) => (position - last).abs() > watcher.threshold,
+                None => true,
+            };
+            if !moved {
+                return !watcher.sender.is_closed();
+            }
+            match watcher.sender.try_send(position) {
+                Ok(()) => {
+                    watcher.last = Some(position);
+                    true
+                }
+                // a slow consumer gets the change on a later read
+                Err(error) => !error.is_disconnected(),
+            }
+        });
+        if watchers.is_empty() {
+            self.watchers.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn yields_only_moves_past_threshold() {
+        let mut watchers = PositionWatchers::default();
+        let changes = watchers.subscribe(1, 2.0);
+        for position in [100.0, 101.0, 99.0, 102.5, 103.0, 100.0] {
+            watchers.record(1, position);
+        }
+        watchers.record(2, 50.0);
+        drop(watchers);
+        let changes: Vec<f32> = changes.collect().await;
+        assert_eq!(changes, vec![100.0, 102.5, 100.0]);
+    }
+
+    #[test]
+    fn dropped_streams_are_removed() {
+        let mut watchers = PositionWatchers::default();
+        drop(watchers.subscribe(1, 1.0));
+        watchers.record(1, 10.0);
+        assert!(watchers.watchers.is_empty());
+    }
+}
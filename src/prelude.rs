@@ -0,0 +1,27 @@
+//! Types most applications need, for a single glob import.
+//!
+//! ```no_run
+//! use dynamixel_driver::prelude::*;
+//!
+//! # #[cfg(feature = "serial")]
+//! # async fn example() -> Result<(), DynamixelDriverError> {
+//! let mut driver = DynamixelDriver::builder("/dev/ttyUSB0").build()?;
+//! driver
+//!     .sync_write_position(vec![SyncCommand::new(1, 512), SyncCommand::new(2, 512)])
+//!     .await?;
+//! println!("{:?}", driver.read_angle(1).await?);
+//! # Ok(())
+//! # }
+//! ```
+
+pub use crate::{
+    Angle, BulkRead, BulkWrite, Degrees, DriveMode, DriverConfig, DriverEvent, DynamixelDriver,
+    DynamixelDriverError, FramedDriver, HardwareErrorStatus, OperatingMode, Protocol, Rpm,
+    ServoDescription, ServoModel, Status, StatusError, SyncCommand, SyncCommandFloat, Ticks,
+    WriteOp,
+};
+
+#[cfg(feature = "serial")]
+pub use crate::DynamixelDriverBuilder;
+#[cfg(feature = "trajectory")]
+pub use crate::MotionPrimitive;
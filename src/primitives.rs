@@ -0,0 +1,228 @@
+//! Reusable motions streamed as goal positions by [`crate::DynamixelDriver::run_primitive`].
+
+use std::time::Duration;
+
+use crate::instructions::SyncCommandFloat;
+use crate::motion::VelocityProfile;
+
+/// Motion described as goal positions in joint degrees over time
+pub trait MotionPrimitive: Send + Sync {
+    /// Length of the motion, None for motions that run until they are cancelled
+    fn duration(&self) -> Option<Duration>;
+
+    /// Goal positions `elapsed` after the start, never sampled past [`Self::duration`]
+    fn sample(&self, elapsed: Duration) -> Vec<SyncCommandFloat>;
+}
+
+/// Swings joints around `center` degrees by `amplitude` degrees
+#[derive(Debug, Clone, PartialEq)]
+pub struct SinusoidalSweep {
+    ids: Vec<u8>,
+    center: f32,
+    amplitude: f32,
+    period: Duration,
+    cycles: Option<u32>,
+}
+
+impl SinusoidalSweep {
+    /// Sweeps until cancelled, use [`Self::cycles`] for a finite motion
+    pub fn new(ids: Vec<u8>, center: f32, amplitude: f32, period: Duration) -> SinusoidalSweep {
+        SinusoidalSweep {
+            ids,
+            center,
+            amplitude,
+            period,
+            cycles: None,
+        }
+    }
+
+    /// Stops back at the center after `cycles` full periods
+    pub fn cycles(mut self, cycles: u32) -> Self {
+        self.cycles = Some(cycles);
+        self
+    }
+}
+
+impl MotionPrimitive for SinusoidalSweep {
+    fn duration(&self) -> Option<Duration> {
+        self.cycles.map(|cycles| self.period * cycles)
+    }
+
+    fn sample(&self, elapsed: Duration) -> Vec<SyncCommandFloat> {
+        let phase = elapsed.as_secs_f32() / self.period.as_secs_f32() * std::f32::consts::TAU;
+        let goal = self.center + self.amplitude * phase.sin();
+        self.ids
+            .iter()
+            .map(|&id| SyncCommandFloat::new(id, goal))
+            .collect()
+    }
+}
+
+/// Poses held one after another, jumping between them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepSequence {
+    steps: Vec<(Vec<SyncCommandFloat>, Duration)>,
+}
+
+impl StepSequence {
+    pub fn new() -> StepSequence {
+        StepSequence::default()
+    }
+
+    /// Appends a pose in joint degrees that is held for `hold`
+    pub fn step(mut self, pose: Vec<SyncCommandFloat>, hold: Duration) -> Self {
+        self.steps.push((pose, hold));
+        self
+    }
+}
+
+impl MotionPrimitive for StepSequence {
+    fn duration(&self) -> Option<Duration> {
+        Some(self.steps.iter().map(|(_, hold)| *hold).sum())
+    }
+
+    fn sample(&self, elapsed: Duration) -> Vec<SyncCommandFloat> {
+        let mut start = Duration::ZERO;
+        for (pose, hold) in &self.steps {
+            start += *hold;
+            if elapsed < start {
+                return pose.clone();
+            }
+        }
+        self.steps
+            .last()
+            .map(|(pose, _)| pose.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Smooth move of several joints from their present positions to a home pose
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnToHome {
+    // id, start and home in joint degrees
+    joints: Vec<(u8, f32, f32)>,
+    duration: Duration,
+    profile: VelocityProfile,
+}
+
+impl ReturnToHome {
+    /// Joints of `home` missing from `from` are commanded straight to their home position
+    pub fn new(
+        from: &[SyncCommandFloat],
+        home: &[SyncCommandFloat],
+        duration: Duration,
+    ) -> ReturnToHome {
+        let joints = home
+            .iter()
+            .map(|goal| {
+                let start = from
+                    .iter()
+                    .find(|present| present.id() == goal.id())
+                    .map(|present| present.value())
+                    .unwrap_or(goal.value());
+                (goal.id(), start, goal.value())
+            })
+            .collect();
+        ReturnToHome {
+            joints,
+            duration,
+            profile: VelocityProfile::SCurve,
+        }
+    }
+
+    /// Defaults to [`VelocityProfile::SCurve`]
+    pub fn profile(mut self, profile: VelocityProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+}
+
+impl MotionPrimitive for ReturnToHome {
+    fn duration(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+
+    fn sample(&self, elapsed: Duration) -> Vec<SyncCommandFloat> {
+        let progress = if self.duration.is_zero() {
+            1.0
+        } else {
+            elapsed.as_secs_f32() / self.duration.as_secs_f32()
+        };
+        let fraction = self.profile.sample(progress);
+        self.joints
+            .iter()
+            .map(|&(id, start, home)| SyncCommandFloat::new(id, start + (home - start) * fraction))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goals(commands: Vec<SyncCommandFloat>) -> Vec<(u8, f32)> {
+        commands
+            .into_iter()
+            .map(|command| (command.id(), command.value()))
+            .collect()
+    }
+
+    #[test]
+    fn sweep_oscillates_around_center() {
+        let sweep = SinusoidalSweep::new(vec![1, 2], 150.0, 30.0, Duration::from_secs(2));
+        assert_eq!(sweep.duration(), None);
+        assert_eq!(
+            goals(sweep.sample(Duration::ZERO)),
+            vec![(1, 150.0), (2, 150.0)]
+        );
+        let peak = sweep.sample(Duration::from_millis(500));
+        assert!((peak[0].value() - 180.0).abs() < 1e-3);
+        let trough = sweep.sample(Duration::from_millis(1500));
+        assert!((trough[1].value() - 120.0).abs() < 1e-3);
+        let sweep = sweep.cycles(3);
+        assert_eq!(sweep.duration(), Some(Duration::from_secs(6)));
+        assert!((sweep.sample(Duration::from_secs(6))[0].value() - 150.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn step_sequence_holds_each_pose() {
+        let sequence = StepSequence::new()
+            .step(vec![(1, 100.0).into()], Duration::from_millis(100))
+            .step(vec![(1, 200.0).into()], Duration::from_millis(50));
+        assert_eq!(sequence.duration(), Some(Duration::from_millis(150)));
+        assert_eq!(goals(sequence.sample(Duration::ZERO)), vec![(1, 100.0)]);
+        assert_eq!(
+            goals(sequence.sample(Duration::from_millis(99))),
+            vec![(1, 100.0)]
+        );
+        assert_eq!(
+            goals(sequence.sample(Duration::from_millis(100))),
+            vec![(1, 200.0)]
+        );
+        assert_eq!(
+            goals(sequence.sample(Duration::from_millis(150))),
+            vec![(1, 200.0)]
+        );
+        assert!(StepSequence::new().sample(Duration::ZERO).is_empty());
+    }
+
+    #[test]
+    fn return_to_home_starts_at_present_and_ends_home() {
+        let home = ReturnToHome::new(
+            &[(1, 100.0).into()],
+            &[(1, 150.0).into(), (2, 90.0).into()],
+            Duration::from_secs(1),
+        )
+        .profile(VelocityProfile::Trapezoidal);
+        assert_eq!(
+            goals(home.sample(Duration::ZERO)),
+            vec![(1, 100.0), (2, 90.0)]
+        );
+        let halfway = home.sample(Duration::from_millis(500));
+        assert!((halfway[0].value() - 125.0).abs() < 1e-3);
+        assert_eq!(
+            goals(home.sample(Duration::from_secs(1))),
+            vec![(1, 150.0), (2, 90.0)]
+        );
+    }
+}
@@ -0,0 +1,423 @@
+//! Packet formats of the Dynamixel protocol generations.
+//!
+//! The driver builds its instructions and the codec frames status packets through
+//! [`Protocol`], so the high level API is written once for every generation. Opcodes
+//! are shared between generations, packets differ in header, field widths and checksum.
+
+use crate::byte_stuffing;
+use crate::crc16::crc16;
+use crate::instructions::{calc_checksum, DynamixelDriverError, Instruction, Result, SyncCommand};
+
+pub(crate) const PING: u8 = 0x01;
+pub(crate) const READ: u8 = 0x02;
+pub(crate) const WRITE: u8 = 0x03;
+pub(crate) const REG_WRITE: u8 = 0x04;
+pub(crate) const ACTION: u8 = 0x05;
+pub(crate) const REBOOT: u8 = 0x08;
+pub(crate) const SYNC_WRITE: u8 = 0x83;
+pub(crate) const BULK_READ: u8 = 0x92;
+pub(crate) const BULK_WRITE: u8 = 0x93;
+// instruction byte of every Protocol 2.0 status packet
+pub(crate) const STATUS: u8 = 0x55;
+
+const BROADCAST_ID: u8 = 0xFE;
+
+/// Packet format of a protocol generation
+pub trait Protocol: std::fmt::Debug + Send + Sync {
+    /// Generation number, 1 or 2
+    fn version(&self) -> u8;
+
+    /// Bytes every packet starts with
+    fn header(&self) -> &'static [u8];
+
+    /// Width in bytes of register addresses and data lengths in instruction parameters
+    fn field_width(&self) -> usize;
+
+    /// Builds a complete instruction packet including the checksum
+    fn instruction(&self, id: u8, opcode: u8, params: &[u8]) -> Instruction;
+
+    /// Bytes of a status packet needed before its length is known, header included
+    fn status_prefix_len(&self) -> usize;
+
+    /// Total size of the status packet starting with `prefix`
+    fn status_len(&self, prefix: &[u8]) -> Result<usize>;
+
+    /// Checks the checksum of a complete status packet
+    fn verify_checksum(&self, packet: &[u8]) -> Result<()>;
+
+    /// Splits a complete status packet into id, error flags and parameters
+    fn parse_status(&self, packet: &[u8]) -> Result<(u8, u8, Vec<u8>)>;
+
+    /// Splits a complete instruction packet into id, opcode and parameters
+    fn parse_instruction(&self, packet: &[u8]) -> Result<(u8, u8, Vec<u8>)>;
+
+    /// Parses one complete instruction packet, validating header, length and checksum
+    ///
+    /// Instructions are framed like status packets, so the status framing methods
+    /// apply to them too.
+    fn decode_instruction(&self, packet: &[u8]) -> Result<Instruction> {
+        if packet.len() < self.status_prefix_len() || !packet.starts_with(self.header()) {
+            return Err(DynamixelDriverError::DecodingError("malformed instruction"));
+        }
+        if self.status_len(&packet[..self.status_prefix_len()])? != packet.len() {
+            return Err(DynamixelDriverError::DecodingError(
+                "instruction length mismatch",
+            ));
+        }
+        self.verify_checksum(packet)?;
+        let (id, opcode, params) = self.parse_instruction(packet)?;
+        Ok(self.instruction(id, opcode, &params))
+    }
+
+    fn ping(&self, id: u8) -> Instruction {
+        self.instruction(id, PING, &[])
+    }
+
+    fn read(&self, id: u8, addr: u16, len: u16) -> Instruction {
+        let mut params = Vec::with_capacity(2 * self.field_width());
+        self.push_field(addr, &mut params);
+        self.push_field(len, &mut params);
+        self.instruction(id, READ, &params)
+    }
+
+    /// Write of consecutive bytes starting at `addr`
+    fn write(&self, id: u8, addr: u16, data: &[u8]) -> Instruction {
+        self.instruction(id, WRITE, &self.addressed(addr, data))
+    }
+
+    /// Write that is held by the servo until an [`Self::action`] is received
+    fn reg_write(&self, id: u8, addr: u16, data: &[u8]) -> Instruction {
+        self.instruction(id, REG_WRITE, &self.addressed(addr, data))
+    }
+
+    fn action(&self, id: u8) -> Instruction {
+        self.instruction(id, ACTION, &[])
+    }
+
+    /// Restarts the servo, only understood by Protocol 2.0 servos
+    fn reboot(&self, id: u8) -> Instruction {
+        self.instruction(id, REBOOT, &[])
+    }
+
+    /// Writes `data_len` little endian bytes of each command's value to its servo
+    fn sync_write(&self, addr: u16, data_len: u16, commands: &[SyncCommand]) -> Instruction {
+        assert!(data_len <= 4, "sync write values are at most 4 bytes wide");
+        let mut params = Vec::with_capacity(2 * self.field_width() + commands.len() * 5);
+        self.push_field(addr, &mut params);
+        self.push_field(data_len, &mut params);
+        for command in commands {
+            params.push(command.id());
+            params.extend_from_slice(&command.value().to_le_bytes()[..data_len as usize]);
+        }
+        self.instruction(BROADCAST_ID, SYNC_WRITE, &params)
+    }
+
+    /// Appends an address or length field at the width of this protocol
+    fn push_field(&self, field: u16, params: &mut Vec<u8>) {
+        params.extend_from_slice(&field.to_le_bytes()[..self.field_width()]);
+    }
+
+    fn addressed(&self, addr: u16, data: &[u8]) -> Vec<u8> {
+        let mut params = Vec::with_capacity(self.field_width() + data.len());
+        self.push_field(addr, &mut params);
+        params.extend_from_slice(data);
+        params
+    }
+}
+
+/// Protocol 1.0 used by the AX, RX and MX series
+///
+/// Packets are `FF FF id length instruction/error params checksum` with one byte
+/// addresses and an inverted sum as checksum.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Protocol1;
+
+impl Protocol for Protocol1 {
+    fn version(&self) -> u8 {
+        1
+    }
+
+    fn header(&self) -> &'static [u8] {
+        &[0xFF, 0xFF]
+    }
+
+    fn field_width(&self) -> usize {
+        1
+    }
+
+    fn instruction(&self, id: u8, opcode: u8, params: &[u8]) -> Instruction {
+        let mut payload = Vec::with_capacity(params.len() + 6);
+        payload.extend_from_slice(self.header());
+        payload.extend_from_slice(&[id, params.len() as u8 + 2, opcode]);
+        payload.extend_from_slice(params);
+        payload.push(calc_checksum(&payload[2..]));
+        Instruction::from_payload(payload)
+    }
+
+    fn status_prefix_len(&self) -> usize {
+        4
+    }
+
+    fn status_len(&self, prefix: &[u8]) -> Result<usize> {
+        // length counts error, params and checksum
+        let len = prefix[3] as usize;
+        if len < 2 {
+            return Err(DynamixelDriverError::HeaderLenTooSmall(len));
+        }
+        Ok(4 + len)
+    }
+
+    fn verify_checksum(&self, packet: &[u8]) -> Result<()> {
+        let (checksum, body) = packet.split_last().expect("status packet is never empty");
+        let expected = calc_checksum(&body[2..]);
+        if expected != *checksum {
+            return Err(DynamixelDriverError::ChecksumError(
+                expected.into(),
+                (*checksum).into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse_status(&self, packet: &[u8]) -> Result<(u8, u8, Vec<u8>)> {
+        Ok((packet[2], packet[4], packet[5..packet.len() - 1].to_vec()))
+    }
+
+    fn parse_instruction(&self, packet: &[u8]) -> Result<(u8, u8, Vec<u8>)> {
+        // same layout with the opcode in place of the error byte
+        self.parse_status(packet)
+    }
+}
+
+/// Protocol 2.0 used by the X series and MX servos with 2.0 firmware
+///
+/// Packets are `FF FF FD 00 id length(2) instruction params crc(2)` with two byte
+/// addresses and lengths. Instruction and parameters are byte stuffed and the CRC-16
+/// covers the whole packet. Statuses answer with instruction `0x55` and the error byte.
+///
+/// ```no_run
+/// # #[cfg(feature = "serial")]
+/// # async fn example() -> Result<(), dynamixel_driver::DynamixelDriverError> {
+/// use dynamixel_driver::{DynamixelDriver, Protocol, Protocol2};
+/// use std::sync::Arc;
+///
+/// let mut driver = DynamixelDriver::builder("/dev/ttyUSB0")
+///     .protocol(Arc::new(Protocol2))
+///     .build()?;
+/// // XM430 present position
+/// let status = driver.send_instruction(Protocol2.read(1, 132, 4)).await?;
+/// println!("position {}", status.as_u32()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Protocol2;
+
+// alert bit of the error byte, set while a hardware error is latched
+const ALERT: u8 = 0x80;
+
+impl Protocol2 {
+    fn unstuffed_body(packet: &[u8]) -> Result<Vec<u8>> {
+        byte_stuffing::unstuff(&packet[7..packet.len() - 2])
+            .ok_or(DynamixelDriverError::DecodingError("invalid byte stuffing"))
+    }
+
+    /// Maps the numbered errors of Protocol 2.0 onto the Protocol 1.0 flags
+    ///
+    /// The alert bit is kept as bit 7, which hardware error is latched can only be
+    /// read from the hardware error status register.
+    pub(crate) fn error_flags(error: u8) -> u8 {
+        const RANGE_ERROR: u8 = 1 << 3;
+        const CHECKSUM_ERROR: u8 = 1 << 4;
+        const INSTRUCTION_ERROR: u8 = 1 << 6;
+        let flags = match error & !ALERT {
+            0 => 0,
+            3 => CHECKSUM_ERROR,
+            // data range, data length and data limit errors
+            4..=6 => RANGE_ERROR,
+            // result fail, unknown instruction and access errors
+            _ => INSTRUCTION_ERROR,
+        };
+        flags | error & ALERT
+    }
+}
+
+impl Protocol for Protocol2 {
+    fn version(&self) -> u8 {
+        2
+    }
+
+    fn header(&self) -> &'static [u8] {
+        &[0xFF, 0xFF, 0xFD, 0x00]
+    }
+
+    fn field_width(&self) -> usize {
+        2
+    }
+
+    fn instruction(&self, id: u8, opcode: u8, params: &[u8]) -> Instruction {
+        let mut body = Vec::with_capacity(params.len() + 1);
+        body.push(opcode);
+        body.extend_from_slice(params);
+        let body = byte_stuffing::stuff(&body);
+        // length counts the stuffed body and the CRC
+        let len = (body.len() + 2) as u16;
+        let mut payload = Vec::with_capacity(body.len() + 9);
+        payload.extend_from_slice(self.header());
+        payload.push(id);
+        payload.extend_from_slice(&len.to_le_bytes());
+        payload.extend_from_slice(&body);
+        let crc = crc16(&payload);
+        payload.extend_from_slice(&crc.to_le_bytes());
+        Instruction::from_payload(payload)
+    }
+
+    fn status_prefix_len(&self) -> usize {
+        7
+    }
+
+    fn status_len(&self, prefix: &[u8]) -> Result<usize> {
+        let len = u16::from_le_bytes([prefix[5], prefix[6]]) as usize;
+        // instruction and CRC
+        if len < 3 {
+            return Err(DynamixelDriverError::HeaderLenTooSmall(len));
+        }
+        Ok(7 + len)
+    }
+
+    fn verify_checksum(&self, packet: &[u8]) -> Result<()> {
+        let (body, crc) = packet.split_at(packet.len() - 2);
+        let expected = crc16(body);
+        let received = u16::from_le_bytes([crc[0], crc[1]]);
+        if expected != received {
+            return Err(DynamixelDriverError::ChecksumError(expected, received));
+        }
+        Ok(())
+    }
+
+    fn parse_status(&self, packet: &[u8]) -> Result<(u8, u8, Vec<u8>)> {
+        match Protocol2::unstuffed_body(packet)?.as_slice() {
+            [STATUS, error, params @ ..] => {
+                Ok((packet[4], Protocol2::error_flags(*error), params.to_vec()))
+            }
+            _ => Err(DynamixelDriverError::DecodingError("not a status packet")),
+        }
+    }
+
+    fn parse_instruction(&self, packet: &[u8]) -> Result<(u8, u8, Vec<u8>)> {
+        match Protocol2::unstuffed_body(packet)?.as_slice() {
+            [opcode, params @ ..] => Ok((packet[4], *opcode, params.to_vec())),
+            [] => Err(DynamixelDriverError::DecodingError("missing instruction")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protocol1_matches_instruction_builders() {
+        let protocol = Protocol1;
+        assert_eq!(
+            protocol.ping(1).serialize(),
+            vec![0xFF, 0xFF, 0x01, 0x02, 0x01, 0xFB]
+        );
+        assert_eq!(
+            protocol.read(1, 43, 1).serialize(),
+            vec![0xFF, 0xFF, 0x01, 0x04, 0x02, 0x2B, 0x01, 0xCC]
+        );
+        assert_eq!(
+            protocol.write(1, 30, &[0x00, 0x02]),
+            Instruction::write_u16(1, 30, 512)
+        );
+        let commands = [SyncCommand::new(1, 10), SyncCommand::new(2, 300)];
+        assert_eq!(
+            protocol.sync_write(30, 2, &commands),
+            Instruction::sync_command(30, 2, commands.to_vec())
+        );
+        assert_eq!(protocol.action(0xFE), Instruction::action(0xFE));
+    }
+
+    #[test]
+    fn protocol1_status_framing() {
+        let protocol = Protocol1;
+        let packet = [0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0xDB];
+        assert_eq!(protocol.status_len(&packet[..4]).unwrap(), 7);
+        protocol.verify_checksum(&packet).unwrap();
+        assert_eq!(protocol.parse_status(&packet).unwrap(), (1, 0, vec![0x20]));
+        assert!(matches!(
+            protocol.verify_checksum(&[0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0x00]),
+            Err(DynamixelDriverError::ChecksumError(0xDB, 0x00))
+        ));
+        assert!(matches!(
+            protocol.status_len(&[0xFF, 0xFF, 0x01, 0x01]),
+            Err(DynamixelDriverError::HeaderLenTooSmall(1))
+        ));
+    }
+
+    // examples from the Protocol 2.0 e-manual
+    #[test]
+    fn protocol2_instructions() {
+        let protocol = Protocol2;
+        assert_eq!(
+            protocol.ping(1).serialize(),
+            vec![0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x03, 0x00, 0x01, 0x19, 0x4E]
+        );
+        let read = protocol.read(1, 132, 4);
+        assert_eq!(
+            read.as_bytes(),
+            &[0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x07, 0x00, 0x02, 0x84, 0x00, 0x04, 0x00, 0x1D, 0x15]
+        );
+        assert_eq!((read.id(), read.opcode()), (1, 0x02));
+        assert_eq!(&*read.params(), &[0x84, 0x00, 0x04, 0x00]);
+        assert_eq!(read.expected_response_len(), 15);
+        assert_eq!(
+            protocol.write(1, 116, &512_u32.to_le_bytes()).serialize(),
+            vec![
+                0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x09, 0x00, 0x03, 0x74, 0x00, 0x00, 0x02, 0x00, 0x00,
+                0xCA, 0x89
+            ]
+        );
+        assert_eq!(protocol.decode_instruction(read.as_bytes()).unwrap(), read);
+    }
+
+    #[test]
+    fn protocol2_stuffs_params() {
+        let protocol = Protocol2;
+        let write = protocol.write(1, 30, &[0xFF, 0xFF, 0xFD, 0x01]);
+        assert_eq!(
+            &write.as_bytes()[5..15],
+            &[0x0A, 0x00, 0x03, 0x1E, 0x00, 0xFF, 0xFF, 0xFD, 0xFD, 0x01]
+        );
+        assert_eq!(&*write.params(), &[0x1E, 0x00, 0xFF, 0xFF, 0xFD, 0x01]);
+        assert_eq!(
+            protocol.decode_instruction(write.as_bytes()).unwrap(),
+            write
+        );
+    }
+
+    #[test]
+    fn protocol2_status_framing() {
+        let protocol = Protocol2;
+        let packet = [
+            0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x08, 0x00, 0x55, 0x00, 0xA6, 0x00, 0x00, 0x00, 0x8C,
+            0xC0,
+        ];
+        assert_eq!(protocol.status_len(&packet[..7]).unwrap(), 15);
+        protocol.verify_checksum(&packet).unwrap();
+        assert_eq!(
+            protocol.parse_status(&packet).unwrap(),
+            (1, 0, vec![0xA6, 0x00, 0x00, 0x00])
+        );
+        let mut corrupted = packet;
+        corrupted[9] = 0xA7;
+        assert!(matches!(
+            protocol.verify_checksum(&corrupted),
+            Err(DynamixelDriverError::ChecksumError(_, 0xC08C))
+        ));
+        // data range error with the alert bit set
+        assert_eq!(Protocol2::error_flags(0x84), 1 << 3 | 1 << 7);
+        assert_eq!(Protocol2::error_flags(0x80), 1 << 7);
+    }
+}
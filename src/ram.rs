@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use crate::instructions::{DynamixelDriverError, Result};
+
+/// First address of the RAM area of the AX control table
+pub(crate) const RAM_START: u8 = 24;
+/// Number of bytes from torque enable to punch
+pub(crate) const RAM_LEN: u8 = 26;
+
+/// Snapshot of the whole RAM area of an AX servo, read in a single transaction
+///
+/// Speed and load keep the raw encoding with bit 10 selecting CW direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RamTable {
+    pub torque_enabled: bool,
+    pub led: bool,
+    pub cw_compliance_margin: u8,
+    pub ccw_compliance_margin: u8,
+    pub cw_compliance_slope: u8,
+    pub ccw_compliance_slope: u8,
+    pub goal_position: u16,
+    pub moving_speed: u16,
+    pub torque_limit: u16,
+    pub present_position: u16,
+    pub present_speed: u16,
+    pub present_load: u16,
+    /// Volts
+    pub present_voltage: f32,
+    /// Degrees Celsius
+    pub present_temperature: u8,
+    pub registered_instruction: bool,
+    pub moving: bool,
+    pub lock: bool,
+    pub punch: u16,
+}
+
+impl RamTable {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<RamTable> {
+        if bytes.len() != RAM_LEN as usize {
+            return Err(DynamixelDriverError::DecodingError("ram table"));
+        }
+        let byte = |addr: u8| bytes[(addr - RAM_START) as usize];
+        let word = |addr: u8| u16::from_le_bytes([byte(addr), byte(addr + 1)]);
+        Ok(RamTable {
+            torque_enabled: byte(24) != 0,
+            led: byte(25) != 0,
+            cw_compliance_margin: byte(26),
+            ccw_compliance_margin: byte(27),
+            cw_compliance_slope: byte(28),
+            ccw_compliance_slope: byte(29),
+            goal_position: word(30),
+            moving_speed: word(32),
+            torque_limit: word(34),
+            present_position: word(36),
+            present_speed: word(38),
+            present_load: word(40),
+            present_voltage: byte(42) as f32 / 10.0,
+            present_temperature: byte(43),
+            registered_instruction: byte(44) != 0,
+            moving: byte(46) != 0,
+            lock: byte(47) != 0,
+            punch: word(48),
+        })
+    }
+}
+
+/// Writable RAM fields, only fields that are set are written
+///
+/// Adjacent fields are merged into a single write instruction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RamWrite {
+    pub torque_enabled: Option<bool>,
+    pub led: Option<bool>,
+    pub cw_compliance_margin: Option<u8>,
+    pub ccw_compliance_margin: Option<u8>,
+    pub cw_compliance_slope: Option<u8>,
+    pub ccw_compliance_slope: Option<u8>,
+    pub goal_position: Option<u16>,
+    pub moving_speed: Option<u16>,
+    pub torque_limit: Option<u16>,
+    /// Locks the EEPROM area until the servo is power cycled
+    pub lock: Option<bool>,
+    pub punch: Option<u16>,
+}
+
+impl RamWrite {
+    /// Runs of adjacent bytes to write as (start address, data)
+    pub(crate) fn runs(&self) -> Vec<(u8, Vec<u8>)> {
+        let mut bytes = BTreeMap::new();
+        let mut byte = |addr: u8, value: Option<u8>| {
+            if let Some(value) = value {
+                bytes.insert(addr, value);
+            }
+        };
+        byte(24, self.torque_enabled.map(u8::from));
+        byte(25, self.led.map(u8::from));
+        byte(26, self.cw_compliance_margin);
+        byte(27, self.ccw_compliance_margin);
+        byte(28, self.cw_compliance_slope);
+        byte(29, self.ccw_compliance_slope);
+        byte(47, self.lock.map(u8::from));
+        for (addr, value) in [
+            (30, self.goal_position),
+            (32, self.moving_speed),
+            (34, self.torque_limit),
+            (48, self.punch),
+        ] {
+            if let Some(value) = value {
+                let [low, high] = value.to_le_bytes();
+                bytes.insert(addr, low);
+                bytes.insert(addr + 1, high);
+            }
+        }
+
+        let mut runs: Vec<(u8, Vec<u8>)> = vec![];
+        for (addr, value) in bytes {
+            match runs.last_mut() {
+                Some((start, data)) if *start as usize + data.len() == addr as usize => {
+                    data.push(value)
+                }
+                _ => runs.push((addr, vec![value])),
+            }
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_ram_table() {
+        let mut bytes = vec![0; RAM_LEN as usize];
+        bytes[0] = 1;
+        bytes[6] = 0x00;
+        bytes[7] = 0x02;
+        bytes[14] = 0x64;
+        bytes[15] = 0x04;
+        bytes[18] = 120;
+        bytes[19] = 40;
+        bytes[22] = 1;
+        let ram = RamTable::from_bytes(&bytes).unwrap();
+        assert!(ram.torque_enabled);
+        assert!(!ram.led);
+        assert_eq!(ram.goal_position, 512);
+        assert_eq!(ram.present_speed, 1124);
+        assert!((ram.present_voltage - 12.0).abs() < 1e-6);
+        assert_eq!(ram.present_temperature, 40);
+        assert!(ram.moving);
+        assert!(RamTable::from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn adjacent_fields_are_merged() {
+        let write = RamWrite {
+            torque_enabled: Some(true),
+            led: Some(false),
+            goal_position: Some(512),
+            moving_speed: Some(100),
+            punch: Some(32),
+            ..Default::default()
+        };
+        assert_eq!(
+            write.runs(),
+            vec![
+                (24, vec![1, 0]),
+                (30, vec![0x00, 0x02, 100, 0]),
+                (48, vec![32, 0]),
+            ]
+        );
+        assert!(RamWrite::default().runs().is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+//! Joint states in the layout of ROS 2 `sensor_msgs/JointState`.
+//!
+//! No ROS client library is pulled in. Fill the message type of r2r, rclrs or your own
+//! bridge from the fields of [`JointState`]:
+//!
+//! ```text
+//! let state = driver.joint_state(&feedback);
+//! let (sec, nanosec) = state.stamp_parts();
+//! msg.header.stamp = Time { sec, nanosec };
+//! msg.name = state.name;
+//! msg.position = state.position;
+//! msg.velocity = state.velocity;
+//! msg.effort = state.effort;
+//! ```
+
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::feedback::GroupFeedback;
+
+/// Joint names and states, one entry per joint in every list
+///
+/// Positions are in radians, velocities in radians per second and efforts in N·m,
+/// efforts of servos without known stall torque are NaN.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointState {
+    /// When the oldest of the joints was read
+    pub stamp: SystemTime,
+    pub frame_id: String,
+    pub name: Vec<String>,
+    pub position: Vec<f64>,
+    pub velocity: Vec<f64>,
+    pub effort: Vec<f64>,
+}
+
+/// Naming and scaling of one joint, see [`JointState::from_feedback`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointInfo {
+    pub name: String,
+    /// Flips velocity and effort of joints mounted inverted
+    pub inverted: bool,
+    /// Stall torque in N·m that load fractions are scaled by
+    pub stall_torque: Option<f32>,
+}
+
+impl JointState {
+    /// Converts every joint of `feedback` that answered at least once
+    pub fn from_feedback(
+        feedback: &GroupFeedback,
+        mut joint: impl FnMut(u8) -> JointInfo,
+    ) -> JointState {
+        let (now, system_now) = (Instant::now(), SystemTime::now());
+        let mut state = JointState {
+            stamp: system_now,
+            frame_id: String::new(),
+            name: vec![],
+            position: vec![],
+            velocity: vec![],
+            effort: vec![],
+        };
+        for id in feedback.ids() {
+            let Some(values) = feedback.get(id) else {
+                continue;
+            };
+            let info = joint(id);
+            let direction = if info.inverted { -1.0 } else { 1.0 };
+            let age = now.saturating_duration_since(values.updated);
+            state.stamp = state.stamp.min(system_now - age);
+            state.name.push(info.name);
+            state.position.push(f64::from(values.position).to_radians());
+            state
+                .velocity
+                .push(direction * f64::from(values.speed) * std::f64::consts::TAU / 60.0);
+            state.effort.push(
+                info.stall_torque
+                    .map(|torque| direction * f64::from(values.load * torque))
+                    .unwrap_or(f64::NAN),
+            );
+        }
+        state
+    }
+
+    /// Stamp as whole seconds and nanoseconds since the Unix epoch, like
+    /// `builtin_interfaces/Time`
+    pub fn stamp_parts(&self) -> (i32, u32) {
+        let since_epoch = self.stamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+        (since_epoch.as_secs() as i32, since_epoch.subsec_nanos())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feedback::JointFeedback;
+
+    #[test]
+    fn converts_units_and_skips_unread_joints() {
+        let mut feedback = GroupFeedback::new(&[1, 2, 3]);
+        let joint = JointFeedback {
+            position: 90.0,
+            speed: 60.0,
+            load: 0.5,
+            updated: Instant::now(),
+        };
+        feedback.update(1, joint);
+        feedback.update(3, joint);
+        let state = JointState::from_feedback(&feedback, |id| JointInfo {
+            name: format!("joint_{id}"),
+            inverted: id == 3,
+            stall_torque: (id == 1).then_some(1.5),
+        });
+        assert_eq!(state.name, vec!["joint_1", "joint_3"]);
+        assert!((state.position[0] - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+        assert!((state.velocity[0] - std::f64::consts::TAU).abs() < 1e-6);
+        assert!((state.velocity[1] + std::f64::consts::TAU).abs() < 1e-6);
+        assert_eq!(state.effort[0], 0.75);
+        assert!(state.effort[1].is_nan());
+    }
+}
@@ -0,0 +1,42 @@
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+use crate::instructions::StatusError;
+
+/// What the driver does on its own when a servo reports overheating or an input
+/// voltage error, regardless of what the application does with the error
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SafetyPolicy {
+    /// Only return the error and publish an alarm
+    #[default]
+    Disabled,
+    /// Turn off torque of the servo reporting the error
+    TorqueOffServo,
+    /// Turn off torque of every servo on the bus with a broadcast write
+    TorqueOffBus,
+}
+
+impl SafetyPolicy {
+    /// Whether `error` should trigger the policy
+    pub(crate) fn is_triggered_by(self, error: &StatusError) -> bool {
+        self != SafetyPolicy::Disabled && (error.overheating_error || error.input_voltage_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_heat_and_voltage_errors_trigger() {
+        let overload = StatusError::from_flags(1 << 5).unwrap();
+        let overheating = StatusError::from_flags(1 << 2).unwrap();
+        let voltage = StatusError::from_flags(1).unwrap();
+        assert!(!SafetyPolicy::Disabled.is_triggered_by(&overheating));
+        assert!(!SafetyPolicy::TorqueOffServo.is_triggered_by(&overload));
+        assert!(SafetyPolicy::TorqueOffServo.is_triggered_by(&overheating));
+        assert!(SafetyPolicy::TorqueOffBus.is_triggered_by(&voltage));
+    }
+}
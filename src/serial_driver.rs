@@ -1,51 +1,118 @@
+use crate::logging::{trace, warn};
 use async_trait::async_trait;
 use bytes::{BufMut, BytesMut};
+#[cfg(feature = "serial")]
 use futures::{SinkExt, StreamExt};
-use std::str;
-use tokio::time::{timeout, Duration};
-use tokio_serial::SerialPortBuilderExt;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+#[cfg(feature = "serial")]
+use tokio::time::timeout;
+use tokio::time::Duration;
+#[cfg(feature = "serial")]
+use tokio_serial::{FlowControl, Parity, SerialPort, SerialPortBuilderExt, SerialStream, StopBits};
 use tokio_util::codec::{Decoder, Encoder};
-use tracing::warn;
+#[cfg(feature = "serial")]
+use tokio_util::codec::{Framed, FramedParts};
 
-use crate::instructions::{calc_checksum, DynamixelDriverError, Instruction, Result, StatusError};
+use crate::debug;
+use crate::events::DriverEvent;
+use crate::instructions::{DynamixelDriverError, Instruction, Result, StatusError, HARDWARE_ALERT};
+use crate::protocol::{Protocol, Protocol1, Protocol2};
+use crate::stats::BusStats;
 
+/// Kind of status packet, used to explain statuses that answer another instruction than
+/// the one a caller expected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseKind {
+    /// No parameters, such as the answer to a write or a Protocol 1.0 ping
+    Acknowledgment,
+    /// Read response with this many bytes
+    Read(usize),
+}
+
+impl std::fmt::Display for ResponseKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResponseKind::Acknowledgment => write!(f, "write acknowledgment"),
+            ResponseKind::Read(len) => write!(f, "{len}-byte read response"),
+        }
+    }
+}
+
+/// Status packet answering an instruction
+///
+/// The serial codec turns statuses with alarm flags into
+/// [`DynamixelDriverError::StatusError`], custom transports can hand them over with
+/// [`Status::with_error`] and the driver does the same. Statuses flagging only the
+/// Protocol 2.0 hardware alert keep their data, the alert stays latched until reboot
+/// and the hardware error status register has to stay readable.
 #[derive(PartialEq, Debug)]
-pub(crate) struct Status {
+pub struct Status {
     id: u8,
+    error: u8,
     params: Vec<u8>,
 }
 
 impl Status {
-    pub(crate) fn new(id: u8, params: Vec<u8>) -> Status {
-        Status { id, params }
+    pub fn new(id: u8, params: Vec<u8>) -> Status {
+        Status::with_error(id, 0, params)
+    }
+
+    pub fn with_error(id: u8, error: u8, params: Vec<u8>) -> Status {
+        Status { id, error, params }
     }
 
     pub fn id(&self) -> u8 {
         self.id
     }
 
-    pub(crate) fn as_u8(&self) -> Result<u8> {
-        self.params
-            .first()
-            .cloned()
-            .ok_or(DynamixelDriverError::DecodingError("Failed unpacking u8"))
-    }
-
-    pub(crate) fn as_u16(&self) -> Result<u16> {
-        Ok(u16::from_le_bytes([
-            *self
-                .params
-                .first()
-                .ok_or(DynamixelDriverError::DecodingError(
-                    "Failed unpacking u16 first element",
-                ))?,
-            *self
-                .params
-                .get(1)
-                .ok_or(DynamixelDriverError::DecodingError(
-                    "Failed unpacking u16 second element",
-                ))?,
-        ]))
+    pub fn params(&self) -> &[u8] {
+        &self.params
+    }
+
+    /// Raw error byte of the status
+    pub fn error_flags(&self) -> u8 {
+        self.error
+    }
+
+    /// Decoded alarm flags, None if the servo reported no error
+    pub fn error(&self) -> Option<StatusError> {
+        StatusError::from_flags(self.error)
+    }
+
+    /// What kind of instruction the status answers, as far as its parameters tell
+    pub fn kind(&self) -> ResponseKind {
+        match self.params.len() {
+            0 => ResponseKind::Acknowledgment,
+            len => ResponseKind::Read(len),
+        }
+    }
+
+    pub fn as_u8(&self) -> Result<u8> {
+        let [value] = self.read_bytes()?;
+        Ok(value)
+    }
+
+    pub fn as_u16(&self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.read_bytes()?))
+    }
+
+    pub fn as_u32(&self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes()?))
+    }
+
+    // first N parameters, longer reads are accepted like before
+    pub(crate) fn read_bytes<const N: usize>(&self) -> Result<[u8; N]> {
+        match self.params.get(..N) {
+            Some(bytes) => Ok(bytes.try_into().expect("slice has N bytes")),
+            None => Err(DynamixelDriverError::UnexpectedResponse {
+                id: self.id,
+                expected: ResponseKind::Read(N),
+                received: self.kind(),
+            }),
+        }
     }
 
     #[cfg(test)]
@@ -66,7 +133,97 @@ impl Status {
     }
 }
 
-pub(crate) struct DynamixelProtocol;
+/// How the driver treats a status packet from a different servo than the one addressed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IdMismatchPolicy {
+    /// Fail the transaction
+    #[default]
+    Strict,
+    /// Drop the packet and keep waiting for the addressed servo
+    Discard,
+    /// Use the packet anyway, only sensible on single servo buses
+    AcceptAny,
+}
+
+/// When the driver clears stale bytes from the transport's receive buffer
+///
+/// Adapters that echo transmitted bytes or servos answering after the timeout leave bytes
+/// behind that would be read as the answer to the next instruction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FlushPolicy {
+    /// Only after a response of unexpected length
+    #[default]
+    Never,
+    /// After every failed transaction
+    OnError,
+    /// Before sending every instruction
+    BeforeTransaction,
+}
+
+/// How the decoder treats status packets with invalid checksums
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ChecksumPolicy {
+    /// Reject the packet
+    #[default]
+    Strict,
+    /// Log a warning and use the packet anyway
+    WarnAndAccept,
+    /// Reject the packet and retransmit the instruction once
+    Retry,
+}
+
+/// Codec framing instructions and status packets, for use with custom transports
+///
+/// Frames Protocol 1.0 unless constructed with [`DynamixelProtocol::with_protocol`].
+#[derive(Debug)]
+pub struct DynamixelProtocol {
+    protocol: Arc<dyn Protocol>,
+    /// Protocol 1.0 and 2.0 framing, picked by the header of each packet
+    mixed_protocols: Option<(Arc<dyn Protocol>, Arc<dyn Protocol>)>,
+    checksum_policy: ChecksumPolicy,
+    stats: BusStats,
+}
+
+impl Default for DynamixelProtocol {
+    fn default() -> Self {
+        DynamixelProtocol::with_protocol(Arc::new(Protocol1))
+    }
+}
+
+impl DynamixelProtocol {
+    pub fn with_protocol(protocol: Arc<dyn Protocol>) -> DynamixelProtocol {
+        DynamixelProtocol {
+            protocol,
+            mixed_protocols: None,
+            checksum_policy: ChecksumPolicy::default(),
+            stats: BusStats::default(),
+        }
+    }
+
+    pub fn set_checksum_policy(&mut self, policy: ChecksumPolicy) {
+        self.checksum_policy = policy;
+    }
+
+    pub fn stats(&self) -> &BusStats {
+        &self.stats
+    }
+
+    /// Decode status packets of both protocol generations instead of only the one
+    /// this codec was constructed with
+    pub fn set_mixed_protocols(&mut self, enabled: bool) {
+        self.mixed_protocols = enabled.then(|| {
+            let protocol1: Arc<dyn Protocol> = Arc::new(Protocol1);
+            let protocol2: Arc<dyn Protocol> = Arc::new(Protocol2);
+            (protocol1, protocol2)
+        });
+    }
+}
 
 impl Decoder for DynamixelProtocol {
     type Item = Status;
@@ -74,14 +231,28 @@ impl Decoder for DynamixelProtocol {
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
         // Official driver decoding loop <https://github.com/ROBOTIS-GIT/DynamixelSDK/blob/720b6e6a40acb8ba79a830207732bb9ef049e175/c/src/dynamixel_sdk/protocol1_packet_handler.c#L207>
-        if src.len() < 4 {
+        let protocol = match &self.mixed_protocols {
+            // the Protocol 2.0 header starts with the Protocol 1.0 one
+            Some((protocol1, protocol2)) => {
+                if src.len() < protocol2.header().len() {
+                    return Ok(None);
+                }
+                if src.starts_with(protocol2.header()) {
+                    protocol2.clone()
+                } else {
+                    protocol1.clone()
+                }
+            }
+            None => self.protocol.clone(),
+        };
+        let prefix_len = protocol.status_prefix_len();
+        if src.len() < prefix_len {
             return Ok(None);
         }
 
-        let id = src[2];
-        let len = src[3] as usize;
-        if !src.starts_with(&[0xFF, 0xFF]) {
-            if let Some(start) = src.windows(2).position(|pos| pos == [0xFF, 0xFF]) {
+        let header = protocol.header();
+        if !src.starts_with(header) {
+            if let Some(start) = src.windows(header.len()).position(|pos| pos == header) {
                 warn!("skipping {:?} bytes to seek header", start);
                 let _ = src.split_to(start);
             } else {
@@ -93,30 +264,37 @@ impl Decoder for DynamixelProtocol {
             return Ok(None);
         }
         // do this check after checking header
-        if len < 2 {
-            // discard byte to force a move
-            let _ = src.split_to(1);
-            return Err(DynamixelDriverError::HeaderLenTooSmall(len));
-        }
-        if src.len() < 4 + len {
+        let len = match protocol.status_len(&src[..prefix_len]) {
+            Ok(len) => len,
+            Err(error) => {
+                // discard byte to force a move
+                let _ = src.split_to(1);
+                return Err(error);
+            }
+        };
+        if src.len() < len {
+            src.reserve(len - src.len());
             return Ok(None);
         }
 
-        let expected_checksum = calc_checksum(&src[2..5 + (len - 2)]);
-        let received_checksum = src[3 + len];
-        if expected_checksum != received_checksum {
-            // discard byte to force a move
-            let _ = src.split_to(1);
-            return Err(DynamixelDriverError::ChecksumError(
-                expected_checksum,
-                received_checksum,
-            ));
+        if let Err(error) = protocol.verify_checksum(&src[..len]) {
+            self.stats.checksum_mismatches += 1;
+            if self.checksum_policy != ChecksumPolicy::WarnAndAccept {
+                // discard byte to force a move
+                let _ = src.split_to(1);
+                return Err(error);
+            }
+            warn!("accepting packet despite {}", error);
+            self.stats.accepted_bad_checksums += 1;
         }
-        let message = src.split_to(4 + len);
-        StatusError::check_error(message[4])?;
-        let params = message[5..5 + (len - 2)].to_vec();
+        let message = src.split_to(len);
+        self.stats.frames_decoded += 1;
+        self.stats.bytes_received += message.len() as u64;
+        trace!("received {}", debug::format_status(&message));
+        let (id, error, params) = protocol.parse_status(&message)?;
+        StatusError::check_error(error & !HARDWARE_ALERT)?;
 
-        Ok(Some(Status::new(id, params)))
+        Ok(Some(Status::with_error(id, error, params)))
     }
 }
 
@@ -125,64 +303,464 @@ impl Encoder<Instruction> for DynamixelProtocol {
 
     fn encode(&mut self, data: Instruction, buf: &mut BytesMut) -> Result<()> {
         let msg = data.serialize();
+        self.stats.bytes_sent += msg.len() as u64;
         buf.reserve(msg.len());
         buf.put(msg.as_ref());
         Ok(())
     }
 }
 
+/// Codec framing instruction packets, the servo side of [`DynamixelProtocol`]
+///
+/// For tools looking at what a host sends, such as bus sniffers, simulated servos and
+/// replays of captured traffic. Skips bytes up to the next header like the status codec.
+#[derive(Debug)]
+pub struct InstructionDecoder {
+    protocol: Arc<dyn Protocol>,
+}
+
+impl Default for InstructionDecoder {
+    fn default() -> Self {
+        InstructionDecoder::with_protocol(Arc::new(Protocol1))
+    }
+}
+
+impl InstructionDecoder {
+    pub fn with_protocol(protocol: Arc<dyn Protocol>) -> InstructionDecoder {
+        InstructionDecoder { protocol }
+    }
+}
+
+impl Decoder for InstructionDecoder {
+    type Item = Instruction;
+    type Error = DynamixelDriverError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
+        let prefix_len = self.protocol.status_prefix_len();
+        if src.len() < prefix_len {
+            return Ok(None);
+        }
+        let header = self.protocol.header();
+        if !src.starts_with(header) {
+            let start = src
+                .windows(header.len())
+                .position(|pos| pos == header)
+                .unwrap_or(1);
+            let _ = src.split_to(start);
+            return Ok(None);
+        }
+        let len = match self.protocol.status_len(&src[..prefix_len]) {
+            Ok(len) => len,
+            Err(error) => {
+                let _ = src.split_to(1);
+                return Err(error);
+            }
+        };
+        if src.len() < len {
+            src.reserve(len - src.len());
+            return Ok(None);
+        }
+        match self.protocol.decode_instruction(&src[..len]) {
+            Ok(instruction) => {
+                let _ = src.split_to(len);
+                Ok(Some(instruction))
+            }
+            Err(error) => {
+                // discard byte to force a move
+                let _ = src.split_to(1);
+                Err(error)
+            }
+        }
+    }
+}
+
+/// Transport the driver sends instructions and receives status packets over
 #[async_trait]
-pub(crate) trait FramedDriver: Send + Sync {
+pub trait FramedDriver: Send + Sync {
     async fn send(&mut self, instruction: Instruction) -> Result<()>;
     async fn receive(&mut self) -> Result<Status>;
     async fn clear_io_buffers(&mut self) -> Result<()>;
+
+    /// Flushes pending writes and releases the transport, called by [`crate::DynamixelDriver::close`]
+    async fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_checksum_policy(&mut self, _policy: ChecksumPolicy) {}
+
+    /// Decode status packets of both protocol generations, see
+    /// [`crate::DynamixelDriver::set_detect_protocols`]
+    fn set_mixed_protocols(&mut self, _enabled: bool) {}
+
+    /// Called by the driver so transports can publish their own [`DriverEvent`]s
+    fn set_event_sender(&mut self, _events: broadcast::Sender<DriverEvent>) {}
+
+    #[cfg(feature = "serial")]
+    fn configure_serial_port(
+        &mut self,
+        _configure: &mut dyn FnMut(&mut SerialStream) -> tokio_serial::Result<()>,
+    ) -> Result<()> {
+        Err(DynamixelDriverError::UnsupportedByTransport)
+    }
+
+    /// Collects one status from each of the ids, in order, each with its own timeout.
+    /// Servos that don't answer are reported as timeouts without losing later statuses.
+    async fn receive_batch(&mut self, ids: &[u8]) -> Vec<(u8, Result<Status>)> {
+        let mut results = Vec::with_capacity(ids.len());
+        let mut index = 0;
+        while index < ids.len() {
+            match self.receive().await {
+                Ok(status) => {
+                    match ids[index..].iter().position(|id| *id == status.id()) {
+                        Some(offset) => {
+                            // servos in between didn't answer
+                            for skipped in &ids[index..index + offset] {
+                                results.push((*skipped, Err(DynamixelDriverError::Timeout)));
+                            }
+                            results.push((status.id(), Ok(status)));
+                            index += offset + 1;
+                        }
+                        None => warn!("discarding unexpected status from id {}", status.id()),
+                    }
+                }
+                Err(error) => {
+                    results.push((ids[index], Err(error)));
+                    index += 1;
+                }
+            }
+        }
+        results
+    }
+
+    fn stats(&self) -> BusStats {
+        BusStats::default()
+    }
 }
 
+#[cfg(feature = "serial")]
 pub(crate) const TIMEOUT: u64 = 100;
 
+// fits the largest Protocol 1.0 packet, buffers are reused between frames
+#[cfg(feature = "serial")]
+pub(crate) const DEFAULT_BUFFER_CAPACITY: usize = 512;
+
+/// How long to wait for a status packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiveTimeout {
+    /// Same timeout for every response
+    Fixed(Duration),
+    /// Fixed allowance for adapter latency and servo return delay plus twice the time
+    /// the instruction and the expected response take on the wire at the configured baud rate
+    Scaled { latency: Duration },
+}
+
+impl Default for ReceiveTimeout {
+    fn default() -> Self {
+        // FTDI adapters hold bytes for up to 16ms by default before handing them to the host
+        ReceiveTimeout::Scaled {
+            latency: Duration::from_millis(25),
+        }
+    }
+}
+
+impl ReceiveTimeout {
+    /// Receive timeout for an instruction of `instruction_len` bytes answered with `response_len` bytes
+    pub fn for_transaction(
+        &self,
+        baud_rate: u32,
+        instruction_len: usize,
+        response_len: usize,
+    ) -> Duration {
+        match *self {
+            ReceiveTimeout::Fixed(timeout) => timeout,
+            ReceiveTimeout::Scaled { latency } => {
+                // 8 data bits plus start and stop bit
+                let bits = (instruction_len + response_len) as f64 * 10.0;
+                latency + Duration::from_secs_f64(2.0 * bits / baud_rate as f64)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serial")]
+#[derive(Debug, Clone)]
+pub(crate) struct SerialSettings {
+    pub port: String,
+    pub baud_rate: u32,
+    pub timeout: ReceiveTimeout,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+    pub latency_timer: Option<u8>,
+    pub buffer_capacity: usize,
+    pub protocol: Arc<dyn Protocol>,
+}
+
+#[cfg(feature = "serial")]
+impl SerialSettings {
+    pub fn new(port: &str) -> SerialSettings {
+        SerialSettings {
+            port: port.to_owned(),
+            baud_rate: 1000000,
+            timeout: ReceiveTimeout::default(),
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+            latency_timer: None,
+            buffer_capacity: DEFAULT_BUFFER_CAPACITY,
+            protocol: Arc::new(Protocol1),
+        }
+    }
+}
+
+#[cfg(feature = "serial")]
 pub struct FramedSerialDriver {
-    framed_port: tokio_util::codec::Framed<tokio_serial::SerialStream, DynamixelProtocol>,
+    framed_port: Framed<tokio_serial::SerialStream, DynamixelProtocol>,
+    baud_rate: u32,
+    timeout: ReceiveTimeout,
+    pending_timeout: Duration,
 }
 
+#[cfg(feature = "serial")]
 impl FramedSerialDriver {
     pub fn new(port: &str) -> Result<FramedSerialDriver> {
-        let serial_port = tokio_serial::new(port, 1000000)
-            .timeout(std::time::Duration::from_millis(TIMEOUT))
-            .open_native_async()
-            .map_err(|_| DynamixelDriverError::FailedOpeningSerialPort)?;
+        FramedSerialDriver::open(&SerialSettings::new(port))
+    }
 
-        Ok(FramedSerialDriver {
-            framed_port: DynamixelProtocol.framed(serial_port),
+    pub fn with_baud_rate(port: &str, baud_rate: u32) -> Result<FramedSerialDriver> {
+        FramedSerialDriver::open(&SerialSettings {
+            baud_rate,
+            ..SerialSettings::new(port)
         })
     }
 
-    pub fn with_baud_rate(port: &str, baud_rate: u32) -> Result<FramedSerialDriver> {
-        let serial_port = tokio_serial::new(port, baud_rate)
+    pub(crate) fn open(settings: &SerialSettings) -> Result<FramedSerialDriver> {
+        if let Some(latency_timer) = settings.latency_timer {
+            set_ftdi_latency_timer(&settings.port, latency_timer)?;
+        }
+        let serial_port = tokio_serial::new(device_path(&settings.port), settings.baud_rate)
+            .parity(settings.parity)
+            .stop_bits(settings.stop_bits)
+            .flow_control(settings.flow_control)
             .timeout(std::time::Duration::from_millis(TIMEOUT))
             .open_native_async()
-            .map_err(|_| DynamixelDriverError::FailedOpeningSerialPort)?;
+            .map_err(|source| DynamixelDriverError::FailedOpeningSerialPort {
+                port: settings.port.clone(),
+                source,
+            })?;
+        // some adapter drivers, notably on Windows, hand out bytes buffered before the port
+        // was last closed
+        serial_port.clear(tokio_serial::ClearBuffer::Input)?;
 
+        let codec = DynamixelProtocol::with_protocol(settings.protocol.clone());
+        let mut parts = FramedParts::new::<Instruction>(serial_port, codec);
+        parts.read_buf = BytesMut::with_capacity(settings.buffer_capacity);
+        parts.write_buf = BytesMut::with_capacity(settings.buffer_capacity);
         Ok(FramedSerialDriver {
-            framed_port: DynamixelProtocol.framed(serial_port),
+            framed_port: Framed::from_parts(parts),
+            baud_rate: settings.baud_rate,
+            timeout: settings.timeout,
+            pending_timeout: Duration::from_millis(TIMEOUT),
         })
     }
 }
 
+#[cfg(feature = "serial")]
+/// Actionable explanation of why a serial port can or can't be opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortDiagnostic {
+    Available,
+    NotFound { available_ports: Vec<String> },
+    PermissionDenied,
+    Busy,
+    Other(String),
+}
+
+#[cfg(feature = "serial")]
+impl PortDiagnostic {
+    pub fn from_error(error: &tokio_serial::Error) -> PortDiagnostic {
+        match error.kind() {
+            tokio_serial::ErrorKind::NoDevice
+            | tokio_serial::ErrorKind::Io(std::io::ErrorKind::NotFound) => {
+                PortDiagnostic::NotFound {
+                    available_ports: tokio_serial::available_ports()
+                        .map(|ports| ports.into_iter().map(|port| port.port_name).collect())
+                        .unwrap_or_default(),
+                }
+            }
+            tokio_serial::ErrorKind::Io(std::io::ErrorKind::PermissionDenied) => {
+                PortDiagnostic::PermissionDenied
+            }
+            tokio_serial::ErrorKind::Io(std::io::ErrorKind::ResourceBusy) => PortDiagnostic::Busy,
+            _ if error.description.to_lowercase().contains("busy") => PortDiagnostic::Busy,
+            _ => PortDiagnostic::Other(error.description.clone()),
+        }
+    }
+}
+
+#[cfg(feature = "serial")]
+impl std::fmt::Display for PortDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PortDiagnostic::Available => write!(f, "port is available"),
+            PortDiagnostic::NotFound { available_ports } if available_ports.is_empty() => write!(
+                f,
+                "port not found and no serial ports detected, check that the adapter is plugged in"
+            ),
+            PortDiagnostic::NotFound { available_ports } => write!(
+                f,
+                "port not found, available ports are {}",
+                available_ports.join(", ")
+            ),
+            PortDiagnostic::PermissionDenied => write!(
+                f,
+                "permission denied, on Linux add your user to the dialout group. \
+                 On Windows this also means the port is open in another program"
+            ),
+            PortDiagnostic::Busy => write!(f, "port is in use by another process"),
+            PortDiagnostic::Other(description) => write!(f, "{}", description),
+        }
+    }
+}
+
+#[cfg(feature = "serial")]
+/// Tries to open the port and reports why it can't be used
+pub fn probe_port(port: &str) -> PortDiagnostic {
+    match tokio_serial::new(device_path(port), 1000000).open() {
+        Ok(_) => PortDiagnostic::Available,
+        Err(error) => PortDiagnostic::from_error(&error),
+    }
+}
+
+#[cfg(feature = "serial")]
+/// Serial port found on the system
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortInfo {
+    pub name: String,
+    /// Human readable device, such as the USB product name
+    pub description: String,
+}
+
+#[cfg(feature = "serial")]
+/// Lists serial ports with a description of the device behind each of them
+pub fn list_ports() -> Result<Vec<PortInfo>> {
+    let ports = tokio_serial::available_ports()?;
+    Ok(ports
+        .into_iter()
+        .map(|port| PortInfo {
+            description: describe_port_type(&port.port_type),
+            name: port.port_name,
+        })
+        .collect())
+}
+
+#[cfg(feature = "serial")]
+fn describe_port_type(port_type: &tokio_serial::SerialPortType) -> String {
+    match port_type {
+        tokio_serial::SerialPortType::UsbPort(usb) => {
+            let name = match (&usb.manufacturer, &usb.product) {
+                (Some(manufacturer), Some(product)) if !product.contains(manufacturer.as_str()) => {
+                    format!("{} {}", manufacturer, product)
+                }
+                (_, Some(product)) => product.clone(),
+                (Some(manufacturer), None) => manufacturer.clone(),
+                (None, None) => "USB serial device".to_owned(),
+            };
+            format!("{} ({:04x}:{:04x})", name, usb.vid, usb.pid)
+        }
+        tokio_serial::SerialPortType::PciPort => "PCI serial port".to_owned(),
+        tokio_serial::SerialPortType::BluetoothPort => "Bluetooth serial port".to_owned(),
+        tokio_serial::SerialPortType::Unknown => "unknown device".to_owned(),
+    }
+}
+
+/// Windows only opens `COM10` and up through the device namespace, `\\.\COM10`
+#[cfg_attr(not(all(feature = "serial", windows)), allow(dead_code))]
+fn windows_device_path(port: &str) -> String {
+    let name = port.trim().trim_end_matches(':');
+    let is_com_port = name.len() > 3
+        && name[..3].eq_ignore_ascii_case("com")
+        && name[3..].bytes().all(|byte| byte.is_ascii_digit());
+    if is_com_port {
+        format!(r"\\.\{}", name.to_ascii_uppercase())
+    } else {
+        port.to_owned()
+    }
+}
+
+#[cfg(all(feature = "serial", windows))]
+fn device_path(port: &str) -> String {
+    windows_device_path(port)
+}
+
+#[cfg(all(feature = "serial", not(windows)))]
+fn device_path(port: &str) -> String {
+    port.to_owned()
+}
+
+#[cfg(all(feature = "serial", target_os = "linux"))]
+fn ftdi_latency_timer_path(port: &str) -> Option<std::path::PathBuf> {
+    // resolve links such as /dev/serial/by-id/... to the ttyUSB device
+    let device = std::fs::canonicalize(port).unwrap_or_else(|_| port.into());
+    let name = device.file_name()?;
+    Some(
+        std::path::Path::new("/sys/bus/usb-serial/devices")
+            .join(name)
+            .join("latency_timer"),
+    )
+}
+
+#[cfg(all(feature = "serial", target_os = "linux"))]
+fn set_ftdi_latency_timer(port: &str, latency_ms: u8) -> Result<()> {
+    let path = ftdi_latency_timer_path(port).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("can't find latency timer for {:?}", port),
+        )
+    })?;
+    std::fs::write(path, latency_ms.to_string())?;
+    Ok(())
+}
+
+#[cfg(all(feature = "serial", not(target_os = "linux")))]
+fn set_ftdi_latency_timer(_port: &str, _latency_ms: u8) -> Result<()> {
+    warn!("FTDI latency timer can only be configured on Linux");
+    Ok(())
+}
+
+#[cfg(feature = "serial")]
 #[async_trait]
 impl FramedDriver for FramedSerialDriver {
     async fn send(&mut self, instruction: Instruction) -> Result<()> {
+        trace!(
+            "sending {}",
+            debug::format_instruction(instruction.as_bytes())
+        );
+        self.pending_timeout = self.timeout.for_transaction(
+            self.baud_rate,
+            instruction.as_bytes().len(),
+            instruction.expected_response_len(),
+        );
+        self.framed_port
+            .read_buffer_mut()
+            .reserve(instruction.expected_response_len());
         self.framed_port.send(instruction).await?;
         Ok(())
     }
 
     async fn receive(&mut self) -> Result<Status> {
-        let response = timeout(Duration::from_millis(TIMEOUT), self.framed_port.next())
+        let response = timeout(self.pending_timeout, self.framed_port.next())
             .await
             .map_err(|_| DynamixelDriverError::Timeout)?
             .ok_or(DynamixelDriverError::ReadingError)??;
         Ok(response)
     }
 
+    async fn close(&mut self) -> Result<()> {
+        self.framed_port.close().await?;
+        Ok(())
+    }
+
     async fn clear_io_buffers(&mut self) -> Result<()> {
         // do not touch the internal IO buffer because you might put it in a corrupt state
         // self.framed_port
@@ -193,6 +771,29 @@ impl FramedDriver for FramedSerialDriver {
         self.framed_port.read_buffer_mut().clear();
         Ok(())
     }
+
+    fn set_checksum_policy(&mut self, policy: ChecksumPolicy) {
+        self.framed_port.codec_mut().checksum_policy = policy;
+    }
+
+    fn set_mixed_protocols(&mut self, enabled: bool) {
+        self.framed_port.codec_mut().set_mixed_protocols(enabled);
+    }
+
+    fn configure_serial_port(
+        &mut self,
+        configure: &mut dyn FnMut(&mut SerialStream) -> tokio_serial::Result<()>,
+    ) -> Result<()> {
+        let port = self.framed_port.get_mut();
+        configure(port)?;
+        // keep scaled timeouts in line with the new settings
+        self.baud_rate = port.baud_rate()?;
+        Ok(())
+    }
+
+    fn stats(&self) -> BusStats {
+        self.framed_port.codec().stats.clone()
+    }
 }
 
 #[cfg(test)]
@@ -200,10 +801,19 @@ mod tests {
     use super::*;
     use bytes::BytesMut;
 
+    #[test]
+    fn windows_com_ports_use_device_namespace() {
+        assert_eq!(windows_device_path("COM10"), r"\\.\COM10");
+        assert_eq!(windows_device_path("com3:"), r"\\.\COM3");
+        assert_eq!(windows_device_path(r"\\.\COM12"), r"\\.\COM12");
+        assert_eq!(windows_device_path("COM"), "COM");
+        assert_eq!(windows_device_path("/dev/ttyUSB0"), "/dev/ttyUSB0");
+    }
+
     #[test]
     fn test_message_decode() {
         let mut payload = BytesMut::from(vec![0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0xDB].as_slice());
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         let res = codec.decode(&mut payload).unwrap().unwrap();
         assert_eq!(res, Status::new(1, vec![0x20]));
     }
@@ -213,7 +823,7 @@ mod tests {
         let mut payload = BytesMut::from(
             vec![0xFF, 0x12, 0x21, 0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0xDB].as_slice(),
         );
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         assert!(codec.decode(&mut payload).unwrap().is_none());
         let res = codec.decode(&mut payload).unwrap().unwrap();
         assert_eq!(res, Status::new(1, vec![0x20]));
@@ -227,7 +837,7 @@ mod tests {
             ]
             .as_slice(),
         );
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         assert!(codec.decode(&mut payload).unwrap().is_none());
         assert!(std::matches!(
             codec.decode(&mut payload).unwrap_err(),
@@ -243,7 +853,7 @@ mod tests {
     fn test_message_skip_checksum_error_and_decode() {
         let mut payload =
             BytesMut::from(vec![0xFF, 0xFF, 0xFF, 0x04, 0x03, 0x00, 0x20, 0xD8].as_slice());
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         assert!(std::matches!(
             codec.decode(&mut payload).unwrap_err(),
             DynamixelDriverError::ChecksumError(_, _)
@@ -252,11 +862,58 @@ mod tests {
         assert_eq!(res, Status::new(4, vec![0x20]));
     }
 
+    #[test]
+    fn partial_frame_reserves_remaining_bytes() {
+        let mut payload = BytesMut::with_capacity(4);
+        payload.extend_from_slice(&[0xFF, 0xFF, 0x01, 0x06]);
+        let mut codec = DynamixelProtocol::default();
+        assert!(codec.decode(&mut payload).unwrap().is_none());
+        assert!(payload.capacity() >= 10);
+    }
+
+    #[test]
+    fn test_warn_and_accept_bad_checksum() {
+        let mut payload = BytesMut::from(vec![0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0xDC].as_slice());
+        let mut codec = DynamixelProtocol {
+            checksum_policy: ChecksumPolicy::WarnAndAccept,
+            ..Default::default()
+        };
+        let res = codec.decode(&mut payload).unwrap().unwrap();
+        assert_eq!(res, Status::new(1, vec![0x20]));
+        assert_eq!(
+            codec.stats,
+            BusStats {
+                frames_decoded: 1,
+                checksum_mismatches: 1,
+                accepted_bad_checksums: 1,
+                bytes_received: 7,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_strict_checksum_counts_mismatch() {
+        let mut payload = BytesMut::from(
+            vec![
+                0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0xDC, 0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0xDB,
+            ]
+            .as_slice(),
+        );
+        let mut codec = DynamixelProtocol::default();
+        assert!(codec.decode(&mut payload).is_err());
+        assert!(codec.decode(&mut payload).unwrap().is_none());
+        assert!(codec.decode(&mut payload).unwrap().is_some());
+        assert_eq!(codec.stats.checksum_mismatches, 1);
+        assert_eq!(codec.stats.accepted_bad_checksums, 0);
+        assert_eq!(codec.stats.frames_decoded, 1);
+    }
+
     #[test]
     fn test_input_voltage_error() {
         let mut payload =
             BytesMut::from(vec![0xFF, 0xFF, 0x01, 0x03, 0b00000001, 0x20, 0xDA].as_slice());
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         let err = codec.decode(&mut payload).unwrap_err();
         if let DynamixelDriverError::StatusError(status) = err {
             assert!(status.input_voltage_error);
@@ -269,7 +926,7 @@ mod tests {
     fn test_angle_limit_error() {
         let mut payload =
             BytesMut::from(vec![0xFF, 0xFF, 0x01, 0x03, 0b00000010, 0x20, 0xD9].as_slice());
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         let err = codec.decode(&mut payload).unwrap_err();
         if let DynamixelDriverError::StatusError(status) = err {
             assert!(status.angle_limit_error);
@@ -282,7 +939,7 @@ mod tests {
     fn test_overheating_error() {
         let mut payload =
             BytesMut::from(vec![0xFF, 0xFF, 0x01, 0x03, 0b00000100, 0x20, 0xD7].as_slice());
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         let err = codec.decode(&mut payload).unwrap_err();
         if let DynamixelDriverError::StatusError(status) = err {
             assert!(status.overheating_error);
@@ -295,7 +952,7 @@ mod tests {
     fn test_range_error() {
         let mut payload =
             BytesMut::from(vec![0xFF, 0xFF, 0x01, 0x03, 0b00001000, 0x20, 0xD3].as_slice());
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         let err = codec.decode(&mut payload).unwrap_err();
         if let DynamixelDriverError::StatusError(status) = err {
             assert!(status.range_error);
@@ -308,7 +965,7 @@ mod tests {
     fn test_checksum_error() {
         let mut payload =
             BytesMut::from(vec![0xFF, 0xFF, 0x01, 0x03, 0b00010000, 0x20, 0xCB].as_slice());
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         let err = codec.decode(&mut payload).unwrap_err();
         if let DynamixelDriverError::StatusError(status) = err {
             assert!(status.checksum_error);
@@ -321,7 +978,7 @@ mod tests {
     fn test_overload_error() {
         let mut payload =
             BytesMut::from(vec![0xFF, 0xFF, 0x01, 0x03, 0b00100000, 0x20, 0xBB].as_slice());
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         let err = codec.decode(&mut payload).unwrap_err();
         if let DynamixelDriverError::StatusError(status) = err {
             assert!(status.overload_error);
@@ -334,7 +991,7 @@ mod tests {
     fn test_instruction_error() {
         let mut payload =
             BytesMut::from(vec![0xFF, 0xFF, 0x01, 0x03, 0b01000000, 0x20, 0x9B].as_slice());
-        let mut codec = DynamixelProtocol {};
+        let mut codec = DynamixelProtocol::default();
         let err = codec.decode(&mut payload).unwrap_err();
         if let DynamixelDriverError::StatusError(status) = err {
             assert!(status.instruction_error);
@@ -343,9 +1000,234 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decodes_protocol2_statuses() {
+        let mut payload = BytesMut::from(
+            &[
+                0x00, 0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x08, 0x00, 0x55, 0x00, 0xA6, 0x00, 0x00, 0x00,
+                0x8C, 0xC0,
+            ][..],
+        );
+        let mut codec = DynamixelProtocol::with_protocol(Arc::new(crate::Protocol2));
+        assert!(codec.decode(&mut payload).unwrap().is_none());
+        let status = codec.decode(&mut payload).unwrap().unwrap();
+        assert_eq!(status.as_u32().unwrap(), 166);
+        assert!(payload.is_empty());
+    }
+
+    #[test]
+    fn decodes_mixed_protocols() {
+        let mut payload = BytesMut::from(
+            &[
+                0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0xDB, 0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x08, 0x00,
+                0x55, 0x00, 0xA6, 0x00, 0x00, 0x00, 0x8C, 0xC0,
+            ][..],
+        );
+        let mut codec = DynamixelProtocol::default();
+        codec.set_mixed_protocols(true);
+        let status = codec.decode(&mut payload).unwrap().unwrap();
+        assert_eq!(status, Status::new(1, vec![0x20]));
+        let status = codec.decode(&mut payload).unwrap().unwrap();
+        assert_eq!(status.as_u32().unwrap(), 166);
+        assert!(payload.is_empty());
+    }
+
+    struct QueuedStatuses(Vec<Result<Status>>);
+
+    #[async_trait]
+    impl FramedDriver for QueuedStatuses {
+        async fn send(&mut self, _instruction: Instruction) -> Result<()> {
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<Status> {
+            if self.0.is_empty() {
+                return Err(DynamixelDriverError::Timeout);
+            }
+            self.0.remove(0)
+        }
+
+        async fn clear_io_buffers(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn receive_batch_collects_in_order() {
+        let mut port = QueuedStatuses(vec![
+            Ok(Status::new(1, vec![1])),
+            Ok(Status::new(2, vec![2])),
+            Ok(Status::new(3, vec![3])),
+        ]);
+        let results = port.receive_batch(&[1, 2, 3]).await;
+        let results: Vec<_> = results
+            .into_iter()
+            .map(|(id, status)| (id, status.unwrap()))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                (1, Status::new(1, vec![1])),
+                (2, Status::new(2, vec![2])),
+                (3, Status::new(3, vec![3])),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn receive_batch_reports_missing_servos() {
+        // servo 2 is missing so servo 3 answers inside its timeout window
+        // and servo 4 times out at the end
+        let mut port = QueuedStatuses(vec![
+            Ok(Status::new(1, vec![1])),
+            Ok(Status::new(3, vec![3])),
+        ]);
+        let results = port.receive_batch(&[1, 2, 3, 4]).await;
+        assert_eq!(results.len(), 4);
+        assert!(results[0].1.is_ok());
+        assert!(matches!(
+            results[1],
+            (2, Err(DynamixelDriverError::Timeout))
+        ));
+        assert_eq!(results[2].0, 3);
+        assert_eq!(*results[2].1.as_ref().unwrap(), Status::new(3, vec![3]));
+        assert!(matches!(
+            results[3],
+            (4, Err(DynamixelDriverError::Timeout))
+        ));
+    }
+
+    #[tokio::test]
+    async fn receive_batch_discards_stale_statuses_and_attributes_errors() {
+        let mut port = QueuedStatuses(vec![
+            Ok(Status::new(9, vec![])),
+            Err(DynamixelDriverError::ChecksumError(1, 2)),
+            Ok(Status::new(2, vec![2])),
+        ]);
+        let results = port.receive_batch(&[1, 2]).await;
+        assert!(matches!(
+            results[0],
+            (1, Err(DynamixelDriverError::ChecksumError(1, 2)))
+        ));
+        assert_eq!(*results[1].1.as_ref().unwrap(), Status::new(2, vec![2]));
+    }
+
+    #[test]
+    fn fixed_timeout_ignores_length() {
+        let timeout = ReceiveTimeout::Fixed(Duration::from_millis(100));
+        assert_eq!(
+            timeout.for_transaction(57600, 8, 80),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    fn scaled_timeout_grows_with_length_and_shrinks_with_baud() {
+        let timeout = ReceiveTimeout::Scaled {
+            latency: Duration::from_millis(10),
+        };
+        // 10 bytes at 1Mbps is 100us on the wire
+        assert_eq!(
+            timeout.for_transaction(1_000_000, 8, 2),
+            Duration::from_micros(10_200)
+        );
+        // 74 byte dump at 57600 baud
+        let slow = timeout.for_transaction(57600, 8, 80);
+        assert!(slow > Duration::from_millis(40));
+        assert!(slow < Duration::from_millis(50));
+    }
+
+    #[test]
+    #[cfg(feature = "serial")]
+    fn diagnose_serial_errors() {
+        let permission = tokio_serial::Error::new(
+            tokio_serial::ErrorKind::Io(std::io::ErrorKind::PermissionDenied),
+            "Permission denied",
+        );
+        assert_eq!(
+            PortDiagnostic::from_error(&permission),
+            PortDiagnostic::PermissionDenied
+        );
+        let busy =
+            tokio_serial::Error::new(tokio_serial::ErrorKind::Unknown, "Device or resource busy");
+        assert_eq!(PortDiagnostic::from_error(&busy), PortDiagnostic::Busy);
+        let missing = tokio_serial::Error::new(tokio_serial::ErrorKind::NoDevice, "missing");
+        assert!(matches!(
+            PortDiagnostic::from_error(&missing),
+            PortDiagnostic::NotFound { .. }
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serial")]
+    fn probe_missing_port() {
+        assert!(matches!(
+            probe_port("/dev/this-port-does-not-exist"),
+            PortDiagnostic::NotFound { .. }
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serial")]
+    fn open_error_carries_source_and_port() {
+        let error = FramedSerialDriver::new("/dev/this-port-does-not-exist")
+            .err()
+            .unwrap();
+        match error {
+            DynamixelDriverError::FailedOpeningSerialPort { port, source } => {
+                assert_eq!(port, "/dev/this-port-does-not-exist");
+                assert!(matches!(
+                    PortDiagnostic::from_error(&source),
+                    PortDiagnostic::NotFound { .. }
+                ));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[cfg(all(feature = "serial", target_os = "linux"))]
+    #[test]
+    fn latency_timer_path_for_tty_usb() {
+        assert_eq!(
+            ftdi_latency_timer_path("/dev/ttyUSB7").unwrap(),
+            std::path::PathBuf::from("/sys/bus/usb-serial/devices/ttyUSB7/latency_timer")
+        );
+    }
+
     #[test]
     fn endianness_test() {
         let a = Status::new(0, vec![10, 20]);
         assert_eq!(a.as_u16().unwrap(), a.as_u16_bad().unwrap());
     }
+
+    #[test]
+    fn status_accessors() {
+        let status = Status::with_error(3, 0b0010_0100, vec![1, 2, 3, 4]);
+        assert_eq!(status.as_u8().unwrap(), 1);
+        assert_eq!(status.as_u32().unwrap(), 0x0403_0201);
+        assert!(Status::new(3, vec![1, 2]).as_u32().is_err());
+        let error = status.error().unwrap();
+        assert!(error.overheating_error && error.overload_error);
+        assert_eq!(error.flags(), status.error_flags());
+        assert_eq!(Status::new(3, vec![]).error(), None);
+    }
+
+    #[test]
+    fn typed_reads_explain_crossed_responses() {
+        let ack = Status::new(3, vec![]);
+        assert_eq!(ack.kind(), ResponseKind::Acknowledgment);
+        assert_eq!(
+            ack.as_u16().unwrap_err().to_string(),
+            "received write acknowledgment from servo 3 while expecting 2-byte read response"
+        );
+        assert!(matches!(
+            Status::new(3, vec![1]).as_u32(),
+            Err(DynamixelDriverError::UnexpectedResponse {
+                id: 3,
+                expected: ResponseKind::Read(4),
+                received: ResponseKind::Read(1),
+            })
+        ));
+        assert_eq!(Status::new(3, vec![1, 2, 3]).as_u16().unwrap(), 0x0201);
+    }
 }
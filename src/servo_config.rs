@@ -0,0 +1,99 @@
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+use crate::instructions::WriteOp;
+
+/// Register profile applied to a servo with [`crate::DynamixelDriver::apply_config_to_bus`]
+///
+/// Only registers that are set are written. Values are raw control table units.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct ServoConfig {
+    pub return_delay_time: Option<u8>,
+    pub cw_angle_limit: Option<u16>,
+    pub ccw_angle_limit: Option<u16>,
+    pub temperature_limit: Option<u8>,
+    pub max_torque: Option<u16>,
+    pub compliance_margin: Option<u8>,
+    pub compliance_slope: Option<u8>,
+    pub torque_limit: Option<u16>,
+}
+
+impl ServoConfig {
+    pub(crate) fn write_ops(&self, id: u8) -> Vec<WriteOp> {
+        let bytes = [
+            (5, self.return_delay_time),
+            (11, self.temperature_limit),
+            (26, self.compliance_margin),
+            (27, self.compliance_margin),
+            (28, self.compliance_slope),
+            (29, self.compliance_slope),
+        ];
+        let words = [
+            (6, self.cw_angle_limit),
+            (8, self.ccw_angle_limit),
+            (14, self.max_torque),
+            (34, self.torque_limit),
+        ];
+        let mut ops: Vec<WriteOp> = bytes
+            .into_iter()
+            .filter_map(|(addr, value)| value.map(|value| WriteOp::u8(id, addr, value)))
+            .chain(
+                words
+                    .into_iter()
+                    .filter_map(|(addr, value)| value.map(|value| WriteOp::u16(id, addr, value))),
+            )
+            .collect();
+        ops.sort_by_key(|op| op.addr());
+        ops
+    }
+}
+
+/// Progress of [`crate::DynamixelDriver::apply_config_to_bus`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigProgress {
+    Applying { id: u8, index: usize, total: usize },
+    Applied { id: u8 },
+    RolledBack { id: u8 },
+    RollbackFailed { id: u8 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_set_registers_are_written() {
+        let config = ServoConfig {
+            return_delay_time: Some(0),
+            compliance_slope: Some(32),
+            torque_limit: Some(512),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.write_ops(1),
+            vec![
+                WriteOp::u8(1, 5, 0),
+                WriteOp::u8(1, 28, 32),
+                WriteOp::u8(1, 29, 32),
+                WriteOp::u16(1, 34, 512),
+            ]
+        );
+        assert!(ServoConfig::default().write_ops(1).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parse_profile() {
+        let config: ServoConfig = toml::from_str(
+            r#"
+            compliance_margin = 1
+            max_torque = 1023
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.compliance_margin, Some(1));
+        assert_eq!(config.max_torque, Some(1023));
+    }
+}
@@ -0,0 +1,290 @@
+use crate::debug;
+use crate::instructions::{calc_checksum, Instruction, USB2AX_ID};
+use crate::protocol::{Protocol, Protocol1, Protocol2, STATUS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    Instruction,
+    Status,
+}
+
+/// Packet captured by [`BusSniffer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SniffedFrame {
+    pub kind: FrameKind,
+    pub id: u8,
+    pub bytes: Vec<u8>,
+    pub checksum_ok: bool,
+}
+
+impl SniffedFrame {
+    /// Decoded instruction, None for statuses and instructions with a bad checksum
+    pub fn instruction(&self) -> Option<Instruction> {
+        match self.kind {
+            FrameKind::Instruction => protocol_of(&self.bytes)
+                .decode_instruction(&self.bytes)
+                .ok(),
+            FrameKind::Status => None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self.kind {
+            FrameKind::Instruction => debug::format_instruction(&self.bytes),
+            FrameKind::Status => debug::format_status(&self.bytes),
+        }
+    }
+}
+
+/// Counters for judging bus wiring quality from passively captured traffic
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnifferStats {
+    pub instructions: u64,
+    pub statuses: u64,
+    pub checksum_errors: u64,
+    /// Packets with a length field too small to be valid
+    pub malformed_frames: u64,
+    /// Bytes dropped while searching for a packet header
+    pub skipped_bytes: u64,
+}
+
+/// Splits raw bus traffic into instruction and status packets without taking part in it
+///
+/// Protocol 1.0 packets are classified by context: a packet from the servo the last
+/// instruction addressed is its status, anything else with a known opcode is an
+/// instruction. Protocol 2.0 statuses carry their own STATUS instruction byte.
+/// Adapters that echo transmitted bytes show each instruction exactly like a sniffer would.
+#[derive(Debug, Clone)]
+pub struct BusSniffer {
+    buffer: Vec<u8>,
+    awaiting_status_from: Option<u8>,
+    validate_instruction_checksums: bool,
+    stats: SnifferStats,
+}
+
+impl Default for BusSniffer {
+    fn default() -> Self {
+        BusSniffer::new()
+    }
+}
+
+impl BusSniffer {
+    pub fn new() -> BusSniffer {
+        BusSniffer {
+            buffer: vec![],
+            awaiting_status_from: None,
+            validate_instruction_checksums: true,
+            stats: SnifferStats::default(),
+        }
+    }
+
+    /// Whether bad instruction checksums count as errors, disable when only the
+    /// servo side of the wiring is of interest
+    pub fn validate_instruction_checksums(&mut self, enabled: bool) {
+        self.validate_instruction_checksums = enabled;
+    }
+
+    pub fn stats(&self) -> SnifferStats {
+        self.stats
+    }
+
+    /// Feeds captured bytes and returns all packets completed by them
+    pub fn push(&mut self, data: &[u8]) -> Vec<SniffedFrame> {
+        self.buffer.extend_from_slice(data);
+        let mut frames = vec![];
+        while let Some(frame) = self.next_frame() {
+            frames.push(frame);
+        }
+        frames
+    }
+
+    fn next_frame(&mut self) -> Option<SniffedFrame> {
+        loop {
+            if self.buffer.len() < 4 {
+                return None;
+            }
+            if !self.buffer.starts_with(&[0xFF, 0xFF]) {
+                let start = self
+                    .buffer
+                    .windows(2)
+                    .position(|pos| pos == [0xFF, 0xFF])
+                    .unwrap_or(self.buffer.len() - 1);
+                self.skip(start);
+                continue;
+            }
+            let protocol = protocol_of(&self.buffer);
+            let prefix_len = protocol.status_prefix_len();
+            if self.buffer.len() < prefix_len {
+                return None;
+            }
+            let Ok(len) = protocol.status_len(&self.buffer[..prefix_len]) else {
+                self.stats.malformed_frames += 1;
+                self.skip(1);
+                continue;
+            };
+            if self.buffer.len() < len {
+                return None;
+            }
+            let bytes: Vec<u8> = self.buffer.drain(..len).collect();
+            return Some(self.classify(bytes));
+        }
+    }
+
+    fn skip(&mut self, count: usize) {
+        self.stats.skipped_bytes += count as u64;
+        self.buffer.drain(..count);
+    }
+
+    fn classify(&mut self, bytes: Vec<u8>) -> SniffedFrame {
+        let protocol2 = bytes.starts_with(Protocol2.header());
+        let (id, opcode, checksum_ok) = if protocol2 {
+            // the instruction byte is never stuffed
+            (
+                bytes[4],
+                bytes[7],
+                Protocol2.verify_checksum(&bytes).is_ok(),
+            )
+        } else {
+            let last = bytes.len() - 1;
+            (
+                bytes[2],
+                bytes[4],
+                calc_checksum(&bytes[2..last]) == bytes[last],
+            )
+        };
+        let is_instruction = if protocol2 {
+            opcode != STATUS
+        } else {
+            self.awaiting_status_from != Some(id)
+                && opcode != STATUS
+                && debug::instruction_name(opcode) != "UNKNOWN"
+        };
+        let kind = if is_instruction {
+            self.stats.instructions += 1;
+            self.awaiting_status_from = match (id, opcode) {
+                (_, 0x84) => Some(USB2AX_ID),
+                (0xFE, _) => None,
+                _ => Some(id),
+            };
+            if !checksum_ok && self.validate_instruction_checksums {
+                self.stats.checksum_errors += 1;
+            }
+            FrameKind::Instruction
+        } else {
+            self.stats.statuses += 1;
+            self.awaiting_status_from = None;
+            if !checksum_ok {
+                self.stats.checksum_errors += 1;
+            }
+            FrameKind::Status
+        };
+        SniffedFrame {
+            kind,
+            id,
+            bytes,
+            checksum_ok,
+        }
+    }
+}
+
+fn protocol_of(packet: &[u8]) -> &'static dyn Protocol {
+    if packet.starts_with(Protocol2.header()) {
+        &Protocol2
+    } else {
+        &Protocol1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_request_and_response() {
+        let mut sniffer = BusSniffer::new();
+        let mut traffic = vec![0x00, 0x12];
+        traffic.extend(Instruction::read_instruction(1, 43, 1).serialize());
+        // status with error 0x02 which is also the READ opcode
+        traffic.extend([0xFF, 0xFF, 0x01, 0x03, 0x02, 0x20, 0xD9]);
+        let (first, second) = traffic.split_at(10);
+        let mut frames = sniffer.push(first);
+        frames.extend(sniffer.push(second));
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].kind, FrameKind::Instruction);
+        assert_eq!(
+            frames[0].instruction(),
+            Some(Instruction::read_instruction(1, 43, 1))
+        );
+        assert_eq!(frames[1].kind, FrameKind::Status);
+        assert_eq!(frames[1].instruction(), None);
+        assert!(frames.iter().all(|frame| frame.checksum_ok));
+        assert_eq!(
+            sniffer.stats(),
+            SnifferStats {
+                instructions: 1,
+                statuses: 1,
+                skipped_bytes: 2,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn counts_errors() {
+        let mut sniffer = BusSniffer::new();
+        let mut bad_instruction = Instruction::ping(2).serialize();
+        *bad_instruction.last_mut().unwrap() ^= 0xFF;
+        let mut traffic = vec![0xFF, 0xFF, 0x01, 0x01, 0x00];
+        traffic.extend(&bad_instruction);
+        // unanswered broadcast followed by a corrupted status
+        traffic.extend(Instruction::action(0xFE).serialize());
+        traffic.extend([0xFF, 0xFF, 0x03, 0x02, 0x00, 0x00]);
+        let frames = sniffer.push(&traffic);
+        assert_eq!(frames.len(), 3);
+        assert!(!frames[0].checksum_ok);
+        assert_eq!(frames[2].kind, FrameKind::Status);
+        let stats = sniffer.stats();
+        assert_eq!(stats.malformed_frames, 1);
+        assert_eq!(stats.checksum_errors, 2);
+
+        let mut sniffer = BusSniffer::new();
+        sniffer.validate_instruction_checksums(false);
+        sniffer.push(&bad_instruction);
+        assert_eq!(sniffer.stats().checksum_errors, 0);
+    }
+
+    #[test]
+    fn splits_protocol2_traffic() {
+        let mut sniffer = BusSniffer::new();
+        let read = Protocol2.read(1, 132, 4);
+        let mut traffic = read.clone().serialize();
+        traffic.extend([
+            0xFF, 0xFF, 0xFD, 0x00, 0x01, 0x08, 0x00, 0x55, 0x00, 0xA6, 0x00, 0x00, 0x00, 0x8C,
+            0xC0,
+        ]);
+        traffic.extend(Protocol2.reboot(2).serialize());
+        traffic.extend(Instruction::ping(3).serialize());
+        let (first, second) = traffic.split_at(9);
+        let mut frames = sniffer.push(first);
+        frames.extend(sniffer.push(second));
+        let kinds: Vec<_> = frames.iter().map(|frame| (frame.kind, frame.id)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (FrameKind::Instruction, 1),
+                (FrameKind::Status, 1),
+                (FrameKind::Instruction, 2),
+                (FrameKind::Instruction, 3),
+            ]
+        );
+        assert_eq!(frames[0].instruction(), Some(read));
+        assert!(frames[2].describe().contains("instruction=REBOOT(0x08)"));
+        assert!(frames.iter().all(|frame| frame.checksum_ok));
+        assert_eq!(sniffer.stats().statuses, 1);
+
+        let mut corrupted = Protocol2.ping(4).serialize();
+        *corrupted.last_mut().unwrap() ^= 0xFF;
+        assert!(!sniffer.push(&corrupted)[0].checksum_ok);
+        assert_eq!(sniffer.stats().checksum_errors, 1);
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::bus_load::BITS_PER_BYTE;
+
+/// Counters describing the health of the bus
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BusStats {
+    /// Name of the driver, see [`crate::DynamixelDriver::set_name`]
+    pub bus: Option<String>,
+    /// Status packets successfully decoded
+    pub frames_decoded: u64,
+    /// Packets whose checksum didn't match, regardless of checksum policy
+    pub checksum_mismatches: u64,
+    /// Packets with bad checksum that were accepted under the warn-and-accept policy
+    pub accepted_bad_checksums: u64,
+    /// Transactions retransmitted because of a bad checksum under the retry policy
+    pub checksum_retries: u64,
+    /// Registers read back after sync writes with spot checks enabled
+    pub spot_checks: u64,
+    /// Spot checks that didn't read back the written value
+    pub spot_check_failures: u64,
+    /// Instruction bytes written to the bus
+    pub bytes_sent: u64,
+    /// Bytes of successfully framed status packets
+    pub bytes_received: u64,
+    /// Lowest voltage read from each servo while voltage monitoring is enabled,
+    /// in tenths of a volt as reported by the servo
+    pub(crate) min_voltages: BTreeMap<u8, u8>,
+}
+
+impl BusStats {
+    /// Fraction of bus capacity used by the traffic counted so far
+    ///
+    /// Compare two snapshots taken `elapsed` apart with [`BusStats::since`] to measure
+    /// the current load rather than the average since the port was opened.
+    pub fn utilization(&self, baud_rate: u32, elapsed: Duration) -> f64 {
+        let bits = (self.bytes_sent + self.bytes_received) as f64 * BITS_PER_BYTE;
+        bits / (baud_rate as f64 * elapsed.as_secs_f64())
+    }
+
+    /// Lowest input voltage seen on servo `id` while voltage monitoring was enabled
+    pub fn min_voltage(&self, id: u8) -> Option<f32> {
+        self.min_voltages
+            .get(&id)
+            .map(|&voltage| voltage as f32 / 10.0)
+    }
+
+    /// Counters accumulated after the `earlier` snapshot
+    pub fn since(&self, earlier: &BusStats) -> BusStats {
+        BusStats {
+            bus: self.bus.clone(),
+            frames_decoded: self.frames_decoded - earlier.frames_decoded,
+            checksum_mismatches: self.checksum_mismatches - earlier.checksum_mismatches,
+            accepted_bad_checksums: self.accepted_bad_checksums - earlier.accepted_bad_checksums,
+            checksum_retries: self.checksum_retries - earlier.checksum_retries,
+            spot_checks: self.spot_checks - earlier.spot_checks,
+            spot_check_failures: self.spot_check_failures - earlier.spot_check_failures,
+            bytes_sent: self.bytes_sent - earlier.bytes_sent,
+            bytes_received: self.bytes_received - earlier.bytes_received,
+            min_voltages: self.min_voltages.clone(),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+/// Results of [`crate::DynamixelDriver::stress_test`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StressReport {
+    pub transactions: u64,
+    pub timeouts: u64,
+    pub checksum_errors: u64,
+    /// Failures other than timeouts and checksum errors
+    pub other_errors: u64,
+    /// Round trip times of successful transactions, sorted ascending
+    latencies: Vec<Duration>,
+}
+
+impl StressReport {
+    pub(crate) fn record(&mut self, result: Result<Duration, &crate::DynamixelDriverError>) {
+        self.transactions += 1;
+        match result {
+            Ok(latency) => self.latencies.push(latency),
+            Err(crate::DynamixelDriverError::Timeout) => self.timeouts += 1,
+            Err(crate::DynamixelDriverError::ChecksumError(_, _)) => self.checksum_errors += 1,
+            Err(_) => self.other_errors += 1,
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> StressReport {
+        self.latencies.sort_unstable();
+        self
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.timeouts + self.checksum_errors + self.other_errors
+    }
+
+    /// Fraction of transactions that failed
+    pub fn error_rate(&self) -> f64 {
+        if self.transactions == 0 {
+            return 0.0;
+        }
+        self.errors() as f64 / self.transactions as f64
+    }
+
+    /// Latency below which `percentile` (0 to 100) of successful transactions completed
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * (self.latencies.len() - 1) as f64)
+            .round() as usize;
+        Some(self.latencies[rank])
+    }
+
+    pub fn mean_latency(&self) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        Some(self.latencies.iter().sum::<Duration>() / self.latencies.len() as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamixelDriverError;
+
+    #[test]
+    fn statistics() {
+        let mut report = StressReport::default();
+        for millis in [4, 1, 3, 2, 5] {
+            report.record(Ok(Duration::from_millis(millis)));
+        }
+        report.record(Err(&DynamixelDriverError::Timeout));
+        report.record(Err(&DynamixelDriverError::ChecksumError(1, 2)));
+        report.record(Err(&DynamixelDriverError::ReadingError));
+        let report = report.finish();
+        assert_eq!(report.transactions, 8);
+        assert_eq!(report.errors(), 3);
+        assert!((report.error_rate() - 0.375).abs() < 1e-9);
+        assert_eq!(
+            report.latency_percentile(0.0),
+            Some(Duration::from_millis(1))
+        );
+        assert_eq!(
+            report.latency_percentile(50.0),
+            Some(Duration::from_millis(3))
+        );
+        assert_eq!(
+            report.latency_percentile(100.0),
+            Some(Duration::from_millis(5))
+        );
+        assert_eq!(report.mean_latency(), Some(Duration::from_millis(3)));
+        assert_eq!(StressReport::default().latency_percentile(50.0), None);
+    }
+}
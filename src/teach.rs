@@ -0,0 +1,194 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::path::Path;
+#[cfg(feature = "serde")]
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::instructions::SyncCommandFloat;
+#[cfg(feature = "serde")]
+use crate::instructions::{DynamixelDriverError, Result};
+use crate::primitives::MotionPrimitive;
+
+/// Joint positions sampled while moving the robot by hand, stored as TOML
+///
+/// Recorded with [`crate::DynamixelDriver::record_teach`] and played back with
+/// [`crate::DynamixelDriver::replay`], interpolating linearly between frames.
+///
+/// ```toml
+/// ids = [1, 2]
+///
+/// [[frames]]
+/// time_ms = 0
+/// positions = [150.0, 90.0]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct Recording {
+    pub ids: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub frames: Vec<RecordedFrame>,
+}
+
+/// Positions in joint degrees in the order of [`Recording::ids`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct RecordedFrame {
+    /// Time since the start of the recording
+    pub time_ms: u64,
+    pub positions: Vec<f32>,
+}
+
+impl Recording {
+    pub fn new(ids: Vec<u8>) -> Recording {
+        Recording {
+            ids,
+            frames: vec![],
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Recording> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, self.to_toml())?;
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("recording always serializes to TOML")
+    }
+
+    pub fn push(&mut self, time: Duration, positions: Vec<f32>) {
+        self.frames.push(RecordedFrame {
+            time_ms: time.as_millis() as u64,
+            positions,
+        });
+    }
+
+    /// Pose of the first frame, where replay starts from
+    pub fn first_pose(&self) -> Vec<SyncCommandFloat> {
+        self.frames
+            .first()
+            .map(|frame| self.pose(&frame.positions))
+            .unwrap_or_default()
+    }
+
+    fn pose(&self, positions: &[f32]) -> Vec<SyncCommandFloat> {
+        self.ids
+            .iter()
+            .zip(positions)
+            .map(|(&id, &position)| SyncCommandFloat::new(id, position))
+            .collect()
+    }
+}
+
+impl MotionPrimitive for Recording {
+    fn duration(&self) -> Option<Duration> {
+        let last = self.frames.last().map_or(0, |frame| frame.time_ms);
+        Some(Duration::from_millis(last))
+    }
+
+    fn sample(&self, elapsed: Duration) -> Vec<SyncCommandFloat> {
+        let time = elapsed.as_secs_f32() * 1000.0;
+        let next = self
+            .frames
+            .iter()
+            .position(|frame| frame.time_ms as f32 >= time);
+        let positions = match next {
+            None => match self.frames.last() {
+                Some(frame) => frame.positions.clone(),
+                None => return vec![],
+            },
+            Some(0) => self.frames[0].positions.clone(),
+            Some(index) => {
+                let (from, to) = (&self.frames[index - 1], &self.frames[index]);
+                let span = (to.time_ms - from.time_ms) as f32;
+                let fraction = (time - from.time_ms as f32) / span;
+                from.positions
+                    .iter()
+                    .zip(&to.positions)
+                    .map(|(from, to)| from + (to - from) * fraction)
+                    .collect()
+            }
+        };
+        self.pose(&positions)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromStr for Recording {
+    type Err = DynamixelDriverError;
+
+    fn from_str(contents: &str) -> Result<Self> {
+        let recording: Recording = toml::from_str(contents)
+            .map_err(|error| DynamixelDriverError::InvalidConfig(error.to_string()))?;
+        if let Some(frame) = recording
+            .frames
+            .iter()
+            .find(|frame| frame.positions.len() != recording.ids.len())
+        {
+            return Err(DynamixelDriverError::InvalidConfig(format!(
+                "frame at {} ms has {} positions for {} servos",
+                frame.time_ms,
+                frame.positions.len(),
+                recording.ids.len()
+            )));
+        }
+        Ok(recording)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn toml_round_trip() {
+        let mut recording = Recording::new(vec![1, 2]);
+        recording.push(Duration::ZERO, vec![150.0, 90.0]);
+        recording.push(Duration::from_millis(20), vec![151.5, 89.0]);
+        let parsed: Recording = recording.to_toml().parse().unwrap();
+        assert_eq!(parsed, recording);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reject_frames_of_wrong_width() {
+        let result = r#"
+            ids = [1, 2]
+
+            [[frames]]
+            time_ms = 0
+            positions = [150.0]
+        "#
+        .parse::<Recording>();
+        assert!(matches!(
+            result,
+            Err(DynamixelDriverError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn replay_interpolates_between_frames() {
+        let mut recording = Recording::new(vec![1]);
+        recording.push(Duration::ZERO, vec![100.0]);
+        recording.push(Duration::from_millis(100), vec![200.0]);
+        recording.push(Duration::from_millis(300), vec![100.0]);
+        assert_eq!(recording.duration(), Some(Duration::from_millis(300)));
+        let at = |millis| recording.sample(Duration::from_millis(millis))[0].value();
+        assert_eq!(at(0), 100.0);
+        assert!((at(50) - 150.0).abs() < 1e-3);
+        assert!((at(200) - 150.0).abs() < 1e-3);
+        assert_eq!(at(300), 100.0);
+        assert!(Recording::new(vec![1]).sample(Duration::ZERO).is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+//! Jogging servos from gamepad style axis inputs.
+//!
+//! Read axes with any input library, normalize them to -1.0 to 1.0 and pass them to
+//! [`crate::DynamixelDriver::teleop_step`] at a steady rate.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::instructions::SyncCommandFloat;
+
+/// How deflecting an axis moves its joint
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisMode {
+    /// Full deflection moves the joint at `max_speed` degrees per second
+    Velocity { max_speed: f32 },
+    /// The joint follows the axis, full deflection is `range` degrees from `center`
+    Position { center: f32, range: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisMapping {
+    pub id: u8,
+    pub mode: AxisMode,
+    /// Deflection ignored around the rest position of the stick, 0.0 to 1.0
+    pub deadzone: f32,
+    pub inverted: bool,
+}
+
+impl AxisMapping {
+    pub fn velocity(id: u8, max_speed: f32) -> AxisMapping {
+        AxisMapping::new(id, AxisMode::Velocity { max_speed })
+    }
+
+    pub fn position(id: u8, center: f32, range: f32) -> AxisMapping {
+        AxisMapping::new(id, AxisMode::Position { center, range })
+    }
+
+    fn new(id: u8, mode: AxisMode) -> AxisMapping {
+        AxisMapping {
+            id,
+            mode,
+            deadzone: 0.0,
+            inverted: false,
+        }
+    }
+
+    pub fn deadzone(mut self, deadzone: f32) -> Self {
+        self.deadzone = deadzone.clamp(0.0, 0.99);
+        self
+    }
+
+    pub fn inverted(mut self) -> Self {
+        self.inverted = !self.inverted;
+        self
+    }
+
+    /// Axis value with the deadzone removed and the rest rescaled to the full range
+    fn deflection(&self, axis: f32) -> f32 {
+        let axis = axis.clamp(-1.0, 1.0);
+        let magnitude = ((axis.abs() - self.deadzone) / (1.0 - self.deadzone)).max(0.0);
+        let deflection = magnitude.copysign(axis);
+        if self.inverted {
+            -deflection
+        } else {
+            deflection
+        }
+    }
+}
+
+/// Joint goals driven by axes, one mapping per axis in order
+///
+/// Goals stay within the joint limits of the servos, start it with
+/// [`crate::DynamixelDriver::start_teleop`] to pick those up together with present positions.
+#[derive(Debug, Clone)]
+pub struct Teleop {
+    mappings: Vec<AxisMapping>,
+    goals: HashMap<u8, f32>,
+    limits: HashMap<u8, (Option<f32>, Option<f32>)>,
+}
+
+impl Teleop {
+    pub fn new(mappings: Vec<AxisMapping>) -> Teleop {
+        Teleop {
+            mappings,
+            goals: HashMap::new(),
+            limits: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn ids(&self) -> impl Iterator<Item = u8> + '_ {
+        self.mappings.iter().map(|mapping| mapping.id)
+    }
+
+    /// Starting position in joint degrees, velocity mapped joints don't move before they are seeded
+    pub fn seed(&mut self, id: u8, position: f32) {
+        self.goals.insert(id, position);
+    }
+
+    pub fn set_limits(&mut self, id: u8, min: Option<f32>, max: Option<f32>) {
+        self.limits.insert(id, (min, max));
+    }
+
+    /// Goals in joint degrees for the current axis values, `dt` since the previous update
+    ///
+    /// Missing axes count as centered.
+    pub fn update(&mut self, axes: &[f32], dt: Duration) -> Vec<SyncCommandFloat> {
+        let mut goals = Vec::with_capacity(self.mappings.len());
+        for (index, mapping) in self.mappings.iter().enumerate() {
+            let deflection = mapping.deflection(axes.get(index).copied().unwrap_or(0.0));
+            let goal = match mapping.mode {
+                AxisMode::Velocity { max_speed } => match self.goals.get(&mapping.id) {
+                    Some(goal) => goal + deflection * max_speed * dt.as_secs_f32(),
+                    None => continue,
+                },
+                AxisMode::Position { center, range } => center + deflection * range,
+            };
+            let goal = match self.limits.get(&mapping.id) {
+                Some(&(min, max)) => goal
+                    .max(min.unwrap_or(f32::MIN))
+                    .min(max.unwrap_or(f32::MAX)),
+                None => goal,
+            };
+            self.goals.insert(mapping.id, goal);
+            goals.push(SyncCommandFloat::new(mapping.id, goal));
+        }
+        goals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadzone_rescales_remaining_deflection() {
+        let mapping = AxisMapping::position(1, 0.0, 1.0).deadzone(0.2);
+        assert_eq!(mapping.deflection(0.1), 0.0);
+        assert_eq!(mapping.deflection(-0.2), 0.0);
+        assert!((mapping.deflection(0.6) - 0.5).abs() < 1e-6);
+        assert_eq!(mapping.deflection(-1.5), -1.0);
+        assert_eq!(mapping.inverted().deflection(1.0), -1.0);
+    }
+
+    #[test]
+    fn velocity_axes_integrate_within_limits() {
+        let mut teleop = Teleop::new(vec![
+            AxisMapping::velocity(1, 90.0),
+            AxisMapping::position(2, 150.0, 60.0),
+            AxisMapping::velocity(3, 90.0),
+        ]);
+        teleop.seed(1, 0.0);
+        teleop.set_limits(1, Some(-45.0), Some(20.0));
+        let dt = Duration::from_millis(100);
+        let goals: Vec<(u8, f32)> = teleop
+            .update(&[1.0, -0.5], dt)
+            .into_iter()
+            .map(|goal| (goal.id(), goal.value()))
+            .collect();
+        // joint 3 isn't seeded yet
+        assert_eq!(goals.len(), 2);
+        assert!((goals[0].1 - 9.0).abs() < 1e-4);
+        assert_eq!(goals[1], (2, 120.0));
+        let goals = teleop.update(&[1.0], dt);
+        assert!((goals[0].value() - 18.0).abs() < 1e-4);
+        let goals = teleop.update(&[1.0], dt);
+        assert_eq!(goals[0].value(), 20.0);
+        assert_eq!(goals[1].value(), 150.0);
+    }
+}
@@ -0,0 +1,864 @@
+//! Protocol building blocks, a simulated bus and assertions on recorded bus traffic for
+//! writing fixtures in downstream tests.
+//!
+//! Only available with the `test-utils` feature.
+//!
+//! Servos of the [`SimulatedBus`] can move at a finite speed measured in tokio time, see
+//! [`SimulatedBusHandle::set_speed`]. The feature enables tokio's `test-util`, so tests
+//! running with `#[tokio::test(start_paused = true)]` skip every wait of the driver and
+//! play multi second trajectories in milliseconds.
+
+use async_trait::async_trait;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::time::Instant;
+
+pub use crate::instructions::{calc_checksum, Instruction};
+pub use crate::serial_driver::Status;
+
+use crate::instructions::{DynamixelDriverError, Result, StatusError, HARDWARE_ALERT};
+use crate::serial_driver::FramedDriver;
+
+const RANGE_ERROR: u8 = 1 << 3;
+const INSTRUCTION_ERROR: u8 = 1 << 6;
+
+// AX control table addresses, for the assertions below
+pub const ID: u8 = 3;
+pub const TORQUE_ENABLED: u8 = 24;
+pub const LED: u8 = 25;
+pub const GOAL_POSITION: u8 = 30;
+pub const MOVING_SPEED: u8 = 32;
+pub const TORQUE_LIMIT: u8 = 34;
+
+// X series control table addresses of Protocol 2.0 servos
+pub const PROTOCOL2_ID: u8 = 7;
+pub const PROTOCOL2_MAX_POSITION_LIMIT: u8 = 48;
+pub const PROTOCOL2_MIN_POSITION_LIMIT: u8 = 52;
+pub const PROTOCOL2_TORQUE_ENABLE: u8 = 64;
+pub const PROTOCOL2_HARDWARE_ERROR_STATUS: u8 = 70;
+pub const PROTOCOL2_GOAL_POSITION: u8 = 116;
+pub const PROTOCOL2_PRESENT_POSITION: u8 = 132;
+pub const PROTOCOL2_PRESENT_TEMPERATURE: u8 = 146;
+
+// registers the simulation acts on, placed differently in the AX and X series tables
+#[derive(Debug)]
+struct Layout {
+    size: usize,
+    position_width: usize,
+    goal_position: usize,
+    present_position: usize,
+    moving: usize,
+    torque_enable: usize,
+    registered_instruction: usize,
+    present_voltage: usize,
+    voltage_width: usize,
+    present_temperature: usize,
+    // model number and firmware version answering a Protocol 2.0 ping
+    ping: Option<usize>,
+    secondary_id: Option<usize>,
+    // indirect addresses, the data registers they map and the number of both
+    indirect: Option<(usize, usize, usize)>,
+}
+
+const AX_LAYOUT: Layout = Layout {
+    size: 50,
+    position_width: 2,
+    goal_position: GOAL_POSITION as usize,
+    present_position: 36,
+    moving: 46,
+    torque_enable: TORQUE_ENABLED as usize,
+    registered_instruction: 44,
+    present_voltage: 42,
+    voltage_width: 1,
+    present_temperature: 43,
+    ping: None,
+    secondary_id: None,
+    indirect: None,
+};
+
+const XM_LAYOUT: Layout = Layout {
+    size: 662,
+    position_width: 4,
+    goal_position: PROTOCOL2_GOAL_POSITION as usize,
+    present_position: PROTOCOL2_PRESENT_POSITION as usize,
+    moving: 122,
+    torque_enable: PROTOCOL2_TORQUE_ENABLE as usize,
+    registered_instruction: 69,
+    present_voltage: 144,
+    voltage_width: 2,
+    present_temperature: PROTOCOL2_PRESENT_TEMPERATURE as usize,
+    ping: Some(6),
+    secondary_id: Some(12),
+    indirect: Some((168, 224, 28)),
+};
+
+#[derive(Debug, Clone)]
+struct SimulatedServo {
+    table: Vec<u8>,
+    layout: &'static Layout,
+    error_flags: u8,
+    frozen: bool,
+    registered: Option<(usize, Vec<u8>)>,
+    // position steps per second, None reaches goals instantly
+    speed: Option<f32>,
+    // present position when the current goal was set
+    motion: Option<(u32, Instant)>,
+}
+
+impl SimulatedServo {
+    /// AX-12A with factory defaults resting at its center position
+    fn new(id: u8) -> SimulatedServo {
+        SimulatedServo::with_defaults(
+            &AX_LAYOUT,
+            &[
+                (0, &[12, 0]),         // model number
+                (2, &[24]),            // firmware
+                (3, &[id]),            // id
+                (4, &[1]),             // baud rate
+                (5, &[250]),           // return delay time
+                (8, &[0xFF, 0x03]),    // CCW angle limit
+                (11, &[70]),           // temperature limit
+                (12, &[60, 140]),      // voltage limits
+                (14, &[0xFF, 0x03]),   // max torque
+                (16, &[2]),            // status return level
+                (17, &[36, 36]),       // alarm LED and shutdown
+                (26, &[1, 1, 32, 32]), // compliance
+                (30, &[0x00, 0x02]),   // goal position
+                (34, &[0xFF, 0x03]),   // torque limit
+                (36, &[0x00, 0x02]),   // present position
+                (42, &[120, 30]),      // voltage and temperature
+                (48, &[32, 0]),        // punch
+            ],
+        )
+    }
+
+    /// XM430-W350 in position control mode with factory defaults resting at its center
+    fn protocol2(id: u8) -> SimulatedServo {
+        let mut servo = SimulatedServo::with_defaults(
+            &XM_LAYOUT,
+            &[
+                (0, &[0xFC, 0x03]),         // model number
+                (6, &[45]),                 // firmware
+                (7, &[id]),                 // id
+                (8, &[1]),                  // baud rate
+                (9, &[250]),                // return delay time
+                (11, &[3]),                 // operating mode
+                (12, &[255]),               // secondary id
+                (13, &[2]),                 // protocol type
+                (24, &[10, 0, 0, 0]),       // moving threshold
+                (31, &[80]),                // temperature limit
+                (32, &[160, 0, 95, 0]),     // voltage limits
+                (36, &[0x75, 0x03]),        // PWM limit
+                (38, &[0xA9, 0x04]),        // current limit
+                (44, &[200, 0, 0, 0]),      // velocity limit
+                (48, &[0xFF, 0x0F, 0, 0]),  // max position limit
+                (63, &[52]),                // shutdown
+                (68, &[2]),                 // status return level
+                (84, &[0x80, 0x02]),        // position P gain
+                (116, &[0x00, 0x08, 0, 0]), // goal position
+                (132, &[0x00, 0x08, 0, 0]), // present position
+                (144, &[120, 0]),           // voltage
+                (146, &[30]),               // temperature
+            ],
+        );
+        // indirect addresses point at their own data registers until remapped
+        for index in 0..XM_LAYOUT.indirect.map_or(0, |(_, _, count)| count) {
+            let addr = 168 + index * 2;
+            servo.table[addr..addr + 2].copy_from_slice(&(224 + index as u16).to_le_bytes());
+        }
+        servo
+    }
+
+    fn with_defaults(layout: &'static Layout, defaults: &[(usize, &[u8])]) -> SimulatedServo {
+        let mut table = vec![0; layout.size];
+        for (addr, bytes) in defaults {
+            table[*addr..*addr + bytes.len()].copy_from_slice(bytes);
+        }
+        SimulatedServo {
+            table,
+            layout,
+            error_flags: 0,
+            frozen: false,
+            registered: None,
+            speed: None,
+            motion: None,
+        }
+    }
+
+    fn is_protocol2(&self) -> bool {
+        self.layout.ping.is_some()
+    }
+
+    // ids above 252 disable the secondary id
+    fn secondary_id(&self) -> Option<u8> {
+        self.layout
+            .secondary_id
+            .map(|addr| self.table[addr])
+            .filter(|&id| id < 253)
+    }
+
+    fn position(&self, addr: usize) -> u32 {
+        self.table[addr..addr + self.layout.position_width]
+            .iter()
+            .rev()
+            .fold(0, |value, &byte| value << 8 | byte as u32)
+    }
+
+    fn set_position(&mut self, addr: usize, position: u32) {
+        let width = self.layout.position_width;
+        self.table[addr..addr + width].copy_from_slice(&position.to_le_bytes()[..width]);
+    }
+
+    // moves present position towards the goal for the time passed since the goal was set
+    fn advance(&mut self, now: Instant) {
+        let (Some((from, started)), Some(speed)) = (self.motion, self.speed) else {
+            return;
+        };
+        let goal = self.position(self.layout.goal_position);
+        let travelled = (speed * now.duration_since(started).as_secs_f32()) as u32;
+        let distance = goal.abs_diff(from);
+        let present = if travelled >= distance {
+            self.motion = None;
+            self.table[self.layout.moving] = 0;
+            goal
+        } else if goal > from {
+            from + travelled
+        } else {
+            from - travelled
+        };
+        self.set_position(self.layout.present_position, present);
+    }
+
+    // indirect data registers stand in for the register their indirect address points at
+    fn resolve(&self, addr: usize) -> usize {
+        match self.layout.indirect {
+            Some((addresses, data, count)) if (data..data + count).contains(&addr) => {
+                let slot = addresses + (addr - data) * 2;
+                u16::from_le_bytes([self.table[slot], self.table[slot + 1]]) as usize
+            }
+            _ => addr,
+        }
+    }
+
+    fn read(&self, addr: usize, len: usize) -> std::result::Result<Vec<u8>, u8> {
+        (addr..addr + len)
+            .map(|addr| self.table.get(self.resolve(addr)).copied())
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(RANGE_ERROR)
+    }
+
+    fn write(&mut self, addr: usize, data: &[u8]) -> std::result::Result<(), u8> {
+        let targets: Vec<usize> = (addr..addr + data.len())
+            .map(|addr| self.resolve(addr))
+            .collect();
+        if addr + data.len() > self.table.len()
+            || targets.iter().any(|&target| target >= self.table.len())
+        {
+            return Err(RANGE_ERROR);
+        }
+        for (&target, &byte) in targets.iter().zip(data) {
+            self.table[target] = byte;
+        }
+        let layout = self.layout;
+        let goal = layout.goal_position..layout.goal_position + layout.position_width;
+        if !self.frozen && targets.iter().any(|target| goal.contains(target)) {
+            if self.speed.is_some() {
+                self.motion = Some((self.position(layout.present_position), Instant::now()));
+                self.table[layout.moving] = 1;
+            } else {
+                // servos reach their goal instantly
+                self.table.copy_within(goal, layout.present_position);
+            }
+        }
+        Ok(())
+    }
+
+    // clears latched errors, RAM keeps its values apart from torque
+    fn reboot(&mut self) {
+        self.error_flags = 0;
+        if self.is_protocol2() {
+            self.table[PROTOCOL2_HARDWARE_ERROR_STATUS as usize] = 0;
+        }
+        self.table[self.layout.torque_enable] = 0;
+    }
+
+    fn action(&mut self) {
+        if let Some((addr, data)) = self.registered.take() {
+            self.table[self.layout.registered_instruction] = 0;
+            let _ = self.write(addr, &data);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct BusState {
+    servos: BTreeMap<u8, SimulatedServo>,
+}
+
+/// In memory bus of AX-12A servos implementing [`FramedDriver`], for exercising the
+/// full driver stack without hardware
+///
+/// Servos reach goal positions instantly unless frozen. Keep a [`SimulatedBusHandle`]
+/// to inspect registers and inject faults after handing the bus to the driver.
+///
+/// ```
+/// # async fn example() {
+/// use dynamixel_driver::test_utils::SimulatedBus;
+/// let bus = SimulatedBus::with_servos(&[1, 2]);
+/// let handle = bus.handle();
+/// let mut driver = dynamixel_driver::DynamixelDriver::with_driver(Box::new(bus));
+/// handle.set_temperature(1, 80);
+/// assert_eq!(driver.read_temperature(1).await.unwrap(), 80);
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct SimulatedBus {
+    state: Arc<Mutex<BusState>>,
+    responses: VecDeque<Result<Status>>,
+}
+
+impl SimulatedBus {
+    pub fn new() -> SimulatedBus {
+        SimulatedBus::default()
+    }
+
+    pub fn with_servos(ids: &[u8]) -> SimulatedBus {
+        let bus = SimulatedBus::new();
+        for &id in ids {
+            bus.handle().add_servo(id);
+        }
+        bus
+    }
+
+    pub fn handle(&self) -> SimulatedBusHandle {
+        SimulatedBusHandle {
+            state: self.state.clone(),
+        }
+    }
+
+    fn execute(&mut self, instruction: &Instruction) {
+        let (id, opcode) = (instruction.id(), instruction.opcode());
+        let protocol2 = instruction.is_protocol2();
+        // addresses and lengths take one byte in Protocol 1.0 and two in Protocol 2.0
+        let width = if protocol2 { 2 } else { 1 };
+        let params = instruction.params();
+        let params = params.as_ref();
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        for servo in state.servos.values_mut() {
+            servo.advance(now);
+        }
+        if opcode == 0x83 {
+            let Some(([addr, len], blocks)) = fields(params, width) else {
+                return;
+            };
+            for block in blocks.chunks_exact(len + 1) {
+                if let Some(servo) = state
+                    .servos
+                    .get_mut(&block[0])
+                    .filter(|servo| servo.is_protocol2() == protocol2)
+                {
+                    let _ = servo.write(addr, &block[1..]);
+                }
+            }
+            return;
+        }
+        if opcode == 0x93 {
+            let mut entries = params;
+            while let Some((&id, rest)) = entries.split_first() {
+                let Some(([addr, len], rest)) = fields(rest, width) else {
+                    break;
+                };
+                let (data, rest) = rest.split_at(len.min(rest.len()));
+                if let Some(servo) = state
+                    .servos
+                    .get_mut(&id)
+                    .filter(|servo| servo.is_protocol2())
+                {
+                    let _ = servo.write(addr, data);
+                }
+                entries = rest;
+            }
+            return;
+        }
+        let targets: Vec<u8> = if id == 0xFE {
+            state.servos.keys().copied().collect()
+        } else {
+            // servos sharing a secondary id act on its packets as well
+            let mut targets = vec![id];
+            targets.extend(
+                state
+                    .servos
+                    .iter()
+                    .filter(|(&servo_id, servo)| servo_id != id && servo.secondary_id() == Some(id))
+                    .map(|(&servo_id, _)| servo_id),
+            );
+            targets
+        };
+        for target in targets {
+            // servos don't understand packets of the other protocol generation
+            let Some(servo) = state
+                .servos
+                .get_mut(&target)
+                .filter(|servo| servo.is_protocol2() == protocol2)
+            else {
+                continue;
+            };
+            let result = match (opcode, fields(params, width), fields(params, width)) {
+                // Protocol 2.0 pings are answered with model number and firmware version
+                (0x01, _, _) => match servo.layout.ping {
+                    Some(firmware) => {
+                        Ok(vec![servo.table[0], servo.table[1], servo.table[firmware]])
+                    }
+                    None => Ok(vec![]),
+                },
+                (0x02, _, Some(([addr, len], []))) => servo.read(addr, len),
+                (0x03, Some(([addr], data)), _) => servo.write(addr, data).map(|_| vec![]),
+                (0x04, Some(([addr], data)), _) => {
+                    servo.registered = Some((addr, data.to_vec()));
+                    servo.table[servo.layout.registered_instruction] = 1;
+                    Ok(vec![])
+                }
+                (0x05, _, _) => {
+                    servo.action();
+                    Ok(vec![])
+                }
+                (0x08, _, _) if protocol2 => {
+                    servo.reboot();
+                    Ok(vec![])
+                }
+                _ => Err(INSTRUCTION_ERROR),
+            };
+            // broadcasts and packets to a secondary id are never answered
+            if id == 0xFE || target != id {
+                continue;
+            }
+            let response = match result {
+                // the alert alone doesn't stop the servo from answering
+                Ok(params) if servo.error_flags & !HARDWARE_ALERT == 0 => {
+                    Ok(Status::with_error(target, servo.error_flags, params))
+                }
+                Ok(_) => StatusError::check_error(servo.error_flags)
+                    .map(|_| unreachable!("error flags are set")),
+                Err(flags) => StatusError::check_error(flags | servo.error_flags)
+                    .map(|_| unreachable!("error flags are set")),
+            };
+            self.responses.push_back(response);
+        }
+    }
+}
+
+// leading little endian address or length fields of `width` bytes, followed by the rest
+fn fields<const N: usize>(params: &[u8], width: usize) -> Option<([usize; N], &[u8])> {
+    if params.len() < N * width {
+        return None;
+    }
+    let (head, rest) = params.split_at(N * width);
+    let mut fields = [0; N];
+    for (field, bytes) in fields.iter_mut().zip(head.chunks(width)) {
+        *field = bytes
+            .iter()
+            .rev()
+            .fold(0, |value, &byte| value << 8 | byte as usize);
+    }
+    Some((fields, rest))
+}
+
+#[async_trait]
+impl FramedDriver for SimulatedBus {
+    async fn send(&mut self, instruction: Instruction) -> Result<()> {
+        self.execute(&instruction);
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<Status> {
+        self.responses
+            .pop_front()
+            .unwrap_or(Err(DynamixelDriverError::Timeout))
+    }
+
+    async fn clear_io_buffers(&mut self) -> Result<()> {
+        self.responses.clear();
+        Ok(())
+    }
+}
+
+/// Inspects and manipulates servos of a [`SimulatedBus`] to drive fault scenarios
+#[derive(Debug, Clone)]
+pub struct SimulatedBusHandle {
+    state: Arc<Mutex<BusState>>,
+}
+
+impl SimulatedBusHandle {
+    pub fn add_servo(&self, id: u8) {
+        self.state
+            .lock()
+            .unwrap()
+            .servos
+            .insert(id, SimulatedServo::new(id));
+    }
+
+    /// Adds an XM430-W350 answering only Protocol 2.0 packets, with the X series control
+    /// table
+    pub fn add_protocol2_servo(&self, id: u8) {
+        self.state
+            .lock()
+            .unwrap()
+            .servos
+            .insert(id, SimulatedServo::protocol2(id));
+    }
+
+    /// Disconnects a servo so it stops answering
+    pub fn remove_servo(&self, id: u8) {
+        self.state.lock().unwrap().servos.remove(&id);
+    }
+
+    pub fn register(&self, id: u8, addr: u8) -> Option<u8> {
+        self.with_servo(id, |servo| servo.table[addr as usize])
+    }
+
+    pub fn register_u16(&self, id: u8, addr: u8) -> Option<u16> {
+        self.with_servo(id, |servo| {
+            u16::from_le_bytes([servo.table[addr as usize], servo.table[addr as usize + 1]])
+        })
+    }
+
+    /// Sets registers directly, including read only ones, without moving the servo
+    pub fn set_register(&self, id: u8, addr: u8, data: &[u8]) {
+        self.with_servo(id, |servo| {
+            servo.table[addr as usize..addr as usize + data.len()].copy_from_slice(data)
+        });
+    }
+
+    pub fn set_temperature(&self, id: u8, celsius: u8) {
+        self.with_servo(id, |servo| {
+            servo.table[servo.layout.present_temperature] = celsius
+        });
+    }
+
+    pub fn set_voltage(&self, id: u8, volts: f32) {
+        let raw = ((volts * 10.0).round() as u16).to_le_bytes();
+        self.with_servo(id, |servo| {
+            let (addr, width) = (servo.layout.present_voltage, servo.layout.voltage_width);
+            servo.table[addr..addr + width].copy_from_slice(&raw[..width]);
+        });
+    }
+
+    pub fn set_present_position(&self, id: u8, position: u16) {
+        self.with_servo(id, |servo| {
+            servo.set_position(servo.layout.present_position, position.into())
+        });
+    }
+
+    /// Error flags reported in every status packet, such as `1 << 5` for overload
+    pub fn set_error_flags(&self, id: u8, flags: u8) {
+        self.with_servo(id, |servo| servo.error_flags = flags);
+    }
+
+    /// Latches hardware error status bits of a Protocol 2.0 servo, such as `1 << 2` for
+    /// overheating, and flags the alert in every status until the servo reboots
+    pub fn set_hardware_error(&self, id: u8, status: u8) {
+        self.with_servo(id, |servo| {
+            servo.table[PROTOCOL2_HARDWARE_ERROR_STATUS as usize] = status;
+            servo.error_flags = match status {
+                0 => servo.error_flags & !HARDWARE_ALERT,
+                _ => servo.error_flags | HARDWARE_ALERT,
+            };
+        });
+    }
+
+    /// A frozen servo keeps its present position regardless of goal position, like a
+    /// blocked joint. It reports itself as moving while the goal isn't reached.
+    pub fn freeze_position(&self, id: u8, frozen: bool) {
+        self.with_servo(id, |servo| {
+            servo.frozen = frozen;
+            servo.table[servo.layout.moving] = frozen as u8;
+        });
+    }
+
+    /// Moves the servo to new goals at `steps_per_second` instead of instantly, in tokio
+    /// time so paused test clocks apply. It reports itself as moving until it arrives.
+    pub fn set_speed(&self, id: u8, steps_per_second: f32) {
+        self.with_servo(id, |servo| servo.speed = Some(steps_per_second));
+    }
+
+    fn with_servo<T>(&self, id: u8, action: impl FnOnce(&mut SimulatedServo) -> T) -> Option<T> {
+        self.state.lock().unwrap().servos.get_mut(&id).map(|servo| {
+            servo.advance(Instant::now());
+            action(servo)
+        })
+    }
+}
+
+/// Raw status packet as a servo would send it on the wire
+pub fn status_packet(id: u8, error: u8, params: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0xFF, 0xFF, id, params.len() as u8 + 2, error];
+    packet.extend_from_slice(params);
+    packet.push(calc_checksum(&packet[2..]));
+    packet
+}
+
+/// Instruction packet written to the bus, decoded from raw bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SentInstruction {
+    Ping {
+        id: u8,
+    },
+    Read {
+        id: u8,
+        addr: u8,
+        len: u8,
+    },
+    Write {
+        id: u8,
+        addr: u8,
+        data: Vec<u8>,
+    },
+    RegWrite {
+        id: u8,
+        addr: u8,
+        data: Vec<u8>,
+    },
+    Action {
+        id: u8,
+    },
+    SyncWrite {
+        addr: u8,
+        entries: Vec<(u8, Vec<u8>)>,
+    },
+    Other {
+        id: u8,
+        opcode: u8,
+        params: Vec<u8>,
+    },
+}
+
+impl SentInstruction {
+    /// None for anything that isn't a well formed instruction packet
+    pub fn parse(packet: &[u8]) -> Option<SentInstruction> {
+        let instruction = Instruction::from_bytes(packet).ok()?;
+        let (id, opcode, params) = (instruction.id(), instruction.opcode(), instruction.params());
+        let instruction = match (opcode, &*params) {
+            (0x01, []) => SentInstruction::Ping { id },
+            (0x02, &[addr, len]) => SentInstruction::Read { id, addr, len },
+            (0x03, [addr, data @ ..]) => SentInstruction::Write {
+                id,
+                addr: *addr,
+                data: data.to_vec(),
+            },
+            (0x04, [addr, data @ ..]) => SentInstruction::RegWrite {
+                id,
+                addr: *addr,
+                data: data.to_vec(),
+            },
+            (0x05, []) => SentInstruction::Action { id },
+            (0x83, &[addr, len, ref blocks @ ..])
+                if len > 0 && blocks.len() % (len as usize + 1) == 0 =>
+            {
+                SentInstruction::SyncWrite {
+                    addr,
+                    entries: blocks
+                        .chunks_exact(len as usize + 1)
+                        .map(|block| (block[0], block[1..].to_vec()))
+                        .collect(),
+                }
+            }
+            _ => SentInstruction::Other {
+                id,
+                opcode,
+                params: params.to_vec(),
+            },
+        };
+        Some(instruction)
+    }
+}
+
+/// Decodes every recorded packet, panicking on bytes that aren't an instruction
+#[track_caller]
+pub fn decode_traffic(traffic: &[Vec<u8>]) -> Vec<SentInstruction> {
+    traffic
+        .iter()
+        .map(|packet| match SentInstruction::parse(packet) {
+            Some(instruction) => instruction,
+            None => panic!("not an instruction packet: {:?}", packet),
+        })
+        .collect()
+}
+
+// little endian register value, as the driver writes them
+fn register_value(data: &[u8]) -> u32 {
+    data.iter()
+        .rev()
+        .fold(0, |value, &byte| value << 8 | byte as u32)
+}
+
+/// Asserts that `traffic` contains a sync write to `addr` setting exactly these values,
+/// in this order
+#[track_caller]
+pub fn assert_sync_write(traffic: &[Vec<u8>], addr: u8, expected: &[(u8, u32)]) {
+    let sync_writes: Vec<Vec<(u8, u32)>> = decode_traffic(traffic)
+        .into_iter()
+        .filter_map(|instruction| match instruction {
+            SentInstruction::SyncWrite {
+                addr: written,
+                entries,
+            } if written == addr => Some(
+                entries
+                    .iter()
+                    .map(|(id, data)| (*id, register_value(data)))
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .collect();
+    assert!(
+        sync_writes.iter().any(|values| values == expected),
+        "no sync write of {:?} to register {}, sync writes to it: {:?}",
+        expected,
+        addr,
+        sync_writes
+    );
+}
+
+/// Asserts that `traffic` contains a write of `value` to register `addr` of servo `id`
+#[track_caller]
+pub fn assert_write(traffic: &[Vec<u8>], id: u8, addr: u8, value: u32) {
+    let writes: Vec<u32> = decode_traffic(traffic)
+        .into_iter()
+        .filter_map(|instruction| match instruction {
+            SentInstruction::Write {
+                id: target,
+                addr: written,
+                data,
+            } if target == id && written == addr => Some(register_value(&data)),
+            _ => None,
+        })
+        .collect();
+    assert!(
+        writes.contains(&value),
+        "no write of {} to register {} of servo {}, writes to it: {:?}",
+        value,
+        addr,
+        id,
+        writes
+    );
+}
+
+/// Asserts that no write, registered write or sync write in `traffic` targets `addr`
+#[track_caller]
+pub fn assert_no_write(traffic: &[Vec<u8>], addr: u8) {
+    let written = decode_traffic(traffic)
+        .into_iter()
+        .find(|instruction| match instruction {
+            SentInstruction::Write { addr: written, .. }
+            | SentInstruction::RegWrite { addr: written, .. }
+            | SentInstruction::SyncWrite { addr: written, .. } => *written == addr,
+            _ => false,
+        });
+    if let Some(instruction) = written {
+        panic!("unexpected write to register {}: {:?}", addr, instruction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use std::time::Duration;
+    use tokio_util::codec::Decoder;
+
+    use crate::instructions::SyncCommand;
+    use crate::serial_driver::DynamixelProtocol;
+    use crate::{DriverEvent, DynamixelDriver};
+
+    #[tokio::test]
+    async fn simulated_bus_through_driver() {
+        let bus = SimulatedBus::with_servos(&[1, 2]);
+        let handle = bus.handle();
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        assert_eq!(driver.search_all().await.unwrap(), vec![1, 2]);
+        driver.write_position(1, 300).await.unwrap();
+        assert_eq!(driver.read_position(1).await.unwrap(), 300);
+        driver
+            .sync_write_position(vec![(1_u8, 100_u32), (2, 200)])
+            .await
+            .unwrap();
+        assert_eq!(handle.register_u16(2, 36), Some(200));
+
+        handle.set_temperature(2, 75);
+        assert_eq!(driver.read_temperature(2).await.unwrap(), 75);
+        handle.remove_servo(2);
+        assert!(matches!(
+            driver.read_temperature(2).await,
+            Err(DynamixelDriverError::Timeout)
+        ));
+    }
+
+    #[tokio::test]
+    async fn simulated_faults() {
+        let bus = SimulatedBus::with_servos(&[1]);
+        let handle = bus.handle();
+        let mut driver = DynamixelDriver::with_driver(Box::new(bus));
+        let mut events = driver.subscribe();
+        handle.set_error_flags(1, 1 << 5);
+        assert!(driver.ping(1).await.is_err());
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DriverEvent::Alarm { id: 1, error } if error.overload_error
+        ));
+        handle.set_error_flags(1, 0);
+
+        handle.freeze_position(1, true);
+        driver.set_motion_poll_interval(Duration::from_millis(1));
+        assert!(matches!(
+            driver
+                .wait_until_reached(1, 30.0, 1.0, Duration::from_millis(20))
+                .await,
+            Err(DynamixelDriverError::MotionTimeout(1))
+        ));
+        assert_eq!(handle.register_u16(1, 36), Some(512));
+    }
+
+    #[test]
+    fn status_packet_decodes() {
+        let packet = status_packet(1, 0, &[0x20]);
+        assert_eq!(packet, vec![0xFF, 0xFF, 0x01, 0x03, 0x00, 0x20, 0xDB]);
+        let mut buffer = BytesMut::from(&packet[..]);
+        let status = DynamixelProtocol::default()
+            .decode(&mut buffer)
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, Status::new(1, vec![0x20]));
+    }
+
+    #[test]
+    fn traffic_assertions_decode_packets() {
+        let traffic = vec![
+            Instruction::ping(1).serialize(),
+            Instruction::write_u16(2, GOAL_POSITION, 512).serialize(),
+            Instruction::sync_command(
+                GOAL_POSITION,
+                2,
+                vec![SyncCommand::new(1, 512), SyncCommand::new(2, 1023)],
+            )
+            .serialize(),
+        ];
+        assert_eq!(decode_traffic(&traffic)[0], SentInstruction::Ping { id: 1 });
+        assert_sync_write(&traffic, GOAL_POSITION, &[(1, 512), (2, 1023)]);
+        assert_write(&traffic, 2, GOAL_POSITION, 512);
+        assert_no_write(&traffic, TORQUE_ENABLED);
+        assert_eq!(SentInstruction::parse(&[0xFF, 0xFF, 1, 2, 1, 0]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "no sync write")]
+    fn sync_write_assertion_compares_all_entries() {
+        let traffic = vec![Instruction::sync_command(
+            GOAL_POSITION,
+            2,
+            vec![SyncCommand::new(1, 512), SyncCommand::new(2, 1023)],
+        )
+        .serialize()];
+        assert_sync_write(&traffic, GOAL_POSITION, &[(1, 512)]);
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Heating rate of a servo over the recent temperature readings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemperatureTrend {
+    pub id: u8,
+    /// Latest reading in °C
+    pub temperature: u8,
+    /// Fitted rate in °C per minute, negative while cooling down
+    pub degrees_per_minute: f32,
+}
+
+impl TemperatureTrend {
+    /// Time until `limit` °C is reached at the current rate, None while not heating up
+    pub fn time_to_limit(&self, limit: u8) -> Option<Duration> {
+        if self.temperature >= limit {
+            return Some(Duration::ZERO);
+        }
+        if self.degrees_per_minute <= 0.0 {
+            return None;
+        }
+        let minutes = (limit - self.temperature) as f32 / self.degrees_per_minute;
+        Some(Duration::from_secs_f32(minutes * 60.0))
+    }
+}
+
+/// Keeps temperature readings of each servo over a sliding window
+pub(crate) struct TemperatureMonitor {
+    window: Duration,
+    history: HashMap<u8, VecDeque<(Instant, u8)>>,
+}
+
+impl TemperatureMonitor {
+    pub fn new(window: Duration) -> TemperatureMonitor {
+        TemperatureMonitor {
+            window,
+            history: HashMap::new(),
+        }
+    }
+
+    pub fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    pub fn record(&mut self, id: u8, temperature: u8, now: Instant) {
+        let history = self.history.entry(id).or_default();
+        history.push_back((now, temperature));
+        while let Some((time, _)) = history.front() {
+            if now.duration_since(*time) <= self.window {
+                break;
+            }
+            history.pop_front();
+        }
+    }
+
+    /// Least squares fit over the window, None until readings span some time
+    pub fn trend(&self, id: u8) -> Option<TemperatureTrend> {
+        let history = self.history.get(&id)?;
+        let (start, _) = *history.front()?;
+        let (_, temperature) = *history.back()?;
+        let samples: Vec<(f32, f32)> = history
+            .iter()
+            .map(|(time, temperature)| {
+                let minutes = time.duration_since(start).as_secs_f32() / 60.0;
+                (minutes, *temperature as f32)
+            })
+            .collect();
+        let count = samples.len() as f32;
+        let mean_time = samples.iter().map(|(time, _)| time).sum::<f32>() / count;
+        let mean_temperature = samples.iter().map(|(_, value)| value).sum::<f32>() / count;
+        let variance: f32 = samples
+            .iter()
+            .map(|(time, _)| (time - mean_time).powi(2))
+            .sum();
+        if variance <= 0.0 {
+            return None;
+        }
+        let covariance: f32 = samples
+            .iter()
+            .map(|(time, value)| (time - mean_time) * (value - mean_temperature))
+            .sum();
+        Some(TemperatureTrend {
+            id,
+            temperature,
+            degrees_per_minute: covariance / variance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_heating_rate_over_window() {
+        let mut monitor = TemperatureMonitor::new(Duration::from_secs(120));
+        let start = Instant::now();
+        assert_eq!(monitor.trend(1), None);
+        monitor.record(1, 40, start);
+        assert_eq!(monitor.trend(1), None);
+        // old readings from a cooler period fall out of the window
+        monitor.record(1, 30, start + Duration::from_secs(60));
+        for (seconds, temperature) in [(300, 50), (330, 51), (360, 52)] {
+            monitor.record(1, temperature, start + Duration::from_secs(seconds));
+        }
+        let trend = monitor.trend(1).unwrap();
+        assert_eq!(trend.temperature, 52);
+        assert!((trend.degrees_per_minute - 2.0).abs() < 1e-3);
+        let eta = trend.time_to_limit(70).unwrap();
+        assert!((eta.as_secs_f32() - 540.0).abs() < 0.5);
+        assert_eq!(trend.time_to_limit(50), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn cooling_servo_never_reaches_limit() {
+        let trend = TemperatureTrend {
+            id: 1,
+            temperature: 60,
+            degrees_per_minute: -0.5,
+        };
+        assert_eq!(trend.time_to_limit(70), None);
+    }
+}
@@ -0,0 +1,82 @@
+//! Joint names and limits from URDF robot descriptions, so the model used for
+//! visualization also constrains the driver.
+
+use crate::instructions::{DynamixelDriverError, Result};
+
+/// Limits of a revolute joint in degrees
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrdfJoint {
+    pub name: String,
+    pub lower_degrees: f32,
+    pub upper_degrees: f32,
+}
+
+/// Extracts revolute joints with limits, continuous and fixed joints are skipped
+pub fn parse_joints(urdf: &str) -> Result<Vec<UrdfJoint>> {
+    let document = roxmltree::Document::parse(urdf)
+        .map_err(|error| DynamixelDriverError::InvalidConfig(format!("invalid URDF: {}", error)))?;
+    let mut joints = vec![];
+    for joint in document
+        .descendants()
+        .filter(|node| node.has_tag_name("joint"))
+        .filter(|node| node.attribute("type") == Some("revolute"))
+    {
+        let name = joint.attribute("name").ok_or_else(|| {
+            DynamixelDriverError::InvalidConfig("URDF joint without a name".to_owned())
+        })?;
+        let limit = joint
+            .children()
+            .find(|node| node.has_tag_name("limit"))
+            .ok_or_else(|| {
+                DynamixelDriverError::InvalidConfig(format!("URDF joint {:?} has no limit", name))
+            })?;
+        let bound = |attribute: &str| -> Result<f32> {
+            limit
+                .attribute(attribute)
+                .unwrap_or("0")
+                .parse::<f32>()
+                .map(f32::to_degrees)
+                .map_err(|_| {
+                    DynamixelDriverError::InvalidConfig(format!(
+                        "invalid {} limit of URDF joint {:?}",
+                        attribute, name
+                    ))
+                })
+        };
+        joints.push(UrdfJoint {
+            name: name.to_owned(),
+            lower_degrees: bound("lower")?,
+            upper_degrees: bound("upper")?,
+        });
+    }
+    Ok(joints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_revolute_joints() {
+        let urdf = r#"<?xml version="1.0"?>
+            <robot name="leg">
+              <link name="base"/>
+              <link name="thigh"/>
+              <joint name="hip" type="revolute">
+                <parent link="base"/>
+                <child link="thigh"/>
+                <limit lower="-1.5707963" upper="0.7853982" effort="1.5" velocity="5.0"/>
+              </joint>
+              <joint name="wheel" type="continuous">
+                <parent link="base"/>
+                <child link="thigh"/>
+              </joint>
+            </robot>"#;
+        let joints = parse_joints(urdf).unwrap();
+        assert_eq!(joints.len(), 1);
+        assert_eq!(joints[0].name, "hip");
+        assert!((joints[0].lower_degrees + 90.0).abs() < 1e-3);
+        assert!((joints[0].upper_degrees - 45.0).abs() < 1e-3);
+        assert!(parse_joints("<robot><joint").is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::{BTreeMap, HashSet};
+
+/// Reported when a servo's input voltage drops below the brownout threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct Brownout {
+    pub id: u8,
+    pub voltage: f32,
+    pub threshold: f32,
+}
+
+/// Tracks voltage readings to spot battery sag or undersized wiring under load
+pub(crate) struct VoltageMonitor {
+    threshold: f32,
+    below_threshold: HashSet<u8>,
+    // lowest reading per servo in tenths of a volt as reported by the servo
+    min_voltages: BTreeMap<u8, u8>,
+}
+
+impl VoltageMonitor {
+    pub fn new(threshold: f32) -> VoltageMonitor {
+        VoltageMonitor {
+            threshold,
+            below_threshold: HashSet::new(),
+            min_voltages: BTreeMap::new(),
+        }
+    }
+
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// Records a raw voltage reading, returning a brownout once per dip below the threshold
+    pub fn record(&mut self, id: u8, raw_voltage: u8) -> Option<Brownout> {
+        let min = self.min_voltages.entry(id).or_insert(raw_voltage);
+        *min = (*min).min(raw_voltage);
+        let voltage = raw_voltage as f32 / 10.0;
+        if voltage >= self.threshold {
+            self.below_threshold.remove(&id);
+            return None;
+        }
+        if !self.below_threshold.insert(id) {
+            return None;
+        }
+        Some(Brownout {
+            id,
+            voltage,
+            threshold: self.threshold,
+        })
+    }
+
+    pub fn min_voltages(&self) -> &BTreeMap<u8, u8> {
+        &self.min_voltages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_each_dip_once_and_tracks_minimum() {
+        let mut monitor = VoltageMonitor::new(10.0);
+        let mut events: Vec<Brownout> = [120, 95, 92, 110, 98]
+            .into_iter()
+            .filter_map(|raw| monitor.record(1, raw))
+            .collect();
+        events.extend(monitor.record(2, 121));
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].id, 1);
+        assert!((events[0].voltage - 9.5).abs() < 1e-6);
+        assert!((events[1].voltage - 9.8).abs() < 1e-6);
+        assert_eq!(monitor.min_voltages()[&1], 92);
+        assert_eq!(monitor.min_voltages()[&2], 121);
+    }
+}